@@ -158,6 +158,19 @@ export declare class PocketOption {
    * ```
    */
   static newWithUrl(ssid: string, url: string): Promise<PocketOption>
+  /**
+   * Creates a new PocketOption client with the ssid, region and configuration stored in a profile.
+   *
+   * # Arguments
+   * * `name` - Name of the profile
+   * * `vault` - Vault with the profile, by default the vault from the environment is used
+   *
+   * # Examples
+   * ```javascript
+   * const client = PocketOption.fromProfile("demo", new Vault(null, "my passphrase"));
+   * ```
+   */
+  static fromProfile(name: string, vault?: Vault | undefined | null): PocketOption
   /**
    * Checks if the current account is a demo account.
    *
@@ -188,6 +201,23 @@ export declare class PocketOption {
    * ```
    */
   isDemo(): Promise<boolean>
+  /**
+   * Registers a callback for a lifecycle event of the connection.
+   * Several callbacks can be registered for the same event, a failing callback is logged and doesn't affect the others.
+   *
+   * # Arguments
+   * * `event` - One of `connect`, `authenticated`, `disconnect`, `reconnect`, `error` or `close`
+   * * `callback` - Called as `callback(event, detail)`, `detail` is the reason of a disconnection or error and `null` otherwise
+   *
+   * # Examples
+   * ```javascript
+   * client.on("disconnect", (event, reason) => console.log(`Disconnected: ${reason}`));
+   * client.on("reconnect", async () => {
+   *     console.log(`Balance after reconnecting: ${await client.balance()}`);
+   * });
+   * ```
+   */
+  on(event: string, callback: (event: string, detail: string | null) => void | Promise<void>): void
   /**
    * Executes a buy (CALL) order for a specified asset.
    *
@@ -558,6 +588,36 @@ export declare class PocketOption {
    * ```
    */
   createRawIterator(message: string, validator: Validator, timeout?: number | undefined | null): Promise<RawStreamIterator>
+  /**
+   * Retrieves the connection history of every server the client tried.
+   *
+   * # Returns
+   * An object mapping each server url to its successes, failures, last failure,
+   * handshake latency and the moment until which it's skipped
+   *
+   * # Examples
+   * ```javascript
+   * const health = await client.serverHealth();
+   * for (const [url, server] of Object.entries(health)) {
+   *     console.log(`${url}: ${server.successes} ok, ${server.failures} failed`);
+   * }
+   * ```
+   */
+  serverHealth(): Promise<any>
+  /**
+   * Reconnects the client with another session id, for example to move between the demo and the real account.
+   * The balance and deals of the previous account are cleared while subscriptions keep working.
+   *
+   * # Arguments
+   * * `ssid` - Session id of the new account, the previous one is restored if the server rejects it
+   *
+   * # Examples
+   * ```javascript
+   * await client.switchAccount(realSsid);
+   * console.log(await client.isDemo());
+   * ```
+   */
+  switchAccount(ssid: string): Promise<void>
 }
 /**
  * A validator for WebSocket messages that provides various matching strategies.
@@ -963,3 +1023,47 @@ export declare class Logger {
    */
   error(message: string): void
 }
+/**
+ * Encrypted file with named profiles, each profile stores an ssid, a region and configuration overrides.
+ *
+ * # Examples
+ * ```javascript
+ * const vault = new Vault(null, "my passphrase");
+ * vault.setProfile("demo", "your-ssid-here", "EUROPE");
+ * vault.save();
+ * const client = PocketOption.fromProfile("demo", vault);
+ * ```
+ */
+export declare class Vault {
+  /**
+   * Opens the vault at `path` or creates an empty one.
+   *
+   * # Arguments
+   * * `path` - Path of the vault, by default `POCKET_OPTION_VAULT` or `~/.pocketoption/vault.json`
+   * * `passphrase` - Passphrase that unlocks the vault
+   * * `keyFile` - File used as key instead of a passphrase, without both the key is read from
+   *   `POCKET_OPTION_VAULT_PASSPHRASE` or `POCKET_OPTION_VAULT_KEY_FILE`
+   */
+  constructor(path?: string | undefined | null, passphrase?: string | undefined | null, keyFile?: string | undefined | null)
+  path(): string
+  /** Names of the stored profiles */
+  profiles(): Array<string>
+  /** Profile `name` with its ssid, region, marketConnections and config overrides */
+  profile(name: string): any
+  /**
+   * Adds or replaces the profile `name`, changes are only written by `save`.
+   *
+   * # Arguments
+   * * `ssid` - Session id of the account, it's validated before being stored
+   * * `region` - Name of the only region the client connects to, like "EUROPE"
+   * * `marketConnections` - Number of dedicated market data connections
+   * * `config` - Configuration values stored with the profile, like `{ reconnect_time: 10 }`
+   */
+  setProfile(name: string, ssid: string, region?: string | undefined | null, marketConnections?: number | undefined | null, config?: any | undefined | null): void
+  /** Removes the profile `name`, returns false if it didn't exist */
+  removeProfile(name: string): boolean
+  /** Encrypts the vault with a new passphrase or key file from the next `save` on */
+  changeKey(passphrase?: string | undefined | null, keyFile?: string | undefined | null): void
+  /** Encrypts and writes the vault to its path */
+  save(): void
+}
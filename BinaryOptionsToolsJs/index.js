@@ -310,12 +310,13 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { StreamIterator, RawStreamIterator, PocketOption, Validator, startTracing, StreamLogsLayer, StreamLogsIterator, LogBuilder, Logger } = nativeBinding
+const { StreamIterator, RawStreamIterator, PocketOption, Validator, Vault, startTracing, StreamLogsLayer, StreamLogsIterator, LogBuilder, Logger } = nativeBinding
 
 module.exports.StreamIterator = StreamIterator
 module.exports.RawStreamIterator = RawStreamIterator
 module.exports.PocketOption = PocketOption
 module.exports.Validator = Validator
+module.exports.Vault = Vault
 module.exports.startTracing = startTracing
 module.exports.StreamLogsLayer = StreamLogsLayer
 module.exports.StreamLogsIterator = StreamLogsIterator
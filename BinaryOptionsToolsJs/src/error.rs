@@ -1,5 +1,6 @@
 use binary_options_tools::{
-    error::BinaryOptionsToolsError, pocketoption::error::PocketOptionError,
+    error::BinaryOptionsToolsError,
+    pocketoption::error::{PocketOptionError, SsidError, VaultError},
 };
 use napi::Error;
 use thiserror::Error;
@@ -25,6 +26,18 @@ pub enum BinaryErrorJs {
     InvalidRegexError(#[from] regex::Error),
 }
 
+impl From<SsidError> for BinaryErrorJs {
+    fn from(value: SsidError) -> Self {
+        Self::PocketOptionError(value.into())
+    }
+}
+
+impl From<VaultError> for BinaryErrorJs {
+    fn from(value: VaultError) -> Self {
+        Self::PocketOptionError(value.into())
+    }
+}
+
 impl From<BinaryErrorJs> for Error {
     fn from(value: BinaryErrorJs) -> Self {
         Error::from_reason(value.to_string())
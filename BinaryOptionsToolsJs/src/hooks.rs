@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use binary_options_tools::{
+    error::{BinaryOptionsResult, BinaryOptionsToolsError},
+    pocketoption::{parser::message::WebSocketMessage, types::data::PocketData},
+    reimports::{Data, LifecycleEvent, LifecycleHook, SenderMessage},
+};
+use napi::{
+    bindgen_prelude::Promise,
+    threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction},
+    Env, JsFunction, JsUnknown, Result,
+};
+
+use crate::error::BinaryErrorJs;
+
+type PocketEvents = Data<PocketData, WebSocketMessage>;
+
+/// Wraps the user callback so it's called as `callback(event, detail)`, always returns a promise
+/// and reports synchronous exceptions as rejections.
+const WRAPPER: &str =
+    "(callback) => async (_err, event, detail) => { await callback(event, detail); }";
+
+/// Javascript function registered with `PocketOption.on`
+pub struct JsLifecycleHook {
+    event: String,
+    callback: ThreadsafeFunction<(String, Option<String>), ErrorStrategy::CalleeHandled>,
+}
+
+impl JsLifecycleHook {
+    pub fn new(env: &Env, event: String, callback: JsFunction) -> Result<Self> {
+        if !LifecycleEvent::NAMES.contains(&event.as_str()) {
+            return Err(BinaryErrorJs::NotAllowed(format!(
+                "Unknown event '{event}', expected one of {:?}",
+                LifecycleEvent::NAMES
+            ))
+            .into());
+        }
+        let wrapper: JsFunction = env.run_script(WRAPPER)?;
+        let wrapped: JsFunction = wrapper.call(None, &[callback])?.try_into()?;
+        let mut callback = wrapped.create_threadsafe_function(
+            0,
+            |ctx: ThreadSafeCallContext<(String, Option<String>)>| {
+                let (event, detail) = ctx.value;
+                let detail: JsUnknown = match detail {
+                    Some(detail) => ctx.env.create_string(&detail)?.into_unknown(),
+                    None => ctx.env.get_null()?.into_unknown(),
+                };
+                Ok(vec![ctx.env.create_string(&event)?.into_unknown(), detail])
+            },
+        )?;
+        // Hooks must not keep the node process alive
+        callback.unref(env)?;
+        Ok(Self { event, callback })
+    }
+
+    async fn call(&self, event: LifecycleEvent) -> BinaryOptionsResult<()> {
+        if event.name() != self.event {
+            return Ok(());
+        }
+        let args = (event.name().to_string(), event.detail().map(String::from));
+        let promise: Promise<()> = self
+            .callback
+            .call_async(Ok(args))
+            .await
+            .map_err(hook_error)?;
+        promise.await.map_err(hook_error)
+    }
+}
+
+#[async_trait]
+impl LifecycleHook<PocketData, WebSocketMessage> for JsLifecycleHook {
+    async fn on_connect(&self, _data: &PocketEvents) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Connect).await
+    }
+
+    async fn on_authenticated(&self, _data: &PocketEvents) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Authenticated).await
+    }
+
+    async fn on_disconnect(&self, _data: &PocketEvents, reason: &str) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Disconnect {
+            reason: reason.to_string(),
+        })
+        .await
+    }
+
+    async fn on_reconnect(
+        &self,
+        _data: &PocketEvents,
+        _sender: &SenderMessage,
+    ) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Reconnect).await
+    }
+
+    async fn on_error(&self, _data: &PocketEvents, error: &str) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Error {
+            error: error.to_string(),
+        })
+        .await
+    }
+
+    async fn on_close(&self, _data: &PocketEvents) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Close).await
+    }
+}
+
+fn hook_error(error: napi::Error) -> BinaryOptionsToolsError {
+    BinaryOptionsToolsError::HookError(error.to_string())
+}
@@ -1,9 +1,12 @@
+mod error;
+mod hooks;
+mod logs;
 mod pocketoption;
 mod runtime;
-mod error;
 mod validator;
-mod logs;
+mod vault;
 
+pub use logs::{start_tracing, LogBuilder, Logger, StreamLogsIterator, StreamLogsLayer};
 pub use pocketoption::PocketOption;
 pub use validator::Validator;
-pub use logs::{start_tracing, LogBuilder, Logger, StreamLogsLayer, StreamLogsIterator};
+pub use vault::Vault;
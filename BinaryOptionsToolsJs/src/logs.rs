@@ -2,7 +2,7 @@ use std::{fs::OpenOptions, io::Write, sync::Arc};
 
 use binary_options_tools::{
     error::BinaryOptionsResult,
-    stream::{stream_logs_layer, RecieverStream},
+    stream::{stream_logs_layer, RecieverStream, Redacted},
 };
 use chrono::Duration;
 use futures_util::{
@@ -67,19 +67,23 @@ pub fn start_tracing(
         .with(
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(error_logs)
+                .with_writer(Redacted::new(error_logs))
                 .with_filter(LevelFilter::WARN),
         )
         .with(
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(logs)
+                .with_writer(Redacted::new(logs))
                 .with_filter(level),
         );
 
     if terminal {
         subscriber
-            .with(fmt::Layer::default().with_filter(level))
+            .with(
+                fmt::Layer::default()
+                    .with_writer(Redacted::new(std::io::stdout))
+                    .with_filter(level),
+            )
             .init();
     } else {
         subscriber.init()
@@ -283,7 +287,7 @@ impl LogBuilder {
             .map_err(|e| Error::from_reason(e.to_string()))?;
         let layer = fmt::layer()
             .with_ansi(false)
-            .with_writer(logs)
+            .with_writer(Redacted::new(logs))
             .with_filter(level.parse().unwrap_or(LevelFilter::DEBUG))
             .boxed();
         self.layers.push(layer);
@@ -308,6 +312,7 @@ impl LogBuilder {
     #[napi]
     pub fn terminal(&mut self, level: String) {
         let layer = fmt::Layer::default()
+            .with_writer(Redacted::new(std::io::stdout))
             .with_filter(level.parse().unwrap_or(LevelFilter::DEBUG))
             .boxed();
         self.layers.push(layer);
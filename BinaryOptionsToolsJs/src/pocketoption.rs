@@ -1,5 +1,5 @@
-use binary_options_tools::error::BinaryOptionsResult;
-use binary_options_tools::pocketoption::error::PocketResult;
+use binary_options_tools::error::{BinaryOptionsResult, BinaryOptionsToolsError};
+use binary_options_tools::pocketoption::error::{PocketOptionError, PocketResult};
 use binary_options_tools::pocketoption::pocket_client::PocketOption as Pocket;
 use binary_options_tools::pocketoption::types::base::RawWebsocketMessage;
 use binary_options_tools::pocketoption::types::update::DataCandle;
@@ -13,12 +13,15 @@ use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
+use tracing::warn;
 use url::Url;
 use uuid::Uuid;
 
 use crate::error::BinaryErrorJs;
+use crate::hooks::JsLifecycleHook;
 use crate::runtime::get_runtime;
 use crate::validator::Validator;
+use crate::vault::Vault;
 
 /// Iterator for receiving processed WebSocket messages.
 /// Provides asynchronous iteration over parsed messages from the server.
@@ -112,6 +115,27 @@ impl PocketOption {
         Ok(Self { client })
     }
 
+    /// Creates a new PocketOption client with the ssid, region and configuration stored in a profile.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the profile
+    /// * `vault` - Vault with the profile, by default the vault from the environment is used
+    ///
+    /// # Examples
+    /// ```javascript
+    /// const client = PocketOption.fromProfile("demo", new Vault(null, "my passphrase"));
+    /// ```
+    #[napi(factory)]
+    pub fn from_profile(name: String, vault: Option<&Vault>) -> Result<Self> {
+        let runtime = get_runtime()?;
+        let client = match vault {
+            Some(vault) => runtime.block_on(Pocket::from_vault_profile(vault.inner(), name)),
+            None => runtime.block_on(Pocket::from_profile(name)),
+        }
+        .map_err(BinaryErrorJs::from)?;
+        Ok(Self { client })
+    }
+
     /// Checks if the current account is a demo account.
     ///
     /// # Returns
@@ -144,6 +168,29 @@ impl PocketOption {
         self.client.is_demo().await
     }
 
+    /// Registers a callback for a lifecycle event of the connection.
+    /// Several callbacks can be registered for the same event, a failing callback is logged and doesn't affect the others.
+    ///
+    /// # Arguments
+    /// * `event` - One of `connect`, `authenticated`, `disconnect`, `reconnect`, `error` or `close`
+    /// * `callback` - Called as `callback(event, detail)`, `detail` is the reason of a disconnection or error and `null` otherwise
+    ///
+    /// # Examples
+    /// ```javascript
+    /// client.on("disconnect", (event, reason) => console.log(`Disconnected: ${reason}`));
+    /// client.on("reconnect", async () => {
+    ///     console.log(`Balance after reconnecting: ${await client.balance()}`);
+    /// });
+    /// ```
+    #[napi(
+        ts_args_type = "event: string, callback: (event: string, detail: string | null) => void | Promise<void>"
+    )]
+    pub fn on(&self, env: Env, event: String, callback: JsFunction) -> Result<()> {
+        let hook = JsLifecycleHook::new(&env, event, callback)?;
+        self.client.add_hooks(Arc::new(hook));
+        Ok(())
+    }
+
     /// Executes a buy (CALL) order for a specified asset.
     ///
     /// # Arguments
@@ -699,6 +746,44 @@ impl PocketOption {
         let time = self.client.get_server_time().await;
         time.timestamp()
     }
+
+    /// Retrieves the connection history of every server the client tried.
+    ///
+    /// # Returns
+    /// An object mapping each server url to its successes, failures, last failure,
+    /// handshake latency and the moment until which it's skipped
+    ///
+    /// # Examples
+    /// ```javascript
+    /// const health = await client.serverHealth();
+    /// for (const [url, server] of Object.entries(health)) {
+    ///     console.log(`${url}: ${server.successes} ok, ${server.failures} failed`);
+    /// }
+    /// ```
+    #[napi]
+    pub async fn server_health(&self) -> Result<Value> {
+        let res = self.client.server_health().await;
+        serde_json::to_value(&res).map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    /// Reconnects the client with another session id, for example to move between the demo and the real account.
+    /// The balance and deals of the previous account are cleared while subscriptions keep working.
+    ///
+    /// # Arguments
+    /// * `ssid` - Session id of the new account, the previous one is restored if the server rejects it
+    ///
+    /// # Examples
+    /// ```javascript
+    /// await client.switchAccount(realSsid);
+    /// console.log(await client.isDemo());
+    /// ```
+    #[napi]
+    pub async fn switch_account(&self, ssid: String) -> Result<()> {
+        self.client
+            .switch_account(ssid)
+            .await
+            .map_err(|e| Error::from_reason(e.to_string()))
+    }
 }
 
 #[napi]
@@ -730,12 +815,20 @@ impl StreamIterator {
     #[napi]
     pub async fn next(&self) -> Result<Option<Value>> {
         let mut stream = self.stream.lock().await;
-        match stream.next().await {
-            Some(Ok(candle)) => serde_json::to_value(&candle)
-                .map(Some)
-                .map_err(|e| Error::from_reason(e.to_string())),
-            Some(Err(e)) => Err(Error::from_reason(e.to_string())),
-            None => Ok(None),
+        loop {
+            match stream.next().await {
+                Some(Ok(candle)) => {
+                    return serde_json::to_value(&candle)
+                        .map(Some)
+                        .map_err(|e| Error::from_reason(e.to_string()))
+                }
+                // Lagging is not fatal, the stream keeps working after reporting it
+                Some(Err(PocketOptionError::BinaryOptionsToolsError(
+                    BinaryOptionsToolsError::ChannelLagged(skipped),
+                ))) => warn!("Stream lagged behind, {skipped} messages were dropped"),
+                Some(Err(e)) => return Err(Error::from_reason(e.to_string())),
+                None => return Ok(None),
+            }
         }
     }
 }
@@ -780,10 +873,15 @@ impl RawStreamIterator {
     #[napi]
     pub async fn next(&self) -> Result<Option<String>> {
         let mut stream = self.stream.lock().await;
-        match stream.next().await {
-            Some(Ok(msg)) => Ok(Some(msg.to_string())),
-            Some(Err(e)) => Err(Error::from_reason(e.to_string())),
-            None => Ok(None),
+        loop {
+            match stream.next().await {
+                Some(Ok(msg)) => return Ok(Some(msg.to_string())),
+                Some(Err(BinaryOptionsToolsError::ChannelLagged(skipped))) => {
+                    warn!("Raw stream lagged behind, {skipped} messages were dropped")
+                }
+                Some(Err(e)) => return Err(Error::from_reason(e.to_string())),
+                None => return Ok(None),
+            }
         }
     }
 }
@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use binary_options_tools::pocketoption::vault::{
+    ConfigOverrides, Profile, Vault as InnerVault, VaultKey,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde_json::Value;
+
+use crate::error::BinaryErrorJs;
+
+/// Encrypted file with named profiles, each profile stores an ssid, a region and configuration overrides.
+///
+/// # Examples
+/// ```javascript
+/// const vault = new Vault(null, "my passphrase");
+/// vault.setProfile("demo", "your-ssid-here", "EUROPE");
+/// vault.save();
+/// const client = PocketOption.fromProfile("demo", vault);
+/// ```
+#[napi]
+pub struct Vault {
+    vault: InnerVault,
+}
+
+fn vault_key(passphrase: Option<String>, key_file: Option<String>) -> Result<VaultKey> {
+    match (passphrase, key_file) {
+        (Some(passphrase), None) => Ok(VaultKey::Passphrase(passphrase)),
+        (None, Some(path)) => Ok(VaultKey::KeyFile(PathBuf::from(path))),
+        (None, None) => Ok(VaultKey::from_env().map_err(BinaryErrorJs::from)?),
+        (Some(_), Some(_)) => Err(Error::from_reason(
+            "Use either a passphrase or a key file, not both",
+        )),
+    }
+}
+
+impl Vault {
+    pub fn inner(&self) -> &InnerVault {
+        &self.vault
+    }
+}
+
+#[napi]
+impl Vault {
+    /// Opens the vault at `path` or creates an empty one.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the vault, by default `POCKET_OPTION_VAULT` or `~/.pocketoption/vault.json`
+    /// * `passphrase` - Passphrase that unlocks the vault
+    /// * `keyFile` - File used as key instead of a passphrase, without both the key is read from
+    ///   `POCKET_OPTION_VAULT_PASSPHRASE` or `POCKET_OPTION_VAULT_KEY_FILE`
+    #[napi(constructor)]
+    pub fn new(
+        path: Option<String>,
+        passphrase: Option<String>,
+        key_file: Option<String>,
+    ) -> Result<Self> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => InnerVault::default_path().map_err(BinaryErrorJs::from)?,
+        };
+        let key = vault_key(passphrase, key_file)?;
+        let vault = InnerVault::open_or_create(path, &key).map_err(BinaryErrorJs::from)?;
+        Ok(Self { vault })
+    }
+
+    #[napi]
+    pub fn path(&self) -> String {
+        self.vault.path().display().to_string()
+    }
+
+    /// Names of the stored profiles
+    #[napi]
+    pub fn profiles(&self) -> Vec<String> {
+        self.vault.profiles()
+    }
+
+    /// Profile `name` with its ssid, region, marketConnections and config overrides
+    #[napi]
+    pub fn profile(&self, name: String) -> Result<Value> {
+        let profile = self.vault.profile(&name).map_err(BinaryErrorJs::from)?;
+        Ok(serde_json::to_value(profile).map_err(BinaryErrorJs::from)?)
+    }
+
+    /// Adds or replaces the profile `name`, changes are only written by `save`.
+    ///
+    /// # Arguments
+    /// * `ssid` - Session id of the account, it's validated before being stored
+    /// * `region` - Name of the only region the client connects to, like "EUROPE"
+    /// * `marketConnections` - Number of dedicated market data connections
+    /// * `config` - Configuration values stored with the profile, like `{ reconnect_time: 10 }`
+    #[napi]
+    pub fn set_profile(
+        &mut self,
+        name: String,
+        ssid: String,
+        region: Option<String>,
+        market_connections: Option<u32>,
+        config: Option<Value>,
+    ) -> Result<()> {
+        let mut profile = Profile::new(ssid)
+            .map_err(BinaryErrorJs::from)?
+            .market_connections(market_connections.unwrap_or_default() as usize);
+        if let Some(region) = region {
+            profile = profile.region(region);
+        }
+        if let Some(config) = config {
+            let config: ConfigOverrides =
+                serde_json::from_value(config).map_err(BinaryErrorJs::from)?;
+            profile = profile.config(config);
+        }
+        self.vault.set_profile(name, profile);
+        Ok(())
+    }
+
+    /// Removes the profile `name`, returns false if it didn't exist
+    #[napi]
+    pub fn remove_profile(&mut self, name: String) -> bool {
+        self.vault.remove_profile(&name).is_some()
+    }
+
+    /// Encrypts the vault with a new passphrase or key file from the next `save` on
+    #[napi]
+    pub fn change_key(
+        &mut self,
+        passphrase: Option<String>,
+        key_file: Option<String>,
+    ) -> Result<()> {
+        if passphrase.is_none() && key_file.is_none() {
+            return Err(Error::from_reason("Missing the new key"));
+        }
+        let key = vault_key(passphrase, key_file)?;
+        self.vault.change_key(&key).map_err(BinaryErrorJs::from)?;
+        Ok(())
+    }
+
+    /// Encrypts and writes the vault to its path
+    #[napi]
+    pub fn save(&self) -> Result<()> {
+        self.vault.save().map_err(BinaryErrorJs::from)?;
+        Ok(())
+    }
+}
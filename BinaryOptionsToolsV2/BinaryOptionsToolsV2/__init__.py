@@ -7,5 +7,6 @@ from .BinaryOptionsToolsV2 import __doc__  # noqa: F401
 from .pocketoption import __all__ as __pocket_all__
 from . import tracing
 from . import validator
+from . import vault
 
-__all__ = __pocket_all__ + ['tracing', 'validator']
+__all__ = __pocket_all__ + ['tracing', 'validator', 'vault']
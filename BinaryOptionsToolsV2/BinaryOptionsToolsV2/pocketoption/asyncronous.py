@@ -1,5 +1,6 @@
 from BinaryOptionsToolsV2.validator import Validator
 from BinaryOptionsToolsV2.config import Config
+from BinaryOptionsToolsV2.vault import Vault
 from BinaryOptionsToolsV2 import RawPocketOption, Logger
 from datetime import timedelta
 
@@ -112,6 +113,26 @@ class PocketOptionAsync:
             else:
                 self.client = RawPocketOption(ssid)
         self.logger = Logger()
+
+    @classmethod
+    def from_profile(cls, name: str, vault: Vault | None = None) -> "PocketOptionAsync":
+        """
+        Creates a client with the ssid, region and configuration stored in the profile `name`.
+
+        Args:
+            name (str): Name of the profile
+            vault (Vault, optional): Vault with the profile, by default the vault from the environment is used
+
+        Examples:
+            ```python
+            client = PocketOptionAsync.from_profile("demo", Vault(passphrase="my passphrase"))
+            ```
+        """
+        client = cls.__new__(cls)
+        client.config = Config()
+        client.client = RawPocketOption.from_profile(name, vault._vault if vault is not None else None)
+        client.logger = Logger()
+        return client
     
     
     async def buy(self, asset: str, amount: float, time: int, check_win: bool = False) -> tuple[str, dict]:
@@ -468,6 +489,53 @@ class PocketOptionAsync:
         """Returns the current server time as a UNIX timestamp"""
         return await self.client.get_server_time()
     
+    async def server_health(self) -> dict:
+        """
+        Returns the connection history of every server the client tried.
+
+        Returns:
+            dict: Maps each server url to its successes, failures, consecutive_failures, last_success,
+                last_failure, latency of the last handshake and cooldown_until, the moment until which
+                the server is skipped after failing repeatedly.
+        """
+        return json.loads(await self.client.server_health())
+
+    async def switch_account(self, ssid: str) -> None:
+        """
+        Reconnects the client with another ssid, for example to move between the demo and the real account.
+
+        Args:
+            ssid (str): Session id of the new account
+
+        Note:
+            The balance and deals of the previous account are cleared, subscriptions keep working.
+            If the server rejects the ssid the previous one is restored and an error is raised.
+        """
+        await self.client.switch_account(ssid)
+    
+    def on(self, event: str, callback) -> None:
+        """
+        Registers a callback for a lifecycle event of the connection.
+
+        Several callbacks can be registered for the same event, a callback that raises is logged
+        and doesn't affect the other callbacks or the connection.
+
+        Args:
+            event: One of "connect", "authenticated", "disconnect", "reconnect", "error" or "close"
+            callback: Called as callback(event, detail), detail is the reason of a disconnection or error and None otherwise.
+                Coroutine functions are awaited on the event loop that was running when `on` was called.
+
+        Example:
+            ```python
+            async def resubscribe(event, detail):
+                await client.subscribe_symbol("EURUSD_otc")
+
+            client.on("disconnect", lambda event, reason: print(f"Disconnected: {reason}"))
+            client.on("reconnect", resubscribe)
+            ```
+        """
+        self.client.on(event, callback)
+    
     async def is_demo(self) -> bool:
         """
         Checks if the current account is a demo account.
@@ -1,5 +1,6 @@
 from .asyncronous import PocketOptionAsync
 from BinaryOptionsToolsV2.config import Config
+from BinaryOptionsToolsV2.vault import Vault
 from BinaryOptionsToolsV2.validator import Validator
 from datetime import timedelta
 
@@ -107,6 +108,14 @@ class PocketOption:
         """        
         self.loop = asyncio.new_event_loop()
         self._client = PocketOptionAsync(ssid, config)
+
+    @classmethod
+    def from_profile(cls, name: str, vault: Vault | None = None) -> "PocketOption":
+        """Creates a client from the profile `name`, see `PocketOptionAsync.from_profile`"""
+        client = cls.__new__(cls)
+        client.loop = asyncio.new_event_loop()
+        client._client = PocketOptionAsync.from_profile(name, vault)
+        return client
     
     def __del__(self):
         self.loop.close()
@@ -364,6 +373,21 @@ class PocketOption:
         """Returns the current server time as a UNIX timestamp"""
         return self.loop.run_until_complete(self._client.get_server_time())
 
+    def server_health(self) -> dict:
+        """Returns the connection history of every server the client tried, see `PocketOptionAsync.server_health`"""
+        return self.loop.run_until_complete(self._client.server_health())
+
+    def switch_account(self, ssid: str) -> None:
+        """Reconnects the client with another ssid, see `PocketOptionAsync.switch_account`"""
+        self.loop.run_until_complete(self._client.switch_account(ssid))
+
+    def on(self, event: str, callback) -> None:
+        """
+        Registers a callback for a lifecycle event of the connection, see `PocketOptionAsync.on`.
+        Callbacks are called from a background thread and must be regular functions, not coroutines.
+        """
+        self._client.on(event, callback)
+
     def is_demo(self) -> bool:
         """
         Checks if the current account is a demo account.
@@ -0,0 +1,76 @@
+from BinaryOptionsToolsV2 import RawVault
+from BinaryOptionsToolsV2.config import Config
+from typing import List, Optional
+
+import json
+
+
+class Vault:
+    """
+    Encrypted file with named profiles, each profile stores an ssid, a region and configuration overrides.
+
+    The vault is unlocked with a passphrase or a key file, without any of them the key is read from the
+    POCKET_OPTION_VAULT_PASSPHRASE or POCKET_OPTION_VAULT_KEY_FILE environment variables.
+    Without a path the vault at POCKET_OPTION_VAULT or ~/.pocketoption/vault.json is used.
+
+    Example:
+        ```python
+        vault = Vault(passphrase="my passphrase")
+        vault.set_profile("demo", "your-session-id", region="EUROPE")
+        vault.save()
+
+        client = PocketOptionAsync.from_profile("demo", vault)
+        ```
+    """
+
+    def __init__(self, path: Optional[str] = None, passphrase: Optional[str] = None, key_file: Optional[str] = None):
+        self._vault = RawVault(path, passphrase, key_file)
+
+    @property
+    def path(self) -> str:
+        return self._vault.path()
+
+    def profiles(self) -> List[str]:
+        """Returns the names of the stored profiles"""
+        return self._vault.profiles()
+
+    def profile(self, name: str) -> dict:
+        """Returns the profile `name` with its ssid, region, market_connections and config overrides"""
+        return json.loads(self._vault.profile(name))
+
+    def set_profile(self, name: str, ssid: str, region: Optional[str] = None, market_connections: int = 0, config: Config | dict | None = None) -> None:
+        """
+        Adds or replaces the profile `name`, changes are only written by `save`.
+
+        Args:
+            name (str): Name of the profile
+            ssid (str): Session id of the account, it's validated before being stored
+            region (str, optional): Name of the only region the client connects to, like "EUROPE"
+            market_connections (int): Number of dedicated market data connections
+            config (Config | dict, optional): Configuration values stored with the profile
+        """
+        overrides = None
+        if isinstance(config, Config):
+            overrides = {
+                "max_allowed_loops": config.max_allowed_loops,
+                "sleep_interval": config.sleep_interval,
+                "reconnect_time": config.reconnect_time,
+                "timeout": {"secs": config.timeout_secs, "nanos": 0},
+                "connection_initialization_timeout": {"secs": config.connection_initialization_timeout_secs, "nanos": 0},
+                "default_connection_url": config.urls,
+            }
+        elif config is not None:
+            overrides = config
+        self._vault.set_profile(name, ssid, region, market_connections, json.dumps(overrides) if overrides is not None else None)
+
+    def remove_profile(self, name: str) -> bool:
+        """Removes the profile `name`, returns False if it didn't exist"""
+        return self._vault.remove_profile(name)
+
+    def change_key(self, passphrase: Optional[str] = None, key_file: Optional[str] = None) -> None:
+        """Encrypts the vault with a new passphrase or key file from the next `save` on"""
+        self._vault.change_key(passphrase, key_file)
+
+    def save(self) -> None:
+        """Encrypts and writes the vault to its path"""
+        self._vault.save()
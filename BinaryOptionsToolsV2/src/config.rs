@@ -1,10 +1,13 @@
 use binary_options_tools::{error::BinaryOptionsToolsError, pocketoption::parser::message::WebSocketMessage};
 use pyo3::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::time::Duration;
 use url::Url;
-use binary_options_tools::reimports::ConfigBuilder;
+use binary_options_tools::reimports::{ConfigBuilder, ConnectionConfig, TlsConfig};
 use binary_options_tools::pocketoption::types::data::PocketData;
+use binary_options_tools::pocketoption::types::info::MessageInfo;
+use binary_options_tools::pocketoption::ws::regions::ServerSelection;
 
 use crate::error::BinaryResultPy;
 
@@ -23,6 +26,22 @@ pub struct PyConfig {
     pub timeout_secs: u64,
     #[pyo3(get, set)]
     pub urls: Vec<String>,
+    #[pyo3(get, set)]
+    pub proxy: Option<String>,
+    #[pyo3(get, set)]
+    pub headers: HashMap<String, String>,
+    #[pyo3(get, set)]
+    pub cookies: HashMap<String, String>,
+    #[pyo3(get, set)]
+    pub resolve: HashMap<String, String>,
+    #[pyo3(get, set)]
+    pub connect_timeout_secs: u64,
+    #[pyo3(get, set)]
+    pub root_certificates: Vec<String>,
+    #[pyo3(get, set)]
+    pub spki_pins: HashMap<String, Vec<String>>,
+    #[pyo3(get, set)]
+    pub region: Option<String>,
 }
 
 #[pymethods]
@@ -36,6 +55,14 @@ impl PyConfig {
             connection_initialization_timeout_secs: 30,
             timeout_secs: 30,
             urls: Vec::new(),
+            proxy: None,
+            headers: HashMap::new(),
+            cookies: HashMap::new(),
+            resolve: HashMap::new(),
+            connect_timeout_secs: 10,
+            root_certificates: Vec::new(),
+            spki_pins: HashMap::new(),
+            region: None,
         }
     }
 
@@ -43,21 +70,53 @@ impl PyConfig {
 
 impl PyConfig {
     pub fn build(&self) -> BinaryResultPy<ConfigBuilder<PocketData, WebSocketMessage, ()>> {
-        let urls: Result<Vec<Url>, url::ParseError> = self
-            .urls
-            .iter()
-            .map(|url| Url::parse(url))
-            .collect();
+        let urls: Result<Vec<Url>, url::ParseError> =
+            self.urls.iter().map(|url| Url::parse(url)).collect();
+        let connection = ConnectionConfig {
+            proxy: self.proxy.as_deref().map(str::parse).transpose()?,
+            headers: self.headers.clone().into_iter().collect(),
+            cookies: self.cookies.clone().into_iter().collect(),
+            resolve: self
+                .resolve
+                .iter()
+                .map(|(host, ip)| {
+                    ip.parse::<IpAddr>()
+                        .map(|ip| (host.clone(), ip))
+                        .map_err(|e| {
+                            BinaryOptionsToolsError::GeneralParsingError(format!(
+                                "Invalid address '{ip}', {e}"
+                            ))
+                        })
+                })
+                .collect::<Result<_, _>>()?,
+            connect_timeout: Duration::from_secs(self.connect_timeout_secs),
+            tls: TlsConfig {
+                root_certificates: self.root_certificates.clone(),
+                spki_pins: self.spki_pins.clone().into_iter().collect(),
+            },
+        };
 
         let config = ConfigBuilder::new()
-        .max_allowed_loops(self.max_allowed_loops)
-        .sleep_interval(self.sleep_interval)
-        .reconnect_time(self.reconnect_time)
-        .timeout(Duration::from_secs(self.timeout_secs))
-        .default_connection_url(HashSet::from_iter(urls.map_err(|e| {
-            BinaryOptionsToolsError::from(e)
-        })?));
+            .max_allowed_loops(self.max_allowed_loops)
+            .sleep_interval(self.sleep_interval)
+            .reconnect_time(self.reconnect_time)
+            .connection_initialization_timeout(Duration::from_secs(
+                self.connection_initialization_timeout_secs,
+            ))
+            .bootstrap(MessageInfo::bootstrap())
+            .rate_limits(MessageInfo::rate_limits())
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .connection(connection)
+            .default_connection_url(HashSet::from_iter(
+                urls.map_err(|e| BinaryOptionsToolsError::from(e))?,
+            ));
         Ok(config)
     }
 
-}
\ No newline at end of file
+    pub fn servers(&self) -> ServerSelection {
+        match &self.region {
+            Some(region) => ServerSelection::Region(region.clone()),
+            None => ServerSelection::Latency,
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use binary_options_tools::{error::BinaryOptionsToolsError, pocketoption::error::PocketOptionError};
+use binary_options_tools::{
+    error::BinaryOptionsToolsError,
+    pocketoption::error::{PocketOptionError, SsidError, VaultError},
+};
 use pyo3::{exceptions::PyValueError, PyErr};
 use thiserror::Error;
 use uuid::Uuid;
@@ -23,6 +26,18 @@ pub enum BinaryErrorPy {
     InvalidRegexError(#[from] regex::Error)
 }
 
+impl From<SsidError> for BinaryErrorPy {
+    fn from(value: SsidError) -> Self {
+        Self::PocketOptionError(value.into())
+    }
+}
+
+impl From<VaultError> for BinaryErrorPy {
+    fn from(value: VaultError) -> Self {
+        Self::PocketOptionError(value.into())
+    }
+}
+
 impl From<BinaryErrorPy> for PyErr {
     fn from(value: BinaryErrorPy) -> Self {
         PyValueError::new_err(value.to_string())
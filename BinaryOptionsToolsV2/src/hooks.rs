@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use binary_options_tools::{
+    error::{BinaryOptionsResult, BinaryOptionsToolsError},
+    pocketoption::{parser::message::WebSocketMessage, types::data::PocketData},
+    reimports::{Data, LifecycleEvent, LifecycleHook, SenderMessage},
+};
+use pyo3::{exceptions::PyRuntimeError, types::PyAnyMethods, PyErr, PyObject, PyResult, Python};
+use pyo3_async_runtimes::TaskLocals;
+
+use crate::error::{BinaryErrorPy, BinaryResultPy};
+
+type PocketEvents = Data<PocketData, WebSocketMessage>;
+
+/// Python callable registered with `RawPocketOption.on`, it's called as `callback(event, detail)`
+/// and awaited by the event loop that registered it when it returns a coroutine.
+pub struct PyLifecycleHook {
+    event: String,
+    callback: Arc<PyObject>,
+    locals: Option<TaskLocals>,
+}
+
+impl PyLifecycleHook {
+    pub fn new(
+        event: String,
+        callback: PyObject,
+        locals: Option<TaskLocals>,
+    ) -> BinaryResultPy<Self> {
+        if !LifecycleEvent::NAMES.contains(&event.as_str()) {
+            return Err(BinaryErrorPy::NotAllowed(format!(
+                "Unknown event '{event}', expected one of {:?}",
+                LifecycleEvent::NAMES
+            )));
+        }
+        Ok(Self {
+            event,
+            callback: Arc::new(callback),
+            locals,
+        })
+    }
+
+    async fn call(&self, event: LifecycleEvent) -> BinaryOptionsResult<()> {
+        if event.name() != self.event {
+            return Ok(());
+        }
+        let future = Python::with_gil(|py| -> PyResult<_> {
+            let res = self
+                .callback
+                .call1(py, (event.name(), event.detail()))?
+                .into_bound(py);
+            if !res.hasattr("__await__")? {
+                return Ok(None);
+            }
+            let locals = self.locals.as_ref().ok_or_else(|| {
+                PyRuntimeError::new_err("Async hooks must be registered from a running event loop")
+            })?;
+            pyo3_async_runtimes::into_future_with_locals(locals, res).map(Some)
+        })
+        .map_err(hook_error)?;
+        if let Some(future) = future {
+            future.await.map_err(hook_error)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LifecycleHook<PocketData, WebSocketMessage> for PyLifecycleHook {
+    async fn on_connect(&self, _data: &PocketEvents) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Connect).await
+    }
+
+    async fn on_authenticated(&self, _data: &PocketEvents) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Authenticated).await
+    }
+
+    async fn on_disconnect(&self, _data: &PocketEvents, reason: &str) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Disconnect {
+            reason: reason.to_string(),
+        })
+        .await
+    }
+
+    async fn on_reconnect(
+        &self,
+        _data: &PocketEvents,
+        _sender: &SenderMessage,
+    ) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Reconnect).await
+    }
+
+    async fn on_error(&self, _data: &PocketEvents, error: &str) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Error {
+            error: error.to_string(),
+        })
+        .await
+    }
+
+    async fn on_close(&self, _data: &PocketEvents) -> BinaryOptionsResult<()> {
+        self.call(LifecycleEvent::Close).await
+    }
+}
+
+fn hook_error(error: PyErr) -> BinaryOptionsToolsError {
+    BinaryOptionsToolsError::HookError(error.to_string())
+}
@@ -1,18 +1,21 @@
 #![allow(non_snake_case)]
 
+mod config;
 mod error;
+mod hooks;
 mod logs;
 mod pocketoption;
 mod runtime;
 mod stream;
 mod validator;
-mod config;
+mod vault;
 
 use config::PyConfig;
 use logs::{start_tracing, LogBuilder, Logger, StreamLogsIterator, StreamLogsLayer};
 use pocketoption::{RawPocketOption, RawStreamIterator, StreamIterator};
 use pyo3::prelude::*;
 use validator::RawValidator;
+use vault::RawVault;
 
 #[pymodule]
 #[pyo3(name = "BinaryOptionsToolsV2")]
@@ -26,6 +29,7 @@ fn BinaryOptionsTools(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<RawStreamIterator>()?;
     m.add_class::<RawValidator>()?;
     m.add_class::<PyConfig>()?;
+    m.add_class::<RawVault>()?;
 
     m.add_function(wrap_pyfunction!(start_tracing, m)?)?;
     Ok(())
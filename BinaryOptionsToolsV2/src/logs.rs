@@ -2,7 +2,7 @@ use std::{fs::OpenOptions, io::Write, sync::Arc};
 
 use binary_options_tools::{
     error::BinaryOptionsResult,
-    stream::{stream_logs_layer, RecieverStream},
+    stream::{stream_logs_layer, RecieverStream, Redacted},
 };
 use chrono::Duration;
 use futures_util::{
@@ -54,20 +54,24 @@ pub fn start_tracing(
             // log-error file, to log the errors that arise
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(error_logs)
+                .with_writer(Redacted::new(error_logs))
                 .with_filter(LevelFilter::WARN),
         )
         .with(
             // log-debug file, to log the debug
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(logs)
+                .with_writer(Redacted::new(logs))
                 .with_filter(level),
         );
 
     if terminal {
         subscriber
-            .with(fmt::Layer::default().with_filter(level))
+            .with(
+                fmt::Layer::default()
+                    .with_writer(Redacted::new(std::io::stdout))
+                    .with_filter(level),
+            )
             .init();
     } else {
         subscriber.init()
@@ -177,7 +181,7 @@ impl LogBuilder {
         let logs = OpenOptions::new().append(true).create(true).open(path)?;
         let layer = fmt::layer()
             .with_ansi(false)
-            .with_writer(logs)
+            .with_writer(Redacted::new(logs))
             .with_filter(level.parse().unwrap_or(LevelFilter::DEBUG))
             .boxed();
         self.layers.push(layer);
@@ -187,6 +191,7 @@ impl LogBuilder {
     #[pyo3(signature = (level = "DEBUG".to_string()))]
     pub fn terminal(&mut self, level: String) {
         let layer = fmt::Layer::default()
+            .with_writer(Redacted::new(std::io::stdout))
             .with_filter(level.parse().unwrap_or(LevelFilter::DEBUG))
             .boxed();
         self.layers.push(layer);
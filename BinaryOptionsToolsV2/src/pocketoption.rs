@@ -11,16 +11,20 @@ use binary_options_tools::pocketoption::ws::stream::StreamAsset;
 use binary_options_tools::reimports::FilteredRecieverStream;
 use futures_util::stream::{BoxStream, Fuse};
 use futures_util::StreamExt;
-use pyo3::{pyclass, pymethods, Bound, IntoPyObjectExt, Py, PyAny, PyResult, Python};
+use pyo3::{
+    pyclass, pymethods, Bound, IntoPyObjectExt, Py, PyAny, PyObject, PyRef, PyResult, Python,
+};
 use pyo3_async_runtimes::tokio::future_into_py;
 use url::Url;
 use uuid::Uuid;
 
+use crate::config::PyConfig;
 use crate::error::BinaryErrorPy;
+use crate::hooks::PyLifecycleHook;
 use crate::runtime::get_runtime;
 use crate::stream::next_stream;
 use crate::validator::RawValidator;
-use crate::config::PyConfig;
+use crate::vault::RawVault;
 use tokio::sync::Mutex;
 
 #[pyclass]
@@ -47,9 +51,13 @@ impl RawPocketOption {
         let runtime = get_runtime(py)?;
         runtime.block_on(async move {
             let client = if let Some(config) = config {
+                let servers = config.servers();
                 let builder = config.build()?;
-                let config = builder.build().map_err(BinaryOptionsToolsError::from).map_err(BinaryErrorPy::from)?;
-                PocketOption::new_with_config(ssid, config)
+                let config = builder
+                    .build()
+                    .map_err(BinaryOptionsToolsError::from)
+                    .map_err(BinaryErrorPy::from)?;
+                PocketOption::new_with_servers(ssid, config, 0, servers)
                     .await
                     .map_err(BinaryErrorPy::from)?
             } else {
@@ -68,9 +76,13 @@ impl RawPocketOption {
                 .map_err(|e| BinaryErrorPy::from(BinaryOptionsToolsError::from(e)))?;
             
             let client = if let Some(config) = config {
+                let servers = config.servers();
                 let builder = config.build()?;
-                let config = builder.build().map_err(BinaryOptionsToolsError::from).map_err(BinaryErrorPy::from)?;
-                PocketOption::new_with_config(ssid, config)
+                let config = builder
+                    .build()
+                    .map_err(BinaryOptionsToolsError::from)
+                    .map_err(BinaryErrorPy::from)?;
+                PocketOption::new_with_servers(ssid, config, 0, servers)
                     .await
                     .map_err(BinaryErrorPy::from)?
             } else {
@@ -82,7 +94,22 @@ impl RawPocketOption {
         })
     }
 
-    
+    /// Creates a client from the profile `name` of `vault`, or of the default vault if it's `None`
+    #[staticmethod]
+    #[pyo3(signature = (name, vault = None))]
+    pub fn from_profile(
+        py: Python<'_>,
+        name: String,
+        vault: Option<PyRef<'_, RawVault>>,
+    ) -> PyResult<Self> {
+        let runtime = get_runtime(py)?;
+        let client = match vault {
+            Some(vault) => runtime.block_on(PocketOption::from_vault_profile(&vault.vault, name)),
+            None => runtime.block_on(PocketOption::from_profile(name)),
+        }
+        .map_err(BinaryErrorPy::from)?;
+        Ok(Self { client })
+    }
 
     pub async fn is_demo(&self) -> bool {
         self.client.is_demo().await
@@ -408,6 +435,42 @@ impl RawPocketOption {
             async move { Ok(client.get_server_time().await.timestamp()) },
         )
     }
+
+    pub fn server_health<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let res = client.server_health().await;
+            Python::with_gil(|py| {
+                serde_json::to_string(&res)
+                    .map_err(BinaryErrorPy::from)?
+                    .into_py_any(py)
+            })
+        })
+    }
+
+    pub fn switch_account<'py>(
+        &self,
+        py: Python<'py>,
+        ssid: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            client
+                .switch_account(ssid)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
+    }
+
+    /// Registers `callback` for a lifecycle event, it's called as `callback(event, detail)`.
+    /// Coroutines returned by the callback run on the event loop that was running when `on` was called.
+    pub fn on(&self, py: Python<'_>, event: String, callback: PyObject) -> PyResult<()> {
+        let locals = pyo3_async_runtimes::tokio::get_current_locals(py).ok();
+        let hook = PyLifecycleHook::new(event, callback, locals)?;
+        self.client.add_hooks(Arc::new(hook));
+        Ok(())
+    }
 }
 
 #[pymethods]
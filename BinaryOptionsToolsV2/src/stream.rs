@@ -1,5 +1,8 @@
 use std::sync::Arc;
 
+use binary_options_tools::{
+    error::BinaryOptionsToolsError, pocketoption::error::PocketOptionError,
+};
 use futures_util::{
     stream::{BoxStream, Fuse},
     StreamExt,
@@ -9,28 +12,61 @@ use pyo3::{
     PyResult,
 };
 use tokio::sync::Mutex;
+use tracing::warn;
 
 pub type PyStream<T, E> = Fuse<BoxStream<'static, Result<T, E>>>;
 
+/// Errors that can tell if the stream dropped messages because it wasn't consumed fast enough
+pub trait StreamError: std::error::Error {
+    fn lagged(&self) -> Option<u64>;
+}
+
+impl StreamError for BinaryOptionsToolsError {
+    fn lagged(&self) -> Option<u64> {
+        match self {
+            BinaryOptionsToolsError::ChannelLagged(skipped) => Some(*skipped),
+            _ => None,
+        }
+    }
+}
+
+impl StreamError for PocketOptionError {
+    fn lagged(&self) -> Option<u64> {
+        match self {
+            PocketOptionError::BinaryOptionsToolsError(e) => e.lagged(),
+            _ => None,
+        }
+    }
+}
+
 pub async fn next_stream<T, E>(stream: Arc<Mutex<PyStream<T, E>>>, sync: bool) -> PyResult<T>
 where
-    E: std::error::Error,
+    E: StreamError,
 {
     let mut stream = stream.lock().await;
-    match stream.next().await {
-        Some(item) => match item {
-            Ok(itm) => Ok(itm),
-            Err(e) => {
-                println!("Error: {:?}", e);
-                match sync {
-                    true => Err(PyStopIteration::new_err(e.to_string())),
-                    false => Err(PyStopAsyncIteration::new_err(e.to_string())),
+    loop {
+        match stream.next().await {
+            Some(item) => match item {
+                Ok(itm) => return Ok(itm),
+                Err(e) => {
+                    // Lagging is not fatal, the stream keeps working after reporting it
+                    if let Some(skipped) = e.lagged() {
+                        warn!("Stream lagged behind, {skipped} messages were dropped");
+                        continue;
+                    }
+                    println!("Error: {:?}", e);
+                    return match sync {
+                        true => Err(PyStopIteration::new_err(e.to_string())),
+                        false => Err(PyStopAsyncIteration::new_err(e.to_string())),
+                    };
+                }
+            },
+            None => {
+                return match sync {
+                    true => Err(PyStopIteration::new_err("Stream exhausted")),
+                    false => Err(PyStopAsyncIteration::new_err("Stream exhausted")),
                 }
             }
-        },
-        None => match sync {
-            true => Err(PyStopIteration::new_err("Stream exhausted")),
-            false => Err(PyStopAsyncIteration::new_err("Stream exhausted")),
-        },
+        }
     }
 }
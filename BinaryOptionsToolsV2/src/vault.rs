@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use binary_options_tools::pocketoption::error::PocketOptionError;
+use binary_options_tools::pocketoption::vault::{ConfigOverrides, Profile, Vault, VaultKey};
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::error::{BinaryErrorPy, BinaryResultPy};
+
+#[pyclass]
+pub struct RawVault {
+    pub vault: Vault,
+}
+
+fn vault_key(passphrase: Option<String>, key_file: Option<String>) -> BinaryResultPy<VaultKey> {
+    match (passphrase, key_file) {
+        (Some(passphrase), None) => Ok(VaultKey::Passphrase(passphrase)),
+        (None, Some(path)) => Ok(VaultKey::KeyFile(PathBuf::from(path))),
+        (None, None) => Ok(VaultKey::from_env()?),
+        (Some(_), Some(_)) => Err(BinaryErrorPy::NotAllowed(
+            "Use either a passphrase or a key file, not both".into(),
+        )),
+    }
+}
+
+#[pymethods]
+impl RawVault {
+    /// Opens the vault at `path` or creates an empty one, without a passphrase or key file the
+    /// key is read from the environment
+    #[new]
+    #[pyo3(signature = (path = None, passphrase = None, key_file = None))]
+    pub fn new(
+        path: Option<String>,
+        passphrase: Option<String>,
+        key_file: Option<String>,
+    ) -> PyResult<Self> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => Vault::default_path().map_err(BinaryErrorPy::from)?,
+        };
+        let key = vault_key(passphrase, key_file)?;
+        let vault = Vault::open_or_create(path, &key).map_err(BinaryErrorPy::from)?;
+        Ok(Self { vault })
+    }
+
+    pub fn path(&self) -> String {
+        self.vault.path().display().to_string()
+    }
+
+    pub fn profiles(&self) -> Vec<String> {
+        self.vault.profiles()
+    }
+
+    /// Profile `name` as a json string
+    pub fn profile(&self, name: String) -> PyResult<String> {
+        let profile = self.vault.profile(&name).map_err(BinaryErrorPy::from)?;
+        Ok(serde_json::to_string(profile).map_err(BinaryErrorPy::from)?)
+    }
+
+    /// Adds or replaces the profile `name`, `config` is a json string with the values to override
+    #[pyo3(signature = (name, ssid, region = None, market_connections = 0, config = None))]
+    pub fn set_profile(
+        &mut self,
+        name: String,
+        ssid: String,
+        region: Option<String>,
+        market_connections: usize,
+        config: Option<String>,
+    ) -> PyResult<()> {
+        let mut profile = Profile::new(ssid)
+            .map_err(BinaryErrorPy::from)?
+            .market_connections(market_connections);
+        if let Some(region) = region {
+            profile = profile.region(region);
+        }
+        if let Some(config) = config {
+            let config: ConfigOverrides =
+                serde_json::from_str(&config).map_err(BinaryErrorPy::from)?;
+            profile = profile.config(config);
+        }
+        self.vault.set_profile(name, profile);
+        Ok(())
+    }
+
+    pub fn remove_profile(&mut self, name: String) -> bool {
+        self.vault.remove_profile(&name).is_some()
+    }
+
+    #[pyo3(signature = (passphrase = None, key_file = None))]
+    pub fn change_key(
+        &mut self,
+        passphrase: Option<String>,
+        key_file: Option<String>,
+    ) -> PyResult<()> {
+        if passphrase.is_none() && key_file.is_none() {
+            return Err(BinaryErrorPy::from(PocketOptionError::VaultError(
+                "Missing the new key".into(),
+            ))
+            .into());
+        }
+        let key = vault_key(passphrase, key_file)?;
+        self.vault.change_key(&key).map_err(BinaryErrorPy::from)?;
+        Ok(())
+    }
+
+    pub fn save(&self) -> PyResult<()> {
+        self.vault.save().map_err(BinaryErrorPy::from)?;
+        Ok(())
+    }
+}
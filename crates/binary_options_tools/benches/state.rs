@@ -0,0 +1,142 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use binary_options_tools::pocketoption::types::{data::PocketData, update::UpdateBalance};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use tokio::{runtime::Runtime, sync::Mutex};
+
+const READS: usize = 1_000;
+
+fn balance() -> UpdateBalance {
+    serde_json::from_value(serde_json::json!({ "isDemo": 1, "balance": 0.0 }))
+        .expect("Valid balance")
+}
+
+fn with_value(balance: &UpdateBalance, value: f64) -> UpdateBalance {
+    let mut balance = balance.clone();
+    balance.balance = value;
+    balance
+}
+
+/// Shared state the way `PocketData` stored it before, kept as the baseline
+#[derive(Clone)]
+struct MutexState {
+    balance: Arc<Mutex<UpdateBalance>>,
+}
+
+/// Runs `readers` tasks reading `READS` times each while another task keeps updating the state
+fn run<R, W, RF, WF>(rt: &Runtime, readers: usize, read: R, write: W) -> Duration
+where
+    R: Fn() -> RF + Clone + Send + 'static,
+    RF: Future<Output = ()> + Send,
+    W: Fn(f64) -> WF + Send + 'static,
+    WF: Future<Output = ()> + Send,
+{
+    rt.block_on(async move {
+        let writer = tokio::spawn(async move {
+            let mut value = 0.0;
+            loop {
+                value += 1.0;
+                write(value).await;
+                tokio::task::yield_now().await;
+            }
+        });
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..readers)
+            .map(|_| {
+                let read = read.clone();
+                tokio::spawn(async move {
+                    for _ in 0..READS {
+                        read().await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("Reader panicked");
+        }
+        let elapsed = start.elapsed();
+        writer.abort();
+        elapsed
+    })
+}
+
+fn concurrent_reads(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build runtime");
+    let mut group = c.benchmark_group("concurrent_balance_reads");
+    for readers in [1, 8, 64] {
+        group.bench_with_input(
+            BenchmarkId::new("mutex", readers),
+            &readers,
+            |b, &readers| {
+                let state = MutexState {
+                    balance: Arc::new(Mutex::new(balance())),
+                };
+                b.iter_custom(|iters| {
+                    (0..iters)
+                        .map(|_| {
+                            let (reader, writer) = (state.clone(), state.clone());
+                            let template = balance();
+                            run(
+                                &rt,
+                                readers,
+                                move || {
+                                    let state = reader.clone();
+                                    async move {
+                                        std::hint::black_box(state.balance.lock().await.clone());
+                                    }
+                                },
+                                move |value| {
+                                    let state = writer.clone();
+                                    let balance = with_value(&template, value);
+                                    async move {
+                                        *state.balance.lock().await = balance;
+                                    }
+                                },
+                            )
+                        })
+                        .sum()
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("snapshot", readers),
+            &readers,
+            |b, &readers| {
+                let data = Arc::new(PocketData::default());
+                b.iter_custom(|iters| {
+                    (0..iters)
+                        .map(|_| {
+                            let (reader, writer) = (data.clone(), data.clone());
+                            let template = balance();
+                            run(
+                                &rt,
+                                readers,
+                                move || {
+                                    let data = reader.clone();
+                                    async move {
+                                        std::hint::black_box(data.get_balance().await);
+                                    }
+                                },
+                                move |value| {
+                                    let data = writer.clone();
+                                    let balance = with_value(&template, value);
+                                    async move { data.update_balance(balance).await }
+                                },
+                            )
+                        })
+                        .sum()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_reads);
+criterion_main!(benches);
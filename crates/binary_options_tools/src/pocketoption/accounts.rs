@@ -0,0 +1,396 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use binary_options_tools_core::general::ready::ConnectionState;
+use futures_util::future::join_all;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    pocket_client::PocketOption,
+    types::order::{Action, Deal},
+    ws::health::ServerHealth,
+};
+
+/// Groups and stake scale of an account inside an `AccountManager`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountSettings {
+    groups: HashSet<String>,
+    stake_scale: f64,
+}
+
+/// Accounts an order is sent to
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountSelection {
+    All,
+    /// Every account in the group
+    Group(String),
+    /// Accounts by name, missing names get an `UnknownAccount` error in the results
+    Names(Vec<String>),
+}
+
+/// Snapshot of the state of a single account
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountHealth {
+    pub state: ConnectionState,
+    pub ready: bool,
+    pub demo: bool,
+    pub balance: f64,
+    pub opened_deals: usize,
+    pub servers: HashMap<String, ServerHealth>,
+}
+
+#[derive(Clone)]
+struct Account {
+    client: PocketOption,
+    settings: AccountSettings,
+}
+
+/// Owns several `PocketOption` clients by name, aggregates their state and sends orders to many of them at once.
+/// Every account is handled in its own task so a failing or slow session never blocks the others.
+///
+/// # Examples
+/// ```rust
+/// let manager = AccountManager::new();
+/// manager.connect("main", main_ssid, AccountSettings::default().group("eu")).await?;
+/// manager.connect("small", small_ssid, AccountSettings::default().group("eu").stake_scale(0.5)).await?;
+/// let results = manager.broadcast(AccountSelection::Group("eu".into()), "EURUSD_otc", Action::Call, 10.0, 60).await;
+/// ```
+#[derive(Clone, Default)]
+pub struct AccountManager {
+    accounts: Arc<RwLock<HashMap<String, Account>>>,
+}
+
+impl Default for AccountSettings {
+    fn default() -> Self {
+        Self {
+            groups: HashSet::new(),
+            stake_scale: 1.0,
+        }
+    }
+}
+
+impl AccountSettings {
+    /// Adds the account to `group`
+    pub fn group(mut self, group: impl ToString) -> Self {
+        self.groups.insert(group.to_string());
+        self
+    }
+
+    /// Multiplies the amount of every broadcasted order, `0.5` places half the stake
+    pub fn stake_scale(mut self, scale: f64) -> Self {
+        self.stake_scale = scale;
+        self
+    }
+
+    pub fn groups(&self) -> &HashSet<String> {
+        &self.groups
+    }
+
+    pub fn get_stake_scale(&self) -> f64 {
+        self.stake_scale
+    }
+
+    /// Amount placed on this account for an order of `amount`, rounded to cents
+    pub fn scaled_amount(&self, amount: f64) -> f64 {
+        (amount * self.stake_scale * 100.0).round() / 100.0
+    }
+}
+
+impl AccountSelection {
+    fn matches(&self, name: &str, settings: &AccountSettings) -> bool {
+        match self {
+            Self::All => true,
+            Self::Group(group) => settings.groups.contains(group),
+            Self::Names(names) => names.iter().any(|n| n == name),
+        }
+    }
+}
+
+impl AccountManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an already connected client, replacing the account with the same name
+    pub async fn add(&self, name: impl ToString, client: PocketOption, settings: AccountSettings) {
+        let name = name.to_string();
+        info!(target: "AccountManager", "Adding account '{name}'");
+        self.accounts
+            .write()
+            .await
+            .insert(name, Account { client, settings });
+    }
+
+    /// Connects a new client with `ssid` and adds it
+    pub async fn connect(
+        &self,
+        name: impl ToString,
+        ssid: impl ToString,
+        settings: AccountSettings,
+    ) -> PocketResult<()> {
+        let client = PocketOption::new(ssid).await?;
+        self.add(name, client, settings).await;
+        Ok(())
+    }
+
+    /// Removes the account and returns its client, the connection stays open until the client is dropped
+    pub async fn remove(&self, name: &str) -> Option<PocketOption> {
+        self.accounts
+            .write()
+            .await
+            .remove(name)
+            .map(|account| account.client)
+    }
+
+    pub async fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.accounts.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub async fn client(&self, name: &str) -> PocketResult<PocketOption> {
+        self.accounts
+            .read()
+            .await
+            .get(name)
+            .map(|account| account.client.clone())
+            .ok_or_else(|| PocketOptionError::UnknownAccount(name.to_string()))
+    }
+
+    /// Replaces the groups and stake scale of an account
+    pub async fn set_settings(&self, name: &str, settings: AccountSettings) -> PocketResult<()> {
+        let mut accounts = self.accounts.write().await;
+        let account = accounts
+            .get_mut(name)
+            .ok_or_else(|| PocketOptionError::UnknownAccount(name.to_string()))?;
+        account.settings = settings;
+        Ok(())
+    }
+
+    pub async fn balances(&self) -> HashMap<String, f64> {
+        self.collect(|client| async move { client.get_balance().await.balance })
+            .await
+    }
+
+    /// Sum of the balances of the demo accounts and of the real ones, as `(demo, real)`
+    pub async fn total_balance(&self) -> (f64, f64) {
+        let balances = self
+            .collect(|client| async move {
+                (client.is_demo().await, client.get_balance().await.balance)
+            })
+            .await;
+        balances
+            .into_values()
+            .fold((0.0, 0.0), |(demo, real), (is_demo, balance)| {
+                if is_demo {
+                    (demo + balance, real)
+                } else {
+                    (demo, real + balance)
+                }
+            })
+    }
+
+    pub async fn opened_deals(&self) -> HashMap<String, Vec<Deal>> {
+        self.collect(|client| async move { client.get_opened_deals().await })
+            .await
+    }
+
+    pub async fn closed_deals(&self) -> HashMap<String, Vec<Deal>> {
+        self.collect(|client| async move { client.get_closed_deals().await })
+            .await
+    }
+
+    pub async fn health(&self) -> HashMap<String, AccountHealth> {
+        self.collect(|client| async move {
+            AccountHealth {
+                state: client.connection_state(),
+                ready: client.is_ready(),
+                demo: client.is_demo().await,
+                balance: client.get_balance().await.balance,
+                opened_deals: client.get_opened_deals().await.len(),
+                servers: client.server_health().await,
+            }
+        })
+        .await
+    }
+
+    /// Places the same order on every selected account, each one with its scaled stake.
+    /// Accounts whose ssid was rejected fail right away and the others are not affected by their errors.
+    pub async fn broadcast(
+        &self,
+        selection: AccountSelection,
+        asset: impl ToString,
+        action: Action,
+        amount: f64,
+        time: u32,
+    ) -> HashMap<String, PocketResult<(Uuid, Deal)>> {
+        let asset = asset.to_string();
+        let mut results = HashMap::new();
+        let selected: Vec<(String, Account)> = {
+            let accounts = self.accounts.read().await;
+            if let AccountSelection::Names(names) = &selection {
+                for name in names.iter().filter(|n| !accounts.contains_key(*n)) {
+                    results.insert(
+                        name.clone(),
+                        Err(PocketOptionError::UnknownAccount(name.clone())),
+                    );
+                }
+            }
+            accounts
+                .iter()
+                .filter(|(name, account)| selection.matches(name, &account.settings))
+                .map(|(name, account)| (name.clone(), account.clone()))
+                .collect()
+        };
+        info!(target: "AccountManager", "Broadcasting {action:?} on '{asset}' to {} accounts", selected.len());
+
+        let tasks = selected.into_iter().map(|(name, account)| {
+            let asset = asset.clone();
+            let action = action.clone();
+            let task = tokio::spawn(async move {
+                if let ConnectionState::AuthFailed(reason) = account.client.connection_state() {
+                    return Err(PocketOptionError::AuthenticationFailed(reason));
+                }
+                let amount = account.settings.scaled_amount(amount);
+                account.client.trade(asset, action, amount, time).await
+            });
+            async move {
+                let result = match task.await {
+                    Ok(result) => result,
+                    Err(e) => Err(PocketOptionError::UnreachableError(format!(
+                        "Order task panicked, {e}"
+                    ))),
+                };
+                if let Err(e) = &result {
+                    warn!(target: "AccountManager", "Order failed on account '{name}', {e}");
+                }
+                (name, result)
+            }
+        });
+        results.extend(join_all(tasks).await);
+        results
+    }
+
+    /// Runs `f` on every account at the same time
+    async fn collect<T, F, Fut>(&self, f: F) -> HashMap<String, T>
+    where
+        F: Fn(PocketOption) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let accounts: Vec<(String, PocketOption)> = self
+            .accounts
+            .read()
+            .await
+            .iter()
+            .map(|(name, account)| (name.clone(), account.client.clone()))
+            .collect();
+        join_all(accounts.into_iter().map(|(name, client)| {
+            let future = f(client);
+            async move { (name, future.await) }
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::pocketoption::testing::{BALANCE, MockConnection, MockServer, WAIT};
+
+    #[test]
+    fn test_account_selection() {
+        let settings = AccountSettings::default().group("eu").stake_scale(0.5);
+        assert!(AccountSelection::All.matches("main", &settings));
+        assert!(AccountSelection::Group("eu".into()).matches("main", &settings));
+        assert!(!AccountSelection::Group("us".into()).matches("main", &settings));
+        assert!(AccountSelection::Names(vec!["main".into()]).matches("main", &settings));
+        assert!(!AccountSelection::Names(vec!["other".into()]).matches("main", &settings));
+
+        assert_eq!(settings.scaled_amount(10.0), 5.0);
+        assert_eq!(settings.scaled_amount(3.33), 1.67);
+        assert_eq!(AccountSettings::default().scaled_amount(7.5), 7.5);
+    }
+
+    /// Adds an account connected to its own mock server, the server has to outlive the connection
+    async fn add_mock_account(
+        manager: &AccountManager,
+        name: &str,
+    ) -> anyhow::Result<(MockServer, MockConnection)> {
+        let server = MockServer::start().await?;
+        let (client, connection) = server.connect(server.config().build()?).await?;
+        manager.add(name, client, AccountSettings::default()).await;
+        Ok((server, connection))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_total_balance_splits_demo_and_real() -> anyhow::Result<()> {
+        let manager = AccountManager::new();
+        let (_demo_server, _demo) = add_mock_account(&manager, "demo").await?;
+        let real_server = MockServer::start().await?;
+        let real_ssid = r#"42["auth",{"session":"a:4:{s:10:\"session_id\";s:32:\"02ac5a5875a4b583042aae064351e0bb\";s:10:\"ip_address\";s:13:\"191.113.133.5\";s:10:\"user_agent\";s:120:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36 OPR/116.\";s:13:\"last_activity\";i:1740838529;}0850af6716a7a1d1eea59e80987f4a59","isDemo":0,"uid":87742848,"platform":2}]"#;
+        let client = tokio::spawn(PocketOption::new_with_config(
+            real_ssid,
+            real_server.config().build()?,
+        ));
+        let mut real = real_server.accept().await?;
+        real.bootstrap_with_balance(250.0).await?;
+        let client = tokio::time::timeout(WAIT, client).await???;
+        manager
+            .add("real", client, AccountSettings::default())
+            .await;
+
+        assert_eq!(manager.total_balance().await, (BALANCE, 250.0));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_broadcast_isolates_failing_accounts() -> anyhow::Result<()> {
+        let manager = AccountManager::new();
+        let (_ok_server, mut ok) = add_mock_account(&manager, "ok").await?;
+        let (_failing_server, mut failing) = add_mock_account(&manager, "failing").await?;
+        let (_rejected_server, mut rejected) = add_mock_account(&manager, "rejected").await?;
+        rejected.reject().await?;
+        let client = manager.client("rejected").await?;
+        tokio::time::timeout(WAIT, async {
+            while !matches!(client.connection_state(), ConnectionState::AuthFailed(_)) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await?;
+
+        let broadcast = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .broadcast(AccountSelection::All, "EURUSD_otc", Action::Call, 1.0, 60)
+                    .await
+            }
+        });
+        // The healthy account gets its order while the failing one is still waiting for an answer
+        let deal = ok.open_order().await?;
+        failing.fail_order("Not enough money").await?;
+        let results = tokio::time::timeout(WAIT, broadcast).await??;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results["ok"].as_ref().map(|(id, _)| *id).ok(),
+            Some(deal.id)
+        );
+        assert!(results["failing"].is_err());
+        assert!(matches!(
+            results["rejected"],
+            Err(PocketOptionError::AuthenticationFailed(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,488 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use binary_options_tools_core::reimports::CancellationToken;
+use chrono::Utc;
+use serde::Serialize;
+use tokio::{
+    sync::{Mutex, RwLock, broadcast::error::RecvError},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{
+    pocket_client::PocketOption,
+    types::{data::DealOpened, order::Deal},
+};
+
+/// Copies that never get a delay longer than this by default
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(3);
+
+/// Number of copies remembered by the `Copier`
+const MAX_RECORDS: usize = 1024;
+
+/// Interval between two reads of the closed deals while waiting for the outcome of a copy
+const CLOSED_DEAL_POLL: Duration = Duration::from_secs(1);
+
+/// Amount a follower stakes on each copied deal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Stake {
+    /// Multiplies the amount of the leader, `0.5` stakes half of it
+    Proportional(f64),
+    /// Same amount for every deal
+    Fixed(f64),
+}
+
+/// Stake, asset filters and maximum delay of a follower
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopySettings {
+    stake: Stake,
+    allow: HashSet<String>,
+    deny: HashSet<String>,
+    max_delay: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum CopyStatus {
+    Copied,
+    /// The deal was filtered out by the settings of the follower
+    Skipped(String),
+    Failed(String),
+}
+
+/// How the deals of the leader and a follower ended
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CopyOutcome {
+    pub leader_profit: f64,
+    pub follower_profit: f64,
+    /// Both deals won, lost or were a draw
+    pub same_result: bool,
+}
+
+/// Copy of a deal of the leader on a single follower
+#[derive(Debug, Clone, Serialize)]
+pub struct CopyRecord {
+    pub follower: String,
+    pub leader_deal: Deal,
+    pub status: CopyStatus,
+    pub follower_deal: Option<Deal>,
+    /// Time between the deal of the leader being opened and the order of the follower being sent
+    pub delay: Duration,
+    /// Open price of the follower minus the one of the leader
+    pub slippage: Option<f64>,
+    /// Filled once both deals are closed
+    pub outcome: Option<CopyOutcome>,
+}
+
+#[derive(Clone)]
+struct Follower {
+    client: PocketOption,
+    settings: CopySettings,
+}
+
+/// Mirrors every deal opened on a leader account to the follower clients.
+/// Each copy is recorded with its delay, open price slippage and, once both deals are closed, the outcome.
+///
+/// # Examples
+/// ```rust
+/// let copier = Copier::new(leader);
+/// copier.add_follower("small", follower, CopySettings::default().stake(Stake::Fixed(1.0)).deny("BTCUSD_otc")).await;
+/// copier.start();
+/// // ...
+/// for record in copier.records().await {
+///     println!("{}: {:?}, slippage {:?}", record.follower, record.status, record.slippage);
+/// }
+/// copier.stop();
+/// ```
+#[derive(Clone)]
+pub struct Copier {
+    leader: PocketOption,
+    followers: Arc<RwLock<HashMap<String, Follower>>>,
+    records: Arc<Mutex<VecDeque<CopyRecord>>>,
+    token: CancellationToken,
+}
+
+impl Stake {
+    /// Amount placed by the follower when the leader places `amount`, rounded to cents
+    pub fn amount(&self, amount: f64) -> f64 {
+        let amount = match self {
+            Self::Proportional(ratio) => amount * ratio,
+            Self::Fixed(fixed) => *fixed,
+        };
+        (amount * 100.0).round() / 100.0
+    }
+}
+
+impl Default for CopySettings {
+    fn default() -> Self {
+        Self {
+            stake: Stake::Proportional(1.0),
+            allow: HashSet::new(),
+            deny: HashSet::new(),
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+impl CopySettings {
+    pub fn stake(mut self, stake: Stake) -> Self {
+        self.stake = stake;
+        self
+    }
+
+    /// Only copies deals on the allowed assets, every asset is allowed until one is added
+    pub fn allow(mut self, asset: impl ToString) -> Self {
+        self.allow.insert(asset.to_string());
+        self
+    }
+
+    /// Never copies deals on `asset`
+    pub fn deny(mut self, asset: impl ToString) -> Self {
+        self.deny.insert(asset.to_string());
+        self
+    }
+
+    /// Deals opened by the leader longer than `delay` ago are skipped, the price already moved too much
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub fn get_stake(&self) -> Stake {
+        self.stake
+    }
+
+    /// Reason to skip a deal on `asset` opened `delay` ago
+    fn skip_reason(&self, asset: &str, delay: Duration) -> Option<String> {
+        if self.deny.contains(asset) || (!self.allow.is_empty() && !self.allow.contains(asset)) {
+            return Some(format!("Asset '{asset}' is not allowed"));
+        }
+        if delay > self.max_delay {
+            return Some(format!(
+                "Delay of {delay:?} is longer than the maximum of {:?}",
+                self.max_delay
+            ));
+        }
+        None
+    }
+}
+
+impl CopyOutcome {
+    fn new(leader: &Deal, follower: &Deal) -> Self {
+        let result = |profit: f64| profit.partial_cmp(&0.0).unwrap_or(Ordering::Equal);
+        Self {
+            leader_profit: leader.profit,
+            follower_profit: follower.profit,
+            same_result: result(leader.profit) == result(follower.profit),
+        }
+    }
+}
+
+impl Copier {
+    pub fn new(leader: PocketOption) -> Self {
+        Self {
+            leader,
+            followers: Arc::new(RwLock::new(HashMap::new())),
+            records: Arc::new(Mutex::new(VecDeque::new())),
+            token: CancellationToken::new(),
+        }
+    }
+
+    /// Adds a follower or replaces the one with the same name, it copies the deals opened from now on
+    pub async fn add_follower(
+        &self,
+        name: impl ToString,
+        client: PocketOption,
+        settings: CopySettings,
+    ) {
+        self.followers
+            .write()
+            .await
+            .insert(name.to_string(), Follower { client, settings });
+    }
+
+    /// Removes the follower, returns false if it didn't exist
+    pub async fn remove_follower(&self, name: &str) -> bool {
+        self.followers.write().await.remove(name).is_some()
+    }
+
+    /// Starts copying the deals opened on the leader from now on
+    pub fn start(&self) -> JoinHandle<()> {
+        // Subscribed before spawning so no deal is missed between `start` and the first poll
+        let events = self.leader.subscribe_opened_deals();
+        info!(target: "Copier", "Started copying the deals of the leader");
+        tokio::spawn(self.clone().run(events))
+    }
+
+    /// Stops copying and waiting for the outcome of the copies, a stopped `Copier` can't be started again
+    pub fn stop(&self) {
+        self.token.cancel();
+    }
+
+    /// Copies of the latest deals, oldest first
+    pub async fn records(&self) -> Vec<CopyRecord> {
+        self.records.lock().await.iter().cloned().collect()
+    }
+
+    async fn run(self, mut events: tokio::sync::broadcast::Receiver<DealOpened>) {
+        loop {
+            let event = tokio::select! {
+                _ = self.token.cancelled() => break,
+                event = events.recv() => event,
+            };
+            match event {
+                Ok(event) => self.copy(event).await,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(target: "Copier", "Missed {missed} deals of the leader")
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+        info!(target: "Copier", "Stopped copying the deals of the leader");
+    }
+
+    async fn copy(&self, event: DealOpened) {
+        let followers: Vec<(String, Follower)> = self
+            .followers
+            .read()
+            .await
+            .iter()
+            .map(|(name, follower)| (name.clone(), follower.clone()))
+            .collect();
+        for (name, follower) in followers {
+            let copier = self.clone();
+            let event = event.clone();
+            // Every follower has its own task so a slow one never delays the others
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = copier.token.cancelled() => {}
+                    _ = copier.copy_to(name, follower, event) => {}
+                }
+            });
+        }
+    }
+
+    async fn copy_to(&self, name: String, follower: Follower, event: DealOpened) {
+        let leader_deal = event.deal;
+        // Measured from the opening on the server, the deal could have waited in a lagging connection
+        let delay = (Utc::now() - leader_deal.open_timestamp)
+            .to_std()
+            .unwrap_or_default();
+        let mut record = CopyRecord {
+            follower: name.clone(),
+            leader_deal: leader_deal.clone(),
+            status: CopyStatus::Copied,
+            follower_deal: None,
+            delay,
+            slippage: None,
+            outcome: None,
+        };
+        if let Some(reason) = follower.settings.skip_reason(&leader_deal.asset, delay) {
+            info!(target: "Copier", "Skipping deal '{}' for '{name}', {reason}", leader_deal.id);
+            record.status = CopyStatus::Skipped(reason);
+            self.push_record(record).await;
+            return;
+        }
+        let amount = follower.settings.stake.amount(leader_deal.amount);
+        let duration = (leader_deal.close_timestamp - leader_deal.open_timestamp)
+            .num_seconds()
+            .max(0) as u32;
+        let (id, deal) = match follower
+            .client
+            .trade(&leader_deal.asset, leader_deal.action(), amount, duration)
+            .await
+        {
+            Ok(res) => res,
+            Err(e) => {
+                warn!(target: "Copier", "Failed to copy deal '{}' for '{name}', {e}", leader_deal.id);
+                record.status = CopyStatus::Failed(e.to_string());
+                self.push_record(record).await;
+                return;
+            }
+        };
+        record.slippage = Some(deal.open_price - leader_deal.open_price);
+        record.follower_deal = Some(deal);
+        self.push_record(record).await;
+
+        let max_wait = Duration::from_secs(duration as u64) + follower.client.get_timeout();
+        let (leader_closed, follower_closed) = tokio::join!(
+            closed_deal(&self.leader, leader_deal.id, max_wait),
+            closed_deal(&follower.client, id, max_wait)
+        );
+        match (leader_closed, follower_closed) {
+            (Some(leader_closed), Some(follower_closed)) => {
+                let outcome = CopyOutcome::new(&leader_closed, &follower_closed);
+                let mut records = self.records.lock().await;
+                if let Some(record) = records.iter_mut().rev().find(|r| {
+                    r.follower == name && r.follower_deal.as_ref().is_some_and(|d| d.id == id)
+                }) {
+                    record.leader_deal = leader_closed;
+                    record.follower_deal = Some(follower_closed);
+                    record.outcome = Some(outcome);
+                }
+            }
+            _ => {
+                warn!(target: "Copier", "Couldn't find the outcome of deal '{}' copied for '{name}'", leader_deal.id)
+            }
+        }
+    }
+
+    async fn push_record(&self, record: CopyRecord) {
+        let mut records = self.records.lock().await;
+        if records.len() >= MAX_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+}
+
+/// Polls the closed deals instead of using `check_results`, so the responses expected by the owners of the clients
+/// are never consumed by the copier
+async fn closed_deal(client: &PocketOption, id: Uuid, max_wait: Duration) -> Option<Deal> {
+    let poll = async {
+        loop {
+            if let Some(deal) = client
+                .get_closed_deals()
+                .await
+                .into_iter()
+                .find(|d| d.id == id)
+            {
+                return deal;
+            }
+            tokio::time::sleep(CLOSED_DEAL_POLL).await;
+        }
+    };
+    tokio::time::timeout(max_wait, poll).await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::pocketoption::{
+        testing::{MockServer, WAIT, deal_for},
+        types::order::Action,
+    };
+
+    #[test]
+    fn test_copy_settings() {
+        let settings = CopySettings::default()
+            .allow("EURUSD_otc")
+            .allow("BTCUSD_otc")
+            .deny("BTCUSD_otc")
+            .max_delay(Duration::from_secs(2));
+        assert!(
+            settings
+                .skip_reason("EURUSD_otc", Duration::from_secs(1))
+                .is_none()
+        );
+        assert!(settings.skip_reason("BTCUSD_otc", Duration::ZERO).is_some());
+        assert!(settings.skip_reason("AUDCAD_otc", Duration::ZERO).is_some());
+        assert!(
+            settings
+                .skip_reason("EURUSD_otc", Duration::from_secs(3))
+                .is_some()
+        );
+        assert!(
+            CopySettings::default()
+                .skip_reason("AUDCAD_otc", Duration::ZERO)
+                .is_none()
+        );
+
+        assert_eq!(Stake::Proportional(0.5).amount(3.33), 1.67);
+        assert_eq!(Stake::Fixed(2.0).amount(100.0), 2.0);
+    }
+
+    /// Waits until the copier recorded `count` copies
+    async fn wait_records(copier: &Copier, count: usize) -> anyhow::Result<Vec<CopyRecord>> {
+        tokio::time::timeout(WAIT, async {
+            loop {
+                let records = copier.records().await;
+                if records.len() >= count {
+                    return records;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("The copier didn't record {count} copies"))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_copy_on_mock_servers() -> anyhow::Result<()> {
+        let leader_server = MockServer::start().await?;
+        let follower_server = MockServer::start().await?;
+        let (leader, mut leader_connection) = leader_server
+            .connect(leader_server.config().build()?)
+            .await?;
+        let (follower, mut follower_connection) = follower_server
+            .connect(follower_server.config().build()?)
+            .await?;
+        let copier = Copier::new(leader.clone());
+        copier
+            .add_follower(
+                "half",
+                follower,
+                CopySettings::default().stake(Stake::Proportional(0.5)),
+            )
+            .await;
+        copier.start();
+
+        let (trade, leader_deal) = tokio::join!(
+            leader.trade("EURUSD_otc", Action::Call, 10.0, 60),
+            leader_connection.open_order()
+        );
+        trade?;
+        let leader_deal = leader_deal?;
+        let order = follower_connection.recv_event("openOrder").await?;
+        assert_eq!(order["amount"], json!(5.0));
+        assert_eq!(order["time"], json!(60));
+        let follower_deal = Deal {
+            open_price: leader_deal.open_price + 0.5,
+            ..deal_for(&order)?
+        };
+        follower_connection
+            .send_event("successopenOrder", &follower_deal)
+            .await?;
+
+        let records = wait_records(&copier, 1).await?;
+        assert_eq!(records[0].follower, "half");
+        assert_eq!(records[0].status, CopyStatus::Copied);
+        assert_eq!(records[0].leader_deal.id, leader_deal.id);
+        assert_eq!(
+            records[0].follower_deal.as_ref().map(|d| d.id),
+            Some(follower_deal.id)
+        );
+        assert_eq!(records[0].slippage, Some(0.5));
+        assert!(records[0].delay < DEFAULT_MAX_DELAY);
+
+        // Opened on the server long ago but only recieved now, like after a reconnection
+        let opened = Utc::now() - chrono::Duration::seconds(10);
+        let late = Deal {
+            open_timestamp: opened,
+            close_timestamp: opened + chrono::Duration::seconds(60),
+            ..deal_for(&json!({"requestId": 7, "asset": "EURUSD_otc", "amount": 10.0, "time": 60}))?
+        };
+        leader_connection
+            .send_event("successopenOrder", &late)
+            .await?;
+        let records = wait_records(&copier, 2).await?;
+        assert_eq!(records[1].leader_deal.id, late.id);
+        assert!(matches!(records[1].status, CopyStatus::Skipped(_)));
+        assert!(records[1].delay >= Duration::from_secs(10));
+        assert!(
+            follower_connection
+                .recv_event_within("openOrder", Duration::from_millis(200))
+                .await
+                .is_none()
+        );
+        copier.stop();
+        Ok(())
+    }
+}
@@ -14,12 +14,21 @@ pub enum PocketOptionError {
     BinaryOptionsToolsError(#[from] BinaryOptionsToolsError),
     #[error("Failed to parse SSID: {0}")]
     SsidParsingError(String),
+    #[error("Invalid SSID, {}", .0.join(", "))]
+    SsidValidationError(Vec<String>),
+    #[error("Authentication failed, the SSID was rejected by the server: {0}")]
+    AuthenticationFailed(String),
+    #[error("Vault error, {0}")]
+    VaultError(String),
+    #[error("Unknown account '{0}'")]
+    UnknownAccount(String),
     #[error("Failed to parse data: {0}")]
     GeneralParsingError(String),
     // #[error("Error making http request: {0}")]
     // HTTPError(#[from] http::Error),
+    /// Invalid TLS configuration, like a root certificate that can't be parsed or pins without the `rustls` feature
     #[error("TLS Certificate error, {0}")]
-    TLSError(#[from] native_tls::Error),
+    TLSError(String),
     // #[error("Failed to connect to websocket server: {0}")]
     // WebsocketConnectionError(#[from] TungsteniteError),
     #[error("Failed to connect to websocket server: {0}")]
@@ -54,6 +63,8 @@ pub enum PocketOptionError {
     Unallowed(String),
     #[error("Error sending request, {0}")]
     WebsocketMessageSendingError(#[from] PocketMessageFail),
+    #[error("Order '{0}' was already submitted but its outcome is unknown")]
+    DuplicateOrder(u64),
     #[error("Expected the data to be non-empty for type '{0}'")]
     EmptyArrayError(String),
     #[error("General compiling error: {0}")]
@@ -62,6 +73,27 @@ pub enum PocketOptionError {
 
 pub type PocketResult<T> = Result<T, PocketOptionError>;
 
+/// Error of `Ssid::parse` and `Ssid::validate`, converted to `SsidParsingError` or `SsidValidationError`
+#[derive(Error, Debug)]
+pub enum SsidError {
+    #[error("Failed to parse SSID: {0}")]
+    Parsing(String),
+    #[error("Invalid SSID, {}", .0.join(", "))]
+    Validation(Vec<String>),
+}
+
+/// Error of the `Vault` operations, converted to `PocketOptionError::VaultError`
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct VaultError(pub String);
+
+#[derive(Error, Debug)]
+#[error("Unknown region '{0}'")]
+pub struct UnknownRegion(pub String);
+
+pub type SsidResult<T> = Result<T, SsidError>;
+pub type VaultResult<T> = Result<T, VaultError>;
+
 impl Error for WebSocketMessage {}
 impl Error for MessageInfo {}
 impl Error for PocketMessageFail {}
@@ -74,3 +106,24 @@ impl From<PocketOptionError> for BinaryOptionsToolsError {
         }
     }
 }
+
+impl From<SsidError> for PocketOptionError {
+    fn from(value: SsidError) -> Self {
+        match value {
+            SsidError::Parsing(error) => Self::SsidParsingError(error),
+            SsidError::Validation(problems) => Self::SsidValidationError(problems),
+        }
+    }
+}
+
+impl From<VaultError> for PocketOptionError {
+    fn from(value: VaultError) -> Self {
+        Self::VaultError(value.0)
+    }
+}
+
+impl From<UnknownRegion> for PocketOptionError {
+    fn from(value: UnknownRegion) -> Self {
+        Self::GeneralParsingError(value.to_string())
+    }
+}
@@ -1,9 +1,16 @@
+pub mod accounts;
+pub mod copier;
 pub mod error;
 pub mod parser;
 pub mod pocket_client;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod types;
 pub mod utils;
 pub mod validators;
+pub mod vault;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 pub mod ws;
 
 // pub use super::pocketoption::ws::basic::WebSocketClient;
@@ -6,7 +6,7 @@ use serde_json::{Value, from_str};
 use tracing::warn;
 
 use binary_options_tools_core::{
-    general::traits::MessageTransfer,
+    general::{limiter::MessagePriority, traits::MessageTransfer},
     reimports::{Bytes, Message},
 };
 
@@ -55,6 +55,9 @@ pub enum WebSocketMessage {
     SuccessupdatePending(Value),
     OpenPendingOrder(OpenPendingOrder),
     SuccessOpenPendingOrder(SuccessOpenPendingOrder),
+    /// The server rejected the ssid, only created by the `Handler`
+    #[serde(skip_deserializing)]
+    NotAuthorized(String),
 
     Raw(RawWebsocketMessage),
     None,
@@ -199,6 +202,7 @@ impl WebSocketMessage {
                     return Self::SuccessOpenPendingOrder(order);
                 }
             }
+            MessageInfo::NotAuthorized => return Self::NotAuthorized(data),
             MessageInfo::Raw(content) => {
                 return WebSocketMessage::Raw(RawWebsocketMessage::from(content.to_owned()));
             }
@@ -235,6 +239,7 @@ impl WebSocketMessage {
             Self::FailOpenPendingOrder(_) => MessageInfo::FailopenPendingOrder,
             Self::SuccessOpenPendingOrder(_) => MessageInfo::SuccessopenPendingOrder,
             Self::OpenPendingOrder(_) => MessageInfo::OpenPendingOrder,
+            Self::NotAuthorized(_) => MessageInfo::NotAuthorized,
             Self::Raw(_) => MessageInfo::None,
             Self::None => MessageInfo::None,
         }
@@ -308,6 +313,7 @@ impl fmt::Display for WebSocketMessage {
             WebSocketMessage::SuccessOpenPendingOrder(order) => write!(f, "{:?}", order),
             WebSocketMessage::FailOpenPendingOrder(order) => write!(f, "{:?}", order),
             WebSocketMessage::OpenPendingOrder(order) => write!(f, "{:?}", order),
+            WebSocketMessage::NotAuthorized(reason) => write!(f, "NotAuthorized, {reason}"),
 
             WebSocketMessage::None => write!(f, "None"),
             // 42["loadHistoryPeriod",{"asset":"#AXP_otc","index":173384282247,"time":1733482800,"offset":540000,"period":3600}]
@@ -380,6 +386,29 @@ impl MessageTransfer for WebSocketMessage {
             None
         }
     }
+
+    fn priority(&self) -> MessagePriority {
+        match self {
+            Self::OpenOrder(_) | Self::OpenPendingOrder(_) => MessagePriority::High,
+            Self::ChangeSymbol(_) | Self::SubscribeSymbol(_) => MessagePriority::Low,
+            _ => MessagePriority::Normal,
+        }
+    }
+
+    fn is_rate_limited(&self) -> bool {
+        match self {
+            Self::FailOpenOrder(fail) => fail.is_rate_limit(),
+            Self::FailOpenPendingOrder(fail) => fail.is_rate_limit(),
+            _ => false,
+        }
+    }
+
+    fn auth_failure(&self) -> Option<String> {
+        if let Self::NotAuthorized(reason) = self {
+            return Some(reason.to_owned());
+        }
+        None
+    }
 }
 
 #[cfg(test)]
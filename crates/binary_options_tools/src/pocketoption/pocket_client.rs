@@ -1,8 +1,15 @@
 use std::{
-    collections::{HashMap, HashSet}, ops::Deref, time::{Duration, Instant}
+    collections::{HashMap, HashSet},
+    iter,
+    ops::Deref,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
+use futures_util::future::{join_all, try_join, try_join_all};
+
 use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
 use url::Url;
 use uuid::Uuid;
@@ -11,18 +18,24 @@ use crate::pocketoption::{
     error::PocketResult,
     parser::basic::LoadHistoryPeriod,
     types::order::SuccessCloseOrder,
+    utils::basic::get_index,
     validators::{candle_validator, order_result_validator},
-    ws::ssid::Ssid,
+    ws::ssid::{Ssid, ValidatedSsid},
 };
 use binary_options_tools_core::{
     error::BinaryOptionsToolsError,
     general::{
         client::WebSocketClient,
-        config::{Config, _Config},
+        config::{_Config, Config, ConfigBuilder},
+        credentials::{CredentialProvider, CredentialStore, StaticCredentials},
+        ready::ConnectionState,
+        send::{ReconnectPolicy, SendTicket},
         stream::FilteredRecieverStream,
         traits::{MessageTransfer, ValidatorTrait},
         types::{Callback, Data},
     },
+    reimports::{CancellationToken, Message},
+    utils::time::{cancellable, timeout},
 };
 
 use super::{
@@ -30,14 +43,21 @@ use super::{
     parser::message::WebSocketMessage,
     types::{
         base::{ChangeSymbol, RawWebsocketMessage},
-        callback::PocketCallback,
-        data::PocketData,
+        callback::{ConnectionRole, PocketCallback, market_index},
+        data::{DealOpened, PocketData},
         info::MessageInfo,
         order::{Action, Deal, OpenOrder},
         update::{DataCandle, UpdateBalance},
     },
     validators::{history_validator, order_validator},
-    ws::{connect::PocketConnect, listener::Handler, stream::StreamAsset},
+    vault::Vault,
+    ws::{
+        connect::PocketConnect,
+        health::{HealthTable, ServerHealth},
+        listener::Handler,
+        regions::{Regions, ServerSelection},
+        stream::StreamAsset,
+    },
 };
 
 /// A client for interacting with the Pocket Option trading platform.
@@ -95,16 +115,13 @@ use super::{
 /// # Thread Safety
 /// The client is inherently thread-safe as it internally uses an Arc-wrapped WebSocket client.
 /// It can be safely cloned and shared between multiple tasks.
+type PocketClient = WebSocketClient<WebSocketMessage, Handler, PocketConnect, Ssid, PocketData, ()>;
+
 #[derive(Clone)]
 pub struct PocketOption {
-    client: WebSocketClient<
-        WebSocketMessage,
-        Handler,
-        PocketConnect,
-        Ssid,
-        PocketData,
-        (),
-        >,
+    client: PocketClient,
+    /// Dedicated market data connections, when empty `client` is used for everything
+    market: Arc<Vec<PocketClient>>,
 }
 
 impl Deref for PocketOption {
@@ -129,25 +146,7 @@ impl PocketOption {
     /// let client = PocketOption::new("your-session-id").await?;
     /// ```
     pub async fn new(ssid: impl ToString) -> PocketResult<Self> {
-        let ssid = Ssid::parse(ssid)?;
-        let data = Data::new(PocketData::default());
-        let handler = Handler::new(ssid.clone());
-        let timeout = Duration::from_millis(500);
-        let callback = PocketCallback;
-        let config = _Config::new(timeout, vec![], ())
-            .builder()
-            .reconnect_time(5)
-            .build()?;
-        let client = WebSocketClient::init(
-            ssid,
-            PocketConnect {},
-            data,
-            handler,
-            Some(Callback::new(std::sync::Arc::new(callback))),
-            config,
-        )
-        .await?;
-        Ok(Self { client })
+        Self::new_with_config(ssid, Self::default_config().build()?).await
     }
 
     /// Creates a new PocketOption client with a custom WebSocket URL.
@@ -166,30 +165,17 @@ impl PocketOption {
     /// let client = PocketOption::new_with_url("your-session-id", url).await?;
     /// ```
     pub async fn new_with_url(ssid: impl ToString, url: Url) -> PocketResult<Self> {
-        let ssid = Ssid::parse(ssid)?;
-        let data = Data::new(PocketData::default());
-        let handler = Handler::new(ssid.clone());
-        let timeout = Duration::from_millis(500);
-        let callback = PocketCallback;
-        let config = _Config::new(timeout, vec![], ())
-            .builder()
-            .reconnect_time(5)
+        let config = Self::default_config()
             .default_connection_url(HashSet::from([url]))
             .build()?;
-        let client = WebSocketClient::init(
-            ssid,
-            PocketConnect {},
-            data,
-            handler,
-            Some(Callback::new(std::sync::Arc::new(callback))),
-            config,
-        )
-        .await?;
-        // println!("Initialized!");
-        Ok(Self { client })
+        Self::new_with_config(ssid, config).await
     }
 
-        /// Creates a new PocketOption client with a provided configuration.
+    /// Creates a new PocketOption client with a provided configuration.
+    ///
+    /// The client only waits for the messages listed in the `bootstrap` field of the config,
+    /// use `MessageInfo::bootstrap()` to wait for the same messages as `PocketOption::new`,
+    /// the same happens with `rate_limits` and `MessageInfo::rate_limits()`.
     ///
     /// # Arguments
     /// * `ssid` - Session ID for authentication
@@ -209,23 +195,170 @@ impl PocketOption {
     pub async fn new_with_config(
         ssid: impl ToString,
         config: Config<PocketData, WebSocketMessage, ()>,
+    ) -> PocketResult<Self> {
+        Self::new_with_connections(ssid, config, 0).await
+    }
+
+    /// Creates a new PocketOption client with a dedicated trading connection and `market_connections` market data connections.
+    ///
+    /// Orders, deals and raw messages use the trading connection, candles, history and subscriptions
+    /// are split by asset between the market data connections so heavy streams don't delay order confirmations.
+    /// Every connection uses the same `Ssid` and updates the same balance, deals and payouts.
+    /// With `market_connections` set to 0 a single connection is used for everything.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let config = PocketOption::default_config().build()?;
+    /// let client = PocketOption::new_with_connections("your-session-id", config, 2).await?;
+    /// ```
+    pub async fn new_with_connections(
+        ssid: impl ToString,
+        config: Config<PocketData, WebSocketMessage, ()>,
+        market_connections: usize,
+    ) -> PocketResult<Self> {
+        Self::new_with_servers(ssid, config, market_connections, ServerSelection::default()).await
+    }
+
+    /// Same as `PocketOption::new_with_connections` but `servers` decides how the server is chosen
+    /// when none of the `default_connection_url` of the config works.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let config = PocketOption::default_config().build()?;
+    /// let servers = ServerSelection::Region("EUROPE".into());
+    /// let client = PocketOption::new_with_servers("your-session-id", config, 0, servers).await?;
+    /// ```
+    pub async fn new_with_servers(
+        ssid: impl ToString,
+        config: Config<PocketData, WebSocketMessage, ()>,
+        market_connections: usize,
+        servers: ServerSelection,
     ) -> PocketResult<Self> {
         let ssid = Ssid::parse(ssid)?;
-        let data = Data::new(PocketData::default());
-        let handler = Handler::new(ssid.clone());
-        let callback = PocketCallback;
-        
-        let client = WebSocketClient::init(
-            ssid,
-            PocketConnect {},
-            data,
-            handler,
-            Some(Callback::new(std::sync::Arc::new(callback))),
+        ssid.validate()?;
+        Self::new_with_provider(
+            Arc::new(StaticCredentials::new(ssid)),
             config,
+            market_connections,
+            servers,
         )
-        .await?;
-        
-        Ok(Self { client })
+        .await
+    }
+
+    /// Same as `PocketOption::new_with_servers` but the `Ssid` comes from `provider`, which is asked for a new one
+    /// when a connection can't authenticate and every `refresh_interval`, so a rotated `Ssid` is used without restarting.
+    /// `Ssid`s that fail `Ssid::validate` are ignored and the previous one is kept.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let provider = FileCredentials::new("ssid.txt").refresh_every(Duration::from_secs(300));
+    /// let config = PocketOption::default_config().build()?;
+    /// let client = PocketOption::new_with_provider(Arc::new(provider), config, 0, ServerSelection::default()).await?;
+    /// ```
+    pub async fn new_with_provider(
+        provider: Arc<dyn CredentialProvider<Ssid>>,
+        config: Config<PocketData, WebSocketMessage, ()>,
+        market_connections: usize,
+        servers: ServerSelection,
+    ) -> PocketResult<Self> {
+        let ssid = CredentialStore::from_provider(Arc::new(ValidatedSsid(provider))).await?;
+        if let ServerSelection::Region(name) = &servers {
+            let demo = ssid.load().demo();
+            if Regions::is_other_account(Regions::region_url(name)?, demo) {
+                return Err(PocketOptionError::Unallowed(format!(
+                    "the region '{name}' is not available for the {} account",
+                    if demo { "demo" } else { "real" }
+                )));
+            }
+        }
+        let connect = PocketConnect {
+            servers,
+            health: HealthTable::default(),
+        };
+        let state = PocketData::new(config.get_channels().stream);
+        let (client, market) = if market_connections == 0 {
+            let client =
+                Self::connect(&ssid, &connect, &config, &state, ConnectionRole::All).await?;
+            (client, Vec::new())
+        } else {
+            let trading = Self::connect(&ssid, &connect, &config, &state, ConnectionRole::Trading);
+            let market = try_join_all((0..market_connections).map(|index| {
+                let role = ConnectionRole::MarketData {
+                    index,
+                    total: market_connections,
+                };
+                Self::connect(&ssid, &connect, &config, &state, role)
+            }));
+            try_join(trading, market).await?
+        };
+        Ok(Self {
+            client,
+            market: Arc::new(market),
+        })
+    }
+
+    /// Creates a client from the profile `name` of the default vault, see `Vault::open_default` for
+    /// the environment variables that locate and unlock it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// // POCKET_OPTION_VAULT_PASSPHRASE=... cargo run
+    /// let client = PocketOption::from_profile("demo").await?;
+    /// ```
+    pub async fn from_profile(name: impl ToString) -> PocketResult<Self> {
+        // Deriving the key is slow on purpose so it runs outside of the async runtime
+        let vault = tokio::task::spawn_blocking(Vault::open_default)
+            .await
+            .map_err(|e| PocketOptionError::VaultError(e.to_string()))??;
+        Self::from_vault_profile(&vault, name).await
+    }
+
+    /// Creates a client from the profile `name` of an already opened `vault`
+    pub async fn from_vault_profile(vault: &Vault, name: impl ToString) -> PocketResult<Self> {
+        vault.profile(&name.to_string())?.connect().await
+    }
+
+    /// Configuration used by `PocketOption::new`
+    pub fn default_config() -> ConfigBuilder<PocketData, WebSocketMessage, ()> {
+        _Config::new(Duration::from_secs(10), vec![], ())
+            .builder()
+            .reconnect_time(5)
+            .bootstrap(MessageInfo::bootstrap())
+            .rate_limits(MessageInfo::rate_limits())
+    }
+
+    /// Opens a connection that shares `state` with the other connections of the client
+    async fn connect(
+        ssid: &CredentialStore<Ssid>,
+        connect: &PocketConnect,
+        config: &Config<PocketData, WebSocketMessage, ()>,
+        state: &PocketData,
+        role: ConnectionRole,
+    ) -> PocketResult<PocketClient> {
+        let data = Data::new_with_channels(state.clone(), config.get_channels());
+        WebSocketClient::init(
+            ssid.clone(),
+            connect.clone(),
+            data,
+            Handler::new(ssid.clone()),
+            Some(Callback::new(Arc::new(PocketCallback::new(role)))),
+            config.clone(),
+        )
+        .await
+        .map_err(|e| match e {
+            BinaryOptionsToolsError::AuthenticationFailed(reason) => {
+                PocketOptionError::AuthenticationFailed(reason)
+            }
+            e => e.into(),
+        })
+    }
+
+    /// Connection used for the market data requests of `asset`
+    fn market(&self, asset: &str) -> &PocketClient {
+        match self.market.len() {
+            0 => &self.client,
+            total => &self.market[market_index(asset, total)],
+        }
     }
 
     /// Executes a trade with the specified parameters.
@@ -250,31 +383,134 @@ impl PocketOption {
         amount: f64,
         time: u32,
     ) -> PocketResult<(Uuid, Deal)> {
-        let order = OpenOrder::new(
+        self.trade_with_id(asset, action, amount, time, get_index()?)
+            .await
+    }
+
+    /// Same as `trade` but using `request_id` as the client order ID, the order is opened at most once for each ID.
+    ///
+    /// Calling it again with the ID of an order that was already submitted never sends the order again,
+    /// it returns the deal opened by the first call or waits for it.
+    ///
+    /// # Errors
+    /// * Returns DuplicateOrder if the order was already submitted but the deal it opened can't be found
+    ///
+    /// # Examples
+    /// ```rust
+    /// let request_id = 42;
+    /// let res = match client.trade_with_id("EURUSD", Action::Call, 100.0, 60, request_id).await {
+    ///     Ok(res) => res,
+    ///     // Safe to retry, this will never open a second position
+    ///     Err(_) => client.trade_with_id("EURUSD", Action::Call, 100.0, 60, request_id).await?,
+    /// };
+    /// ```
+    pub async fn trade_with_id(
+        &self,
+        asset: impl ToString,
+        action: Action,
+        amount: f64,
+        time: u32,
+        request_id: u64,
+    ) -> PocketResult<(Uuid, Deal)> {
+        let data = &self.client.data;
+        if let Some(deal) = data.get_deal_by_request_id(request_id).await {
+            debug!("Order '{request_id}' was already opened");
+            return Ok((deal.id, deal));
+        }
+        if !data.try_submit_order(request_id).await {
+            return self.wait_submitted_order(request_id).await;
+        }
+        let order = OpenOrder::with_request_id(
             amount,
             asset.to_string(),
             action,
             time,
-            self.client.credentials.demo() as u32,
-        )?;
-        let request_id = order.request_id;
+            self.client.credentials.load().demo() as u32,
+            request_id,
+        );
+        let ticket = SendTicket::default();
         let res = self
             .client
-            .send_message_with_timout(
-                self.get_timeout()?,
+            .send_tracked_message_with_timout(
+                self.get_timeout(),
                 "Trade",
                 WebSocketMessage::OpenOrder(order),
                 MessageInfo::SuccessopenOrder,
                 Box::new(order_validator(request_id)),
+                ReconnectPolicy::Reconcile(MessageInfo::UpdateOpenedDeals),
+                ticket.clone(),
             )
-            .await?;
-        if let WebSocketMessage::SuccessopenOrder(order) = res {
-            debug!("Successfully opened buy trade!");
-            return Ok((order.id, order));
+            .await;
+        let res = match res {
+            Ok(res) => res,
+            Err(e) => {
+                // It's safe to submit the order again if the server refused it or if it never left the queue
+                if matches!(e, BinaryOptionsToolsError::WebSocketMessageError(_)) || ticket.cancel()
+                {
+                    data.remove_submitted_order(request_id).await;
+                }
+                return Err(e.into());
+            }
+        };
+        match res {
+            WebSocketMessage::SuccessopenOrder(order) => {
+                debug!("Successfully opened buy trade!");
+                Ok((order.id, order))
+            }
+            WebSocketMessage::UpdateOpenedDeals(deals) => {
+                debug!("Found the opened trade after reconnecting");
+                deals
+                    .0
+                    .into_iter()
+                    .find(|d| d.request_id.is_some_and(|id| id == request_id))
+                    .map(|order| (order.id, order))
+                    .ok_or(PocketOptionError::UnreachableError(
+                        "Error finding correct trade".into(),
+                    ))
+            }
+            res => Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
+                res.info(),
+            )),
         }
-        Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
-            res.info(),
-        ))
+    }
+
+    /// Waits for the response of an order that was already submitted without sending it again
+    async fn wait_submitted_order(&self, request_id: u64) -> PocketResult<(Uuid, Deal)> {
+        info!(target: "Trade", "Order '{request_id}' was already submitted, waiting for it instead of sending it again");
+        let data = &self.client.data;
+        // Every waiter gets the event, the responses of the order only reach one of the callers
+        let mut opened = data.subscribe_opened_deals();
+        // The deal could have arrived before subscribing
+        if let Some(deal) = data.get_deal_by_request_id(request_id).await {
+            return Ok((deal.id, deal));
+        }
+        let res = timeout(
+            self.get_timeout(),
+            async {
+                loop {
+                    match opened.recv().await {
+                        Ok(event) if event.deal.request_id == Some(request_id) => break,
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            if data.get_deal_by_request_id(request_id).await.is_some() {
+                                break;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+                Ok::<_, BinaryOptionsToolsError>(())
+            },
+            "Trade".into(),
+        )
+        .await;
+        if let Err(e) = res {
+            warn!(target: "Trade", "Failed to find the response of order '{request_id}', {e}");
+        }
+        data.get_deal_by_request_id(request_id)
+            .await
+            .map(|deal| (deal.id, deal))
+            .ok_or(PocketOptionError::DuplicateOrder(request_id))
     }
 
     /// Places a buy (CALL) order.
@@ -389,7 +625,7 @@ impl PocketOption {
         debug!("Trade result not found in closed deals list, waiting for closing order to check.");
         if let Some(timestamp) = self.get_deal_end_time(trade_id).await {
             let exp = timestamp
-                .signed_duration_since(Utc::now() - self.get_timeout()?) // TODO: Change this since the current time depends on the timezone.
+                .signed_duration_since(Utc::now() - self.get_timeout()) // TODO: Change this since the current time depends on the timezone.
                 .to_std()
                 .map_err(BinaryOptionsToolsError::from)?;
             debug!(target: "CheckResult", "Expiration time in {exp:?} seconds.");
@@ -398,11 +634,12 @@ impl PocketOption {
             let res: WebSocketMessage = match self
                 .client
                 .send_message_with_timeout_and_retry(
-                    exp + self.get_timeout()?,
+                    exp + self.get_timeout(),
                     "CheckResult",
                     WebSocketMessage::None,
                     MessageInfo::SuccesscloseOrder,
                     Box::new(order_result_validator(trade_id)),
+                    ReconnectPolicy::Reconcile(MessageInfo::UpdateClosedDeals),
                 )
                 .await
             {
@@ -425,24 +662,34 @@ impl PocketOption {
                 }
             };
 
-            if let WebSocketMessage::SuccesscloseOrder(order) = res {
-                return order
-                    .deals
-                    .iter()
-                    .find(|d| d.id == trade_id)
-                    .cloned()
-                    .ok_or(PocketOptionError::UnreachableError(
-                        "Error finding correct trade".into(),
+            let deals = match res {
+                WebSocketMessage::SuccesscloseOrder(order) => order.deals,
+                WebSocketMessage::UpdateClosedDeals(deals) => deals.0,
+                res => {
+                    return Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
+                        res.info(),
                     ));
-            }
-            return Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
-                res.info(),
-            ));
+                }
+            };
+            return deals.iter().find(|d| d.id == trade_id).cloned().ok_or(
+                PocketOptionError::UnreachableError("Error finding correct trade".into()),
+            );
         }
         warn!("No opened trade with the given uuid please check if you are passing the correct id");
         Err(BinaryOptionsToolsError::Unallowed("Couldn't check result for a deal that is not in the list of opened trades nor closed trades.".into()).into())
     }
 
+    /// Same as `check_results` but stops waiting as soon as `token` is cancelled, returning a `Cancelled` error.
+    ///
+    /// Dropping the returned future has the same effect, in both cases the pending request is unregistered.
+    pub async fn check_results_with_cancel(
+        &self,
+        trade_id: Uuid,
+        token: &CancellationToken,
+    ) -> PocketResult<Deal> {
+        cancellable(token, self.check_results(trade_id), "CheckResults".into()).await
+    }
+
     pub async fn get_candles_advanced(&self, asset: impl ToString, time: i64, period: i64, offset: i64) -> PocketResult<Vec<DataCandle>> {
         info!(target: "GetCandlesAdvanced", "Retrieving candles for asset '{}' with period of '{}' and offset of '{}'", asset.to_string(), period, offset);
         if time == 0 {
@@ -458,13 +705,14 @@ impl PocketOption {
         );
         let request = LoadHistoryPeriod::new(asset.to_string(), time, period, offset)?;
         let res = self
-            .client
+            .market(&asset.to_string())
             .send_message_with_timeout_and_retry(
-                self.get_timeout()?,
+                self.get_timeout(),
                 "GetCandles",
                 WebSocketMessage::GetCandles(request),
                 MessageInfo::LoadHistoryPeriod,
                 Box::new(candle_validator(index)),
+                ReconnectPolicy::Resend,
             )
             .await?;
         if let WebSocketMessage::LoadHistoryPeriod(history) = res {
@@ -475,6 +723,23 @@ impl PocketOption {
         ))
     }
 
+    /// Same as `get_candles_advanced` but stops waiting as soon as `token` is cancelled
+    pub async fn get_candles_advanced_with_cancel(
+        &self,
+        asset: impl ToString,
+        time: i64,
+        period: i64,
+        offset: i64,
+        token: &CancellationToken,
+    ) -> PocketResult<Vec<DataCandle>> {
+        cancellable(
+            token,
+            self.get_candles_advanced(asset, time, period, offset),
+            "GetCandlesAdvanced".into(),
+        )
+        .await
+    }
+
     /// Retrieves historical candle data for a specific asset.
     ///
     /// # Arguments
@@ -525,13 +790,14 @@ impl PocketOption {
 
         let request = ChangeSymbol::new(asset.to_string(), period);
         let res = self
-            .client
+            .market(&asset.to_string())
             .send_message_with_timeout_and_retry(
-                self.get_timeout()?,
+                self.get_timeout(),
                 "History",
                 WebSocketMessage::ChangeSymbol(request),
                 MessageInfo::UpdateHistoryNew,
                 Box::new(history_validator(asset.to_string(), period)),
+                ReconnectPolicy::Resend,
             )
             .await?;
         if let WebSocketMessage::UpdateHistoryNew(history) = res {
@@ -542,6 +808,16 @@ impl PocketOption {
         ))
     }
 
+    /// Same as `history` but stops waiting as soon as `token` is cancelled
+    pub async fn history_with_cancel(
+        &self,
+        asset: impl ToString,
+        period: i64,
+        token: &CancellationToken,
+    ) -> PocketResult<Vec<DataCandle>> {
+        cancellable(token, self.history(asset, period), "History".into()).await
+    }
+
     pub async fn get_closed_deals(&self) -> Vec<Deal> {
         info!(target: "GetClosedDeals", "Retrieving list of closed deals");
         self.client.data.get_closed_deals().await
@@ -557,6 +833,11 @@ impl PocketOption {
         self.client.data.get_opened_deals().await
     }
 
+    /// Recieves every deal opened on the account from now on, including the ones placed from the website or another client
+    pub fn subscribe_opened_deals(&self) -> broadcast::Receiver<DealOpened> {
+        self.client.data.subscribe_opened_deals()
+    }
+
     pub async fn get_balance(&self) -> UpdateBalance {
         info!(target: "GetBalance", "Retrieving account balance");
         self.client.data.get_balance().await
@@ -564,7 +845,100 @@ impl PocketOption {
 
     pub async fn is_demo(&self) -> bool {
         info!(target: "IsDemo", "Retrieving demo status");
-        self.client.credentials.demo()
+        self.client.credentials.load().demo()
+    }
+
+    /// Checks if the authentication, balance and assets of every connection were already recieved
+    pub fn is_ready(&self) -> bool {
+        self.client.is_ready() && self.market.iter().all(|c| c.is_ready())
+    }
+
+    /// State of the connections, `AuthFailed` as soon as the server rejects the ssid in any of them
+    pub fn connection_state(&self) -> ConnectionState {
+        let mut state = ConnectionState::Ready;
+        for client in iter::once(&self.client).chain(self.market.iter()) {
+            match client.data.readiness().state() {
+                ConnectionState::AuthFailed(reason) => return ConnectionState::AuthFailed(reason),
+                ConnectionState::Connecting => state = ConnectionState::Connecting,
+                ConnectionState::Ready => {}
+            }
+        }
+        state
+    }
+
+    /// Waits until every connection is ready, useful after a reconnection
+    pub async fn wait_until_ready(&self) {
+        join_all(
+            std::iter::once(&self.client)
+                .chain(self.market.iter())
+                .map(|c| c.wait_until_ready()),
+        )
+        .await;
+    }
+
+    /// Reconnects every connection with `ssid`, for example to move between the demo and the real account.
+    /// The balance and deals of the previous account are cleared while subscriptions and `StreamAsset`s keep working.
+    /// If the server rejects `ssid` the connections go back to the previous credentials and it fails with `AuthenticationFailed`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let stream = client.subscribe_symbol("EURUSD_otc").await?;
+    /// client.switch_account(real_ssid).await?;
+    /// assert!(!client.is_demo().await);
+    /// ```
+    pub async fn switch_account(&self, ssid: impl ToString) -> PocketResult<()> {
+        let ssid = Ssid::parse(ssid)?;
+        ssid.validate()?;
+        let credentials = &self.client.credentials;
+        info!(target: "SwitchAccount", "Switching to the {} account", if ssid.demo() { "demo" } else { "real" });
+        // The connections go back to the previous ssid by themselves if the new one is rejected
+        credentials
+            .replace_until_accepted(Arc::new(StaticCredentials::new(ssid)))
+            .await?;
+        // Cleared once the new connection authenticates, messages still in flight on the old one would fill it again
+        self.client.data.reset_account_on_auth();
+
+        let clients: Vec<_> = iter::once(&self.client).chain(self.market.iter()).collect();
+        // Subscribed before closing so the new connections can't be missed
+        let connections: Vec<_> = clients
+            .iter()
+            .map(|c| c.data.readiness().connections())
+            .collect();
+        for client in clients.iter() {
+            client.sender.priority_send(Message::Close(None)).await?;
+        }
+        let authenticated =
+            clients
+                .iter()
+                .zip(connections)
+                .map(|(client, mut connections)| async move {
+                    // The readiness lives as long as `client` so this can't fail
+                    let _ = connections.changed().await;
+                    client
+                        .data
+                        .readiness()
+                        .wait_authenticated()
+                        .await
+                        .map_err(BinaryOptionsToolsError::AuthenticationFailed)
+                });
+        let max_wait = self.get_connection_initialization_timeout();
+        match timeout(
+            max_wait,
+            try_join_all(authenticated),
+            "SwitchAccount".into(),
+        )
+        .await
+        {
+            Err(BinaryOptionsToolsError::AuthenticationFailed(reason)) => {
+                warn!(target: "SwitchAccount", "The new ssid was rejected, reconnecting with the previous one");
+                Err(PocketOptionError::AuthenticationFailed(reason))
+            }
+            res => {
+                // Only a rejection restores the previous ssid, after a timeout the new one is kept
+                credentials.accept();
+                res.map(|_| ()).map_err(PocketOptionError::from)
+            }
+        }
     }
 
     pub async fn get_payout(&self) -> HashMap<String, i32> {
@@ -787,6 +1161,22 @@ impl PocketOption {
             .await?)
     }
 
+    /// Same as `create_raw_order_with_timeout_and_retry` but stops waiting as soon as `token` is cancelled
+    pub async fn create_raw_order_with_timeout_and_retry_and_cancel(
+        &self,
+        message: impl Into<RawWebsocketMessage>,
+        validator: Box<dyn ValidatorTrait<RawWebsocketMessage> + Send + Sync>,
+        timeout: Duration,
+        token: &CancellationToken,
+    ) -> PocketResult<RawWebsocketMessage> {
+        cancellable(
+            token,
+            self.create_raw_order_with_timeout_and_retry(message, validator, timeout),
+            "CreateRawOrderWithRetry".into(),
+        )
+        .await
+    }
+
     /// Creates a stream of validated WebSocket messages.
     ///
     /// # Arguments
@@ -828,6 +1218,11 @@ impl PocketOption {
         Utc::now() + Duration::from_secs(2 * 3600 + 123)
     }
 
+    /// Success rate, last failure, handshake latency and cool-down of every server the client tried
+    pub async fn server_health(&self) -> HashMap<String, ServerHealth> {
+        self.client.connector.health.snapshot().await
+    }
+
     pub fn kill(self) {
         drop(self)
     }
@@ -849,6 +1244,7 @@ mod tests {
     use url::Url;
 
     use super::*;
+    use crate::pocketoption::testing::{BALANCE, DEMO_SSID, MockServer, WAIT};
 
     fn to_future(stream: StreamAsset, id: i32) -> JoinHandle<anyhow::Result<()>> {
         tokio::spawn(async move {
@@ -1256,4 +1652,195 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_trade_on_mock_server() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let (client, mut connection) = server.connect(server.config().build()?).await?;
+        assert_eq!(connection.ssid, DEMO_SSID);
+        assert_eq!(client.get_balance().await.balance, BALANCE);
+
+        let (res, deal) = tokio::join!(
+            client.trade("EURUSD_otc", Action::Call, 5.0, 60),
+            connection.open_order()
+        );
+        let (id, opened) = res?;
+        let deal = deal?;
+        assert_eq!((id, &opened), (deal.id, &deal));
+        assert_eq!(opened.asset, "EURUSD_otc");
+        assert_eq!(opened.amount, 5.0);
+        assert_eq!(client.get_opened_deals().await, vec![deal]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_trades_with_the_same_id() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let (client, mut connection) = server.connect(server.config().build()?).await?;
+
+        let trades = try_join_all((0..8).map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client
+                    .trade_with_id("EURUSD_otc", Action::Put, 1.0, 60, 42)
+                    .await
+            })
+        }));
+        // Callers that didn't send the order get the deal as soon as it's opened too
+        let (results, deal) = tokio::time::timeout(WAIT, async {
+            tokio::join!(trades, connection.open_order())
+        })
+        .await?;
+        let deal = deal?;
+        for res in results? {
+            assert_eq!(res?.0, deal.id);
+        }
+        // Only one of the calls sent the order
+        assert!(
+            connection
+                .recv_event_within("openOrder", Duration::from_millis(500))
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_preferred_server_is_the_connected_one() -> anyhow::Result<()> {
+        let servers = [MockServer::start().await?, MockServer::start().await?];
+        let config = servers[0]
+            .config()
+            .default_connection_url(servers.iter().map(MockServer::url).collect())
+            .build()?;
+        let client = tokio::spawn(PocketOption::new_with_config(DEMO_SSID, config));
+        // Both servers accept the handshake, only the connection that was kept authenticates
+        let (url, mut connection) = tokio::select! {
+            connection = servers[0].accept() => (servers[0].url(), connection?),
+            connection = servers[1].accept() => (servers[1].url(), connection?),
+        };
+        connection.bootstrap().await?;
+        let client = tokio::time::timeout(WAIT, client).await???;
+        assert_eq!(
+            client.client.connector.health.preferred().await,
+            Some(url.to_string())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trade_is_retried_after_a_timeout() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let config = server
+            .config()
+            .timeout(Duration::from_millis(500))
+            .build()?;
+        let (client, connection) = server.connect(config).await?;
+        drop(connection);
+        // The order stays queued until the new connection is ready
+        let mut connection = server.accept().await?;
+        let res = client
+            .trade_with_id("EURUSD_otc", Action::Call, 1.0, 60, 42)
+            .await;
+        assert!(res.is_err());
+
+        connection.bootstrap().await?;
+        let (res, deal) = tokio::join!(
+            client.trade_with_id("EURUSD_otc", Action::Call, 2.0, 60, 42),
+            connection.open_order()
+        );
+        let deal = deal?;
+        assert_eq!(res?.0, deal.id);
+        assert_eq!(deal.amount, 2.0);
+        // The order of the first call was dropped instead of being sent late
+        assert!(
+            connection
+                .recv_event_within("openOrder", Duration::from_millis(500))
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_region_of_the_other_account_is_rejected() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let servers = ServerSelection::Region("EUROPE".into());
+        let res =
+            PocketOption::new_with_servers(DEMO_SSID, server.config().build()?, 0, servers).await;
+        assert!(matches!(res, Err(PocketOptionError::Unallowed(_))));
+        // Rejected before connecting
+        assert!(
+            server
+                .accept_within(Duration::from_millis(100))
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_switch_account_resets_the_account_and_keeps_the_streams() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let (client, mut connection) = server.connect(server.config().build()?).await?;
+        let (trade, deal) = tokio::join!(
+            client.trade("EURUSD_otc", Action::Call, 1.0, 60),
+            connection.open_order()
+        );
+        assert_eq!(trade?.0, deal?.id);
+        let stream = client.client.data.add_stream("EURUSD_otc".into()).await;
+
+        let switch = tokio::spawn({
+            let client = client.clone();
+            let ssid = DEMO_SSID.replace("87742848", "12345678");
+            async move { client.switch_account(ssid).await }
+        });
+        connection.closed().await?;
+        let mut connection = server.accept().await?;
+        assert!(connection.ssid.contains("12345678"));
+        // Nothing is cleared until the new account is authenticated
+        assert_eq!(client.get_opened_deals().await.len(), 1);
+        connection.bootstrap_with_balance(50.0).await?;
+        tokio::time::timeout(WAIT, switch).await???;
+        assert!(client.get_opened_deals().await.is_empty());
+        assert_eq!(client.get_balance().await.balance, 50.0);
+
+        let time = Utc::now().timestamp() as f64;
+        connection
+            .send_event(
+                "updateStream",
+                &serde_json::json!([["EURUSD_otc", time, 1.5]]),
+            )
+            .await?;
+        let candle = tokio::time::timeout(WAIT, stream.recieve()).await??;
+        assert_eq!(candle.close, 1.5);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_rejected_switch_account_goes_back_to_the_previous_ssid() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let (client, connection) = server.connect(server.config().build()?).await?;
+        let switch = tokio::spawn({
+            let client = client.clone();
+            let ssid = DEMO_SSID.replace("87742848", "12345678");
+            async move { client.switch_account(ssid).await }
+        });
+        connection.closed().await?;
+        let mut rejected = server.accept().await?;
+        assert!(rejected.ssid.contains("12345678"));
+        rejected.reject().await?;
+        let result = tokio::time::timeout(WAIT, switch).await??;
+        assert!(matches!(
+            result,
+            Err(PocketOptionError::AuthenticationFailed(_))
+        ));
+
+        let mut connection = server.accept().await?;
+        assert!(connection.ssid.contains("87742848"));
+        connection.bootstrap().await?;
+        tokio::time::timeout(WAIT, client.wait_until_ready()).await?;
+        assert!(client.is_ready());
+        assert_eq!(client.client.credentials.load().uid(), 87742848);
+        Ok(())
+    }
 }
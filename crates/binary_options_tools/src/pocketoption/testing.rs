@@ -0,0 +1,245 @@
+//! Pocket Option server running on localhost, used to test `PocketOption` without a real account
+
+use std::{collections::HashSet, time::Duration};
+
+use async_channel::{Receiver, Sender, unbounded};
+use chrono::{SubsecRound, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{WebSocketStream, accept_async, tungstenite::Message};
+use url::Url;
+use uuid::Uuid;
+
+use binary_options_tools_core::general::config::{Config, ConfigBuilder};
+
+use super::{
+    parser::message::WebSocketMessage,
+    pocket_client::PocketOption,
+    types::{data::PocketData, order::Deal},
+};
+
+/// Time the tests wait for something that should happen right away
+pub const WAIT: Duration = Duration::from_secs(5);
+
+/// Ssid of a demo account, the mock server accepts any ssid
+pub const DEMO_SSID: &str =
+    r#"42["auth",{"session":"looc69ct294h546o368s0lct7d","isDemo":1,"uid":87742848,"platform":2}]"#;
+
+/// Balance sent in the bootstrap messages
+pub const BALANCE: f64 = 1000.0;
+
+/// Accepts websocket connections on localhost and completes the socket.io handshake of each one
+pub struct MockServer {
+    url: Url,
+    connections: Receiver<MockConnection>,
+}
+
+/// Server side of an authenticated connection, dropping it closes the connection
+pub struct MockConnection {
+    /// `auth` message sent by the client
+    pub ssid: String,
+    socket: WebSocketStream<TcpStream>,
+}
+
+impl MockServer {
+    pub async fn start() -> anyhow::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = Url::parse(&format!(
+            "ws://{}/socket.io/?EIO=4&transport=websocket",
+            listener.local_addr()?
+        ))?;
+        let (sender, connections) = unbounded();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // `WebSocketClient::init` keeps a connection open without reading from it, it must not block the others
+                tokio::spawn(handshake(stream, sender.clone()));
+            }
+        });
+        Ok(Self { url, connections })
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Config that connects to this server and reconnects after one second
+    pub fn config(&self) -> ConfigBuilder<PocketData, WebSocketMessage, ()> {
+        PocketOption::default_config()
+            .default_connection_url(HashSet::from([self.url()]))
+            .reconnect_time(1)
+    }
+
+    /// Waits for the next authenticated connection, fails if the client doesn't connect in time
+    pub async fn accept(&self) -> anyhow::Result<MockConnection> {
+        self.accept_within(WAIT)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("The client didn't connect"))
+    }
+
+    /// Next connection authenticated in less than `wait`
+    pub async fn accept_within(&self, wait: Duration) -> Option<MockConnection> {
+        tokio::time::timeout(wait, self.connections.recv())
+            .await
+            .ok()?
+            .ok()
+    }
+
+    /// Connects a client with `config` and sends the bootstrap messages to it
+    pub async fn connect(
+        &self,
+        config: Config<PocketData, WebSocketMessage, ()>,
+    ) -> anyhow::Result<(PocketOption, MockConnection)> {
+        let client = tokio::spawn(PocketOption::new_with_config(DEMO_SSID, config));
+        let mut connection = self.accept().await?;
+        connection.bootstrap().await?;
+        let client = tokio::time::timeout(WAIT, client).await???;
+        Ok((client, connection))
+    }
+}
+
+async fn handshake(stream: TcpStream, connections: Sender<MockConnection>) -> anyhow::Result<()> {
+    let mut socket = accept_async(stream).await?;
+    socket
+        .send(Message::text(
+            r#"0{"sid":"mock","upgrades":[],"pingInterval":25000,"pingTimeout":20000}"#,
+        ))
+        .await?;
+    anyhow::ensure!(
+        next_text(&mut socket).await? == "40",
+        "Expected the client to open the namespace"
+    );
+    socket.send(Message::text(r#"40{"sid":"mock"}"#)).await?;
+    let ssid = next_text(&mut socket).await?;
+    connections.send(MockConnection { ssid, socket }).await?;
+    Ok(())
+}
+
+async fn next_text(socket: &mut WebSocketStream<TcpStream>) -> anyhow::Result<String> {
+    while let Some(message) = socket.next().await {
+        if let Message::Text(text) = message? {
+            return Ok(text.to_string());
+        }
+    }
+    anyhow::bail!("The connection was closed")
+}
+
+impl MockConnection {
+    /// Sends `successauth`, `successupdateBalance` and `updateAssets`, the client is ready once it gets them
+    pub async fn bootstrap(&mut self) -> anyhow::Result<()> {
+        self.bootstrap_with_balance(BALANCE).await
+    }
+
+    /// Same as `bootstrap`, for an account with another balance
+    pub async fn bootstrap_with_balance(&mut self, balance: f64) -> anyhow::Result<()> {
+        self.send_event("successauth", &json!({"id": "mock"}))
+            .await?;
+        self.send_event(
+            "successupdateBalance",
+            &json!({"isDemo": 1, "balance": balance}),
+        )
+        .await?;
+        self.send_event("updateAssets", &json!([])).await
+    }
+
+    /// Answers the messages of the client until it closes the connection
+    pub async fn closed(mut self) -> anyhow::Result<()> {
+        tokio::time::timeout(WAIT, async {
+            while let Some(Ok(message)) = self.socket.next().await {
+                if message.is_close() {
+                    break;
+                }
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("The client didn't close the connection"))
+    }
+
+    /// Sends an event the way the server does, a placeholder followed by the payload in a binary frame
+    pub async fn send_event(
+        &mut self,
+        event: &str,
+        payload: &impl Serialize,
+    ) -> anyhow::Result<()> {
+        self.socket
+            .send(Message::text(format!(
+                r#"451-["{event}",{{"_placeholder":true,"num":0}}]"#
+            )))
+            .await?;
+        self.socket
+            .send(Message::binary(serde_json::to_vec(payload)?))
+            .await?;
+        Ok(())
+    }
+
+    /// Next event named `event` sent by the client, other messages are skipped
+    pub async fn recv_event(&mut self, event: &str) -> anyhow::Result<Value> {
+        self.recv_event_within(event, WAIT)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("The client didn't send any '{event}' event"))
+    }
+
+    /// Next event named `event` sent in less than `wait`
+    pub async fn recv_event_within(&mut self, event: &str, wait: Duration) -> Option<Value> {
+        tokio::time::timeout(wait, async {
+            loop {
+                let text = next_text(&mut self.socket).await.ok()?;
+                let Some(message) = text.strip_prefix("42") else {
+                    continue;
+                };
+                if let Ok((name, payload)) = serde_json::from_str::<(String, Value)>(message)
+                    && name == event
+                {
+                    return Some(payload);
+                }
+            }
+        })
+        .await
+        .ok()?
+    }
+
+    /// Waits for the next order and opens a deal for it
+    pub async fn open_order(&mut self) -> anyhow::Result<Deal> {
+        let order = self.recv_event("openOrder").await?;
+        let deal = deal_for(&order)?;
+        self.send_event("successopenOrder", &deal).await?;
+        Ok(deal)
+    }
+
+    /// Waits for the next order and refuses it with `error`
+    pub async fn fail_order(&mut self, error: &str) -> anyhow::Result<()> {
+        let order = self.recv_event("openOrder").await?;
+        let fail = json!({"error": error, "amount": order["amount"], "asset": order["asset"]});
+        self.send_event("failopenOrder", &fail).await
+    }
+
+    /// Rejects the ssid of the client like an expired session
+    pub async fn reject(&mut self) -> anyhow::Result<()> {
+        self.socket
+            .send(Message::text(r#"42["NotAuthorized"]"#))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Deal opened by the server for an `openOrder` payload, its id is derived from the request id
+pub fn deal_for(order: &Value) -> anyhow::Result<Deal> {
+    let deal: Deal = serde_json::from_str(include_str!("../../tests/success_open_order.json"))?;
+    let request_id = order["requestId"]
+        .as_u64()
+        .ok_or_else(|| anyhow::anyhow!("Missing request id in {order}"))?;
+    // Timestamps are sent in milliseconds, whole seconds survive the round trip
+    let now = Utc::now().trunc_subsecs(0);
+    Ok(Deal {
+        id: Uuid::from_u128(request_id.into()),
+        request_id: Some(request_id),
+        asset: order["asset"].as_str().unwrap_or_default().to_string(),
+        amount: order["amount"].as_f64().unwrap_or_default(),
+        is_demo: order["isDemo"].as_u64().unwrap_or_default() as u32,
+        open_timestamp: now,
+        close_timestamp: now
+            + chrono::Duration::seconds(order["time"].as_i64().unwrap_or_default()),
+        ..deal
+    })
+}
@@ -1,8 +1,7 @@
-use std::time::Duration;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 use async_trait::async_trait;
 use futures_util::future::try_join;
-use tokio::time::sleep;
 use tracing::{debug, info, instrument};
 
 use crate::pocketoption::{
@@ -10,32 +9,80 @@ use crate::pocketoption::{
     validators::history_validator,
 };
 use binary_options_tools_core::{
-    error::{BinaryOptionsResult, BinaryOptionsToolsError},
-    general::{config::Config, send::SenderMessage, traits::WCallback, types::Data},
+    error::BinaryOptionsResult,
+    general::{
+        config::Config,
+        send::{ReconnectPolicy, SenderMessage},
+        traits::WCallback,
+        types::Data,
+    },
 };
 
 use super::{base::ChangeSymbol, data::PocketData, order::SuccessCloseOrder};
 
-#[derive(Clone)]
-pub struct PocketCallback;
+/// What a connection is used for when the client opens separate trading and market data connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionRole {
+    /// Single connection used for every request
+    #[default]
+    All,
+    /// Connection used for orders, deals and raw messages
+    Trading,
+    /// Market data connection `index` out of `total`, it only handles the assets assigned to it by `market_index`
+    MarketData { index: usize, total: usize },
+}
+
+/// Index of the market data connection that handles `asset`, every request for an asset uses the same connection
+pub fn market_index(asset: &str, total: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    asset.hash(&mut hasher);
+    (hasher.finish() % total.max(1) as u64) as usize
+}
+
+impl ConnectionRole {
+    pub fn handles_asset(&self, asset: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Trading => false,
+            Self::MarketData { index, total } => market_index(asset, *total) == *index,
+        }
+    }
+
+    pub fn handles_deals(&self) -> bool {
+        matches!(self, Self::All | Self::Trading)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PocketCallback {
+    role: ConnectionRole,
+}
 
 impl PocketCallback {
+    pub fn new(role: ConnectionRole) -> Self {
+        Self { role }
+    }
+
     async fn update_assets(
+        &self,
         data: &Data<PocketData, WebSocketMessage>,
         sender: &SenderMessage,
         config: &Config<PocketData, WebSocketMessage, ()>,
     ) -> BinaryOptionsResult<()> {
         for asset in data.stream_assets().await {
-            sleep(Duration::from_secs(1)).await;
+            if !self.role.handles_asset(&asset) {
+                continue;
+            }
             let history = ChangeSymbol::new(asset.to_string(), 3600);
             let res = sender
                 .send_message_with_timout(
-                    config.get_timeout()?,
+                    config.get_timeout(),
                     "SubscribeSymbolCallback",
                     data,
                     WebSocketMessage::ChangeSymbol(history),
                     MessageInfo::UpdateHistoryNew,
                     Box::new(history_validator(asset.to_string(), 3600)),
+                    ReconnectPolicy::Resend,
                 )
                 .await?;
             if let WebSocketMessage::UpdateHistoryNew(_) = res {
@@ -51,8 +98,12 @@ impl PocketCallback {
     }
 
     async fn update_check_results(
+        &self,
         data: &Data<PocketData, WebSocketMessage>,
     ) -> BinaryOptionsResult<()> {
+        if !self.role.handles_deals() {
+            return Ok(());
+        }
         if let Some(sender) = data.sender(MessageInfo::SuccesscloseOrder).await {
             let deals = data.get_closed_deals().await;
             if !deals.is_empty() {
@@ -60,10 +111,7 @@ impl PocketCallback {
                 let close_order = SuccessCloseOrder { profit: 0.0, deals };
                 sender
                     .send(WebSocketMessage::SuccesscloseOrder(close_order))
-                    .await
-                    .map_err(|e| {
-                        BinaryOptionsToolsError::GeneralMessageSendingError(e.to_string())
-                    })?;
+                    .await?;
             }
         }
         Ok(())
@@ -83,9 +131,29 @@ impl WCallback for PocketCallback {
         config: &Config<Self::T, Self::Transfer, Self::U>,
     ) -> BinaryOptionsResult<()> {
         // let sender = sender.clone();
-        let update_assets_future = Self::update_assets(&data, sender, &config);
-        let update_check_results_future = Self::update_check_results(&data);
+        let update_assets_future = self.update_assets(&data, sender, config);
+        let update_check_results_future = self.update_check_results(&data);
         try_join(update_assets_future, update_check_results_future).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assets_split_between_connections() {
+        let assets = ["EURUSD_otc", "AUDCAD", "#AAPL", "BTCUSD", "GBPJPY_otc"];
+        let roles: Vec<_> = (0..3)
+            .map(|index| ConnectionRole::MarketData { index, total: 3 })
+            .collect();
+        for asset in assets {
+            let handlers = roles.iter().filter(|r| r.handles_asset(asset)).count();
+            assert_eq!(handlers, 1);
+            assert!(ConnectionRole::All.handles_asset(asset));
+            assert!(!ConnectionRole::Trading.handles_asset(asset));
+        }
+        assert!(!roles[0].handles_deals());
+    }
+}
@@ -1,18 +1,25 @@
 use std::{
-    collections::{HashMap, HashSet},
-    sync::Arc,
-    time::Duration,
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicI64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
-use async_channel::{Receiver, Sender, bounded};
 use async_trait::async_trait;
 use chrono::Utc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, broadcast};
 use tracing::{info, warn};
 use uuid::Uuid;
 
 use binary_options_tools_core::{
-    constants::MAX_CHANNEL_CAPACITY, error::BinaryOptionsResult, general::traits::DataHandler,
+    error::BinaryOptionsResult,
+    general::{
+        channel::{ChannelConfig, PolicyReceiver, PolicySender, channel},
+        traits::DataHandler,
+    },
+    reimports::ArcSwap,
 };
 
 use crate::pocketoption::{
@@ -24,22 +31,58 @@ use super::{
     update::{UpdateAssets, UpdateBalance, UpdateStream},
 };
 
-pub struct Channels(Sender<WebSocketMessage>, Receiver<WebSocketMessage>);
+pub struct Channels(
+    PolicySender<WebSocketMessage>,
+    PolicyReceiver<WebSocketMessage>,
+);
+
+/// Deal that appeared in the opened deals, from an order of this client or one placed anywhere else
+#[derive(Debug, Clone)]
+pub struct DealOpened {
+    pub deal: Deal,
+    /// Moment the deal was recieved
+    pub recieved: Instant,
+}
+
+pub struct DealEvents(broadcast::Sender<DealOpened>);
 
 #[derive(Default, Clone)]
+/// Market and account state, every read gets a snapshot that never waits for `DataHandler::update`
 pub struct PocketData {
-    balance: Arc<Mutex<UpdateBalance>>,
-    opened_deals: Arc<Mutex<HashMap<Uuid, Deal>>>,
-    closed_deals: Arc<Mutex<HashSet<Deal>>>,
-    payout_data: Arc<Mutex<HashMap<String, i32>>>,
-    server_time: Arc<Mutex<i64>>,
+    balance: Arc<ArcSwap<UpdateBalance>>,
+    opened_deals: Arc<ArcSwap<HashMap<Uuid, Deal>>>,
+    closed_deals: Arc<ArcSwap<HashSet<Deal>>>,
+    payout_data: Arc<ArcSwap<HashMap<String, i32>>>,
+    server_time: Arc<AtomicI64>,
     stream_channels: Arc<Channels>,
     stream_assets: Arc<Mutex<Vec<String>>>,
+    submitted_orders: Arc<Mutex<VecDeque<u64>>>,
+    opened_events: Arc<DealEvents>,
+    /// Set while switching accounts, the account is reset once the next connection authenticates
+    reset_on_auth: Arc<AtomicBool>,
 }
 
+/// Number of submitted orders remembered to avoid opening the same order twice
+const MAX_SUBMITTED_ORDERS: usize = 1024;
+
+/// Opened deals buffered for each subscriber, the initial list of opened deals can be long
+const MAX_DEAL_EVENTS: usize = 256;
+
 impl Default for Channels {
     fn default() -> Self {
-        let (s, r) = bounded(MAX_CHANNEL_CAPACITY);
+        Self::new(ChannelConfig::default())
+    }
+}
+
+impl Default for DealEvents {
+    fn default() -> Self {
+        Self(broadcast::channel(MAX_DEAL_EVENTS).0)
+    }
+}
+
+impl Channels {
+    pub fn new(config: ChannelConfig) -> Self {
+        let (s, r) = channel(config);
         Self(s, r)
     }
 }
@@ -55,84 +98,137 @@ impl From<UpdateAssets> for HashMap<String, i32> {
 }
 
 impl PocketData {
+    /// Creates a new `PocketData` instance where the market data streams follow the given `ChannelConfig`.
+    /// The updates are forwarded by the listener of the connection, with `BackpressurePolicy::Block` a stream that
+    /// isn't read holds every other message, including the responses of the requests
+    pub fn new(stream: ChannelConfig) -> Self {
+        Self {
+            stream_channels: Arc::new(Channels::new(stream)),
+            ..Default::default()
+        }
+    }
+
     pub async fn update_balance(&self, balance: UpdateBalance) {
-        let mut blnc = self.balance.lock().await;
-        *blnc = balance;
+        self.balance.store(Arc::new(balance));
     }
 
     pub async fn get_balance(&self) -> UpdateBalance {
-        self.balance.lock().await.clone()
+        self.balance.load().as_ref().clone()
     }
 
     pub async fn update_opened_deals(&self, deals: impl Into<Vec<Deal>>) {
-        let mut opened = self.opened_deals.lock().await;
-        let new_deals: HashMap<Uuid, Deal> = HashMap::from_iter(
-            deals
-                .into()
-                .into_iter()
-                .map(|d| (d.id, d))
-                .collect::<Vec<(Uuid, Deal)>>(),
-        );
-        opened.extend(new_deals);
+        let deals = deals.into();
+        let previous = self.opened_deals.rcu(|opened| {
+            let mut opened = opened.as_ref().clone();
+            opened.extend(deals.iter().map(|d| (d.id, d.clone())));
+            opened
+        });
+        let recieved = Instant::now();
+        for deal in deals.into_iter().filter(|d| !previous.contains_key(&d.id)) {
+            // Fails only without subscribers
+            let _ = self.opened_events.0.send(DealOpened { deal, recieved });
+        }
     }
 
-    pub async fn get_opened_deals(&self) -> Vec<Deal> {
-        self.opened_deals
-            .lock()
-            .await
-            .clone()
-            .into_values()
-            .collect()
+    /// Recieves every deal that gets opened from now on, deals already known are not sent again after a reconnection
+    pub fn subscribe_opened_deals(&self) -> broadcast::Receiver<DealOpened> {
+        self.opened_events.0.subscribe()
     }
 
-    async fn remove_opened_deal(&self, id: Uuid) {
-        let mut opened = self.opened_deals.lock().await;
-        opened.remove(&id);
+    pub async fn get_opened_deals(&self) -> Vec<Deal> {
+        self.opened_deals.load().values().cloned().collect()
     }
 
     pub async fn update_closed_deals(&self, deals: impl Into<Vec<Deal>>) {
-        let mut closed = self.closed_deals.lock().await;
         let deals = deals.into();
-        for d in deals.iter() {
-            self.remove_opened_deal(d.id).await;
-        }
-        let new: HashSet<Deal> = HashSet::from_iter(deals);
-        closed.extend(new);
+        // Added to the closed deals first, a reader going through the opened deals and then the closed ones always finds them
+        self.closed_deals.rcu(|closed| {
+            let mut closed = closed.as_ref().clone();
+            closed.extend(deals.iter().cloned());
+            closed
+        });
+        self.opened_deals.rcu(|opened| {
+            let mut opened = opened.as_ref().clone();
+            for d in deals.iter() {
+                opened.remove(&d.id);
+            }
+            opened
+        });
     }
 
     pub async fn get_closed_deals(&self) -> Vec<Deal> {
-        self.closed_deals.lock().await.clone().into_iter().collect()
+        self.closed_deals.load().iter().cloned().collect()
     }
 
     pub async fn clean_closed_deals(&self) {
-        let mut closed = self.closed_deals.lock().await;
-        closed.clear();
+        self.closed_deals.store(Arc::default());
+    }
+
+    /// Forgets the balance, deals and pending orders of the account, the market data and streams are kept
+    pub async fn reset_account(&self) {
+        self.balance.store(Arc::default());
+        self.opened_deals.store(Arc::default());
+        self.closed_deals.store(Arc::default());
+        self.submitted_orders.lock().await.clear();
+    }
+
+    /// Resets the account right before the next connection is authenticated.
+    /// Messages of the previous account that arrive until then are cleared too, and the bootstrap of the new one is kept
+    pub fn reset_account_on_auth(&self) {
+        self.reset_on_auth.store(true, Ordering::SeqCst);
+    }
+
+    /// Searches the opened and closed deals for the one created by the order with the given `request_id`
+    pub async fn get_deal_by_request_id(&self, request_id: u64) -> Option<Deal> {
+        let matches = |d: &&Deal| d.request_id.is_some_and(|id| id == request_id);
+        if let Some(deal) = self.opened_deals.load().values().find(matches) {
+            return Some(deal.clone());
+        }
+        self.closed_deals.load().iter().find(matches).cloned()
+    }
+
+    /// Registers the order as submitted, returns false if it already was so it's never sent twice
+    pub async fn try_submit_order(&self, request_id: u64) -> bool {
+        let mut orders = self.submitted_orders.lock().await;
+        if orders.contains(&request_id) {
+            return false;
+        }
+        if orders.len() >= MAX_SUBMITTED_ORDERS {
+            orders.pop_front();
+        }
+        orders.push_back(request_id);
+        true
+    }
+
+    pub async fn remove_submitted_order(&self, request_id: u64) {
+        self.submitted_orders
+            .lock()
+            .await
+            .retain(|id| *id != request_id);
+    }
+
+    pub async fn is_submitted_order(&self, request_id: u64) -> bool {
+        self.submitted_orders.lock().await.contains(&request_id)
     }
 
     pub async fn update_payout_data(&self, payout: UpdateAssets) {
-        let mut data = self.payout_data.lock().await;
-        *data = payout.into();
+        self.payout_data.store(Arc::new(payout.into()));
     }
 
     pub async fn get_full_payout(&self) -> HashMap<String, i32> {
-        self.payout_data.lock().await.clone()
+        self.payout_data.load().as_ref().clone()
     }
 
     pub async fn get_payout(&self, asset: impl ToString) -> Option<i32> {
-        self.payout_data
-            .lock()
-            .await
-            .get(&asset.to_string())
-            .cloned()
+        self.payout_data.load().get(&asset.to_string()).cloned()
     }
 
     pub async fn update_server_time(&self, time: i64) {
-        let mut s_time = self.server_time.lock().await;
-        *s_time = time;
+        self.server_time.store(time, Ordering::Relaxed);
     }
 
     pub async fn get_server_time(&self) -> i64 {
-        // *self.server_time.lock().await
+        // self.server_time.load(Ordering::Relaxed)
         (Utc::now() + Duration::from_secs(2 * 3600 + 123)).timestamp()
     }
 
@@ -165,7 +261,8 @@ impl PocketData {
         if self.stream_channels.0.receiver_count() > 1 {
             self.stream_channels
                 .0
-                .force_send(WebSocketMessage::UpdateStream(stream))?;
+                .send(WebSocketMessage::UpdateStream(stream))
+                .await?;
         }
         Ok(())
     }
@@ -177,6 +274,13 @@ impl DataHandler for PocketData {
 
     async fn update(&self, message: &WebSocketMessage) -> BinaryOptionsResult<()> {
         match message {
+            // First message of every connection, the rest of the bootstrap comes after it
+            WebSocketMessage::SuccessAuth(_)
+                if self.reset_on_auth.swap(false, Ordering::SeqCst) =>
+            {
+                info!("Authenticated with the new account, clearing the previous one");
+                self.reset_account().await;
+            }
             WebSocketMessage::SuccessupdateBalance(balance) => {
                 self.update_balance(balance.clone()).await
             }
@@ -251,3 +355,131 @@ impl DataHandler for PocketData {
     }
 
 */
+
+#[cfg(test)]
+mod tests {
+    use binary_options_tools_core::general::channel::BackpressurePolicy;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blocked_stream_holds_the_listener() -> anyhow::Result<()> {
+        let data = PocketData::new(ChannelConfig::new(1, BackpressurePolicy::Block));
+        let stream = data.add_stream("EURUSD_otc".into()).await;
+        let update = |price: f64| -> anyhow::Result<WebSocketMessage> {
+            Ok(WebSocketMessage::UpdateStream(serde_json::from_value(
+                serde_json::json!([["EURUSD_otc", Utc::now().timestamp(), price]]),
+            )?))
+        };
+        data.update(&update(1.0)?).await?;
+        // The channel is full, the listener waits for the stream instead of dropping the update
+        let second = update(2.0)?;
+        let blocked = data.update(&second);
+        tokio::pin!(blocked);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), &mut blocked)
+                .await
+                .is_err()
+        );
+        assert_eq!(stream.recieve().await?.close, 1.0);
+        tokio::time::timeout(Duration::from_secs(1), blocked).await??;
+        assert_eq!(stream.recieve().await?.close, 2.0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_submitted_orders() {
+        let data = PocketData::default();
+        for id in 0..=MAX_SUBMITTED_ORDERS as u64 {
+            assert!(data.try_submit_order(id).await);
+        }
+        assert!(!data.try_submit_order(1).await);
+        // The oldest order is forgotten once the limit is reached
+        assert!(!data.is_submitted_order(0).await);
+        assert!(data.is_submitted_order(1).await);
+        data.remove_submitted_order(1).await;
+        assert!(!data.is_submitted_order(1).await);
+        assert!(data.get_deal_by_request_id(2).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_opened_deal_events() -> anyhow::Result<()> {
+        let deal: Deal =
+            serde_json::from_str(&std::fs::read_to_string("tests/success_open_order.json")?)?;
+        let data = PocketData::default();
+        let mut events = data.subscribe_opened_deals();
+        data.update_opened_deals(vec![deal.clone()]).await;
+        // Already known, like the list of opened deals sent after a reconnection
+        data.update_opened_deals(vec![deal.clone()]).await;
+        assert_eq!(events.try_recv()?.deal, deal);
+        assert!(events.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_account_is_reset_on_auth() -> anyhow::Result<()> {
+        let deal: Deal =
+            serde_json::from_str(&std::fs::read_to_string("tests/success_open_order.json")?)?;
+        let auth = WebSocketMessage::SuccessAuth(serde_json::from_str(r#"{"id":"mock"}"#)?);
+        let balance = |balance: f64| -> anyhow::Result<WebSocketMessage> {
+            Ok(WebSocketMessage::SuccessupdateBalance(
+                serde_json::from_value(serde_json::json!({"isDemo": 1, "balance": balance}))?,
+            ))
+        };
+        let data = PocketData::default();
+        data.update(&balance(10.0)?).await?;
+        data.reset_account_on_auth();
+        // Still sent by the connection of the previous account
+        data.update(&WebSocketMessage::SuccessopenOrder(deal.clone()))
+            .await?;
+        assert_eq!(data.get_opened_deals().await.len(), 1);
+
+        data.update(&auth).await?;
+        data.update(&balance(20.0)?).await?;
+        assert!(data.get_opened_deals().await.is_empty());
+        assert_eq!(data.get_balance().await.balance, 20.0);
+
+        // Only the first authentication after the switch resets the account
+        data.update(&WebSocketMessage::SuccessopenOrder(deal))
+            .await?;
+        data.update(&auth).await?;
+        assert_eq!(data.get_opened_deals().await.len(), 1);
+        assert_eq!(data.get_balance().await.balance, 20.0);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_closing_deals_are_always_found() -> anyhow::Result<()> {
+        let deal: Deal =
+            serde_json::from_str(&std::fs::read_to_string("tests/success_open_order.json")?)?;
+        let data = PocketData::default();
+        let deal_with_id = |request_id: u64| Deal {
+            id: Uuid::from_u128(request_id.into()),
+            request_id: Some(request_id),
+            ..deal.clone()
+        };
+        // Big maps make the updates slow enough for the reader to run in the middle of them
+        data.update_opened_deals((1000..5000).map(deal_with_id).collect::<Vec<_>>())
+            .await;
+        for request_id in 0..200 {
+            let deal = deal_with_id(request_id);
+            data.update_opened_deals(vec![deal.clone()]).await;
+            let closing = tokio::spawn({
+                let data = data.clone();
+                async move { data.update_closed_deals(vec![deal]).await }
+            });
+            while !closing.is_finished() {
+                assert!(data.get_deal_by_request_id(request_id).await.is_some());
+            }
+            closing.await?;
+            assert!(data.get_deal_by_request_id(request_id).await.is_some());
+            assert!(
+                data.get_opened_deals()
+                    .await
+                    .iter()
+                    .all(|d| d.request_id != Some(request_id))
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
+use std::collections::HashSet;
+
 use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
 
-use binary_options_tools_core::general::traits::MessageInformation;
+use binary_options_tools_core::general::{
+    limiter::{RateLimit, RateLimitConfig},
+    traits::MessageInformation,
+};
 
 use super::base::RawWebsocketMessage;
 
@@ -30,6 +35,7 @@ pub enum MessageInfo {
     OpenPendingOrder,
     SuccessopenPendingOrder,
     FailopenPendingOrder,
+    NotAuthorized,
     None,
 
     #[serde(other)]
@@ -44,6 +50,23 @@ impl MessageInfo {
             None
         }
     }
+
+    /// Messages the server sends after authenticating, once all of them arrive the client is ready
+    pub fn bootstrap() -> HashSet<Self> {
+        HashSet::from([
+            Self::Successauth,
+            Self::SuccessupdateBalance,
+            Self::UpdateAssets,
+        ])
+    }
+
+    /// Outgoing limits used by default, subscribing to many assets at once is what usually gets the session throttled
+    pub fn rate_limits() -> RateLimitConfig {
+        RateLimitConfig::default()
+            .with_limit(Self::ChangeSymbol, RateLimit::new(3, 1.0))
+            .with_limit(Self::GetCandles, RateLimit::new(5, 2.0))
+            .with_limit(Self::OpenOrder, RateLimit::new(5, 2.0))
+    }
 }
 
 impl MessageInformation for MessageInfo {}
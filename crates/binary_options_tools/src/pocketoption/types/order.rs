@@ -12,11 +12,13 @@ use crate::pocketoption::{
 
 use super::update::{float_time, string_time};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Action {
+    #[serde(alias = "buy")]
     Call, // Buy
-    Put,  // Sell
+    #[serde(alias = "sell")]
+    Put, // Sell
 }
 
 #[derive(Clone, Debug)]
@@ -150,6 +152,16 @@ impl Hash for Deal {
 
 impl Eq for Deal {}
 
+impl Deal {
+    /// Direction of the deal, the server sends `command` 0 for CALL and 1 for PUT
+    pub fn action(&self) -> Action {
+        match self.command {
+            0 => Action::Call,
+            _ => Action::Put,
+        }
+    }
+}
+
 impl OpenOrder {
     pub fn new(
         amount: f64,
@@ -158,15 +170,34 @@ impl OpenOrder {
         duration: u32,
         demo: u32,
     ) -> PocketResult<Self> {
-        Ok(Self {
+        Ok(Self::with_request_id(
+            amount,
+            asset,
+            action,
+            duration,
+            demo,
+            get_index()?,
+        ))
+    }
+
+    /// Creates an order with a client supplied `request_id`, the server echoes it back in the opened deal
+    pub fn with_request_id(
+        amount: f64,
+        asset: String,
+        action: Action,
+        duration: u32,
+        demo: u32,
+        request_id: u64,
+    ) -> Self {
+        Self {
             amount,
             asset,
             action,
             is_demo: demo,
             option_type: 100, // FIXME: Check why it always is 100
-            request_id: get_index()?,
+            request_id,
             time: duration,
-        })
+        }
     }
 
     pub fn put(amount: f64, asset: String, duration: u32, demo: u32) -> PocketResult<Self> {
@@ -225,6 +256,30 @@ impl FailOpenOrder {
             asset: asset.to_string(),
         }
     }
+
+    pub fn is_rate_limit(&self) -> bool {
+        is_rate_limit_error(&self.error)
+    }
+}
+
+impl FailOpenPendingOrder {
+    pub fn is_rate_limit(&self) -> bool {
+        is_rate_limit_error(&self.error)
+    }
+}
+
+/// Checks if the error sent by the server is caused by sending too many requests
+fn is_rate_limit_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "too many",
+        "too often",
+        "too fast",
+        "rate limit",
+        "limit exceeded",
+    ]
+    .iter()
+    .any(|pattern| error.contains(pattern))
 }
 
 impl std::cmp::PartialEq<Uuid> for Deal {
@@ -255,6 +310,14 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_rate_limit_error() {
+        assert!(
+            FailOpenOrder::new("Too many requests, try again later", 1.0, "EURUSD").is_rate_limit()
+        );
+        assert!(!FailOpenOrder::new("Not enough money", 1.0, "EURUSD").is_rate_limit());
+    }
+
     #[test]
     fn test_descerialize_closed_deals() -> Result<(), Box<dyn Error>> {
         let history_raw = File::open("tests/update_closed_deals.json")?;
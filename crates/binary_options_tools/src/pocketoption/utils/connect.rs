@@ -1,8 +1,8 @@
 use binary_options_tools_core::{
     error::BinaryOptionsToolsError,
+    general::connection::ConnectionConfig,
     reimports::{
-        Connector, MaybeTlsStream, Request, WebSocketStream, connect_async_tls_with_config,
-        generate_key,
+        MaybeTlsStream, Request, WebSocketStream, client_async_tls_with_config, generate_key,
     },
 };
 use tokio::net::TcpStream;
@@ -16,10 +16,12 @@ use crate::pocketoption::{
 pub async fn try_connect(
     ssid: Ssid,
     url: String,
+    connection: &ConnectionConfig,
 ) -> PocketResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-    let tls_connector = native_tls::TlsConnector::builder().build()?;
-
-    let connector = Connector::NativeTls(tls_connector);
+    let connector = connection
+        .tls
+        .connector()
+        .map_err(|e| PocketOptionError::TLSError(e.0))?;
 
     let user_agent = ssid.user_agent();
     let t_url = Url::parse(&url)
@@ -29,7 +31,7 @@ pub async fn try_connect(
         .ok_or(PocketOptionError::GeneralParsingError(
             "Host not found".into(),
         ))?;
-    let request = Request::builder()
+    let mut request = Request::builder()
         .uri(t_url.to_string())
         .header("Origin", "https://pocketoption.com")
         .header("Cache-Control", "no-cache")
@@ -41,8 +43,12 @@ pub async fn try_connect(
         .header("Host", host)
         .body(())
         .map_err(BinaryOptionsToolsError::from)?;
+    connection
+        .apply(&mut request)
+        .map_err(BinaryOptionsToolsError::from)?;
 
-    let (ws, _) = connect_async_tls_with_config(request, None, false, Some(connector))
+    let stream = connection.connect(&t_url).await?;
+    let (ws, _) = client_async_tls_with_config(request, stream, None, Some(connector))
         .await
         .map_err(BinaryOptionsToolsError::from)?;
     Ok(ws)
@@ -1,15 +1,51 @@
+use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::pocketoption::error::PocketResult;
+use crate::pocketoption::error::{PocketOptionError, PocketResult};
+
+/// Approximate location of the user, used to sort the servers by distance
+#[async_trait]
+pub trait LocationProvider: Send + Sync {
+    /// Latitude and longitude of the user
+    async fn location(&self) -> PocketResult<(f64, f64)>;
+}
+
+/// Geo-IP lookup of the public ip of the user with `api.ipify.org` and `ip-api.com`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpApiLocation;
+
+/// Fixed location, useful when the user already knows where the client runs
+#[derive(Debug, Clone, Copy)]
+pub struct StaticLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[async_trait]
+impl LocationProvider for IpApiLocation {
+    async fn location(&self) -> PocketResult<(f64, f64)> {
+        let ip = get_public_ip().await?;
+        get_user_location(&ip).await
+    }
+}
+
+#[async_trait]
+impl LocationProvider for StaticLocation {
+    async fn location(&self) -> PocketResult<(f64, f64)> {
+        Ok((self.latitude, self.longitude))
+    }
+}
 
 pub async fn get_user_location(ip_address: &str) -> PocketResult<(f64, f64)> {
     let response = reqwest::get(format!("http://ip-api.com/json/{}", ip_address)).await?;
     let json: Value = response.json().await?;
 
-    let lat = json["lat"].as_f64().unwrap();
-    let lon = json["lon"].as_f64().unwrap();
-
-    Ok((lat, lon))
+    match (json["lat"].as_f64(), json["lon"].as_f64()) {
+        (Some(lat), Some(lon)) => Ok((lat, lon)),
+        _ => Err(PocketOptionError::GeneralParsingError(format!(
+            "Missing coordinates in the location of '{ip_address}'"
+        ))),
+    }
 }
 
 pub fn calculate_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
@@ -31,5 +67,7 @@ pub fn calculate_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
 pub async fn get_public_ip() -> PocketResult<String> {
     let response = reqwest::get("https://api.ipify.org?format=json").await?;
     let json: serde_json::Value = response.json().await?;
-    Ok(json["ip"].as_str().unwrap().to_string())
+    json["ip"].as_str().map(String::from).ok_or_else(|| {
+        PocketOptionError::GeneralParsingError("Missing 'ip' in the ipify response".into())
+    })
 }
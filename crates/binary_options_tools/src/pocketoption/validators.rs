@@ -4,12 +4,17 @@ use super::parser::message::WebSocketMessage;
 
 pub fn order_validator(order_index: u64) -> impl Fn(&WebSocketMessage) -> bool + Send + Sync {
     move |message| {
-        if let WebSocketMessage::SuccessopenOrder(order) = message {
-            if order.request_id.is_some_and(|id| id == order_index) {
-                return true;
+        match message {
+            WebSocketMessage::SuccessopenOrder(order) => {
+                order.request_id.is_some_and(|id| id == order_index)
             }
+            // Used to find the order after a reconnection
+            WebSocketMessage::UpdateOpenedDeals(deals) => deals
+                .0
+                .iter()
+                .any(|d| d.request_id.is_some_and(|id| id == order_index)),
+            _ => false,
         }
-        false
     }
 }
 
@@ -31,12 +36,14 @@ pub fn candle_validator(index: u64) -> impl Fn(&WebSocketMessage) -> bool + Send
 
 pub fn order_result_validator(order_id: Uuid) -> impl Fn(&WebSocketMessage) -> bool + Send + Sync {
     move |message| {
-        if let WebSocketMessage::SuccesscloseOrder(orders) = message {
-            if orders.deals.iter().any(|o| o == &order_id) {
-                return true;
+        match message {
+            WebSocketMessage::SuccesscloseOrder(orders) => {
+                orders.deals.iter().any(|o| o == &order_id)
             }
+            // Used to find the result after a reconnection
+            WebSocketMessage::UpdateClosedDeals(deals) => deals.0.iter().any(|o| o == &order_id),
+            _ => false,
         }
-        false
     }
 }
 
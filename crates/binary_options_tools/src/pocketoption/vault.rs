@@ -0,0 +1,519 @@
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce, aead::Aead};
+use rand::{Rng, rng};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use url::Url;
+
+use binary_options_tools_core::{
+    general::{config::ConfigBuilder, connection::ConnectionConfig},
+    utils::redact::REDACTED,
+};
+
+use super::{
+    error::{PocketResult, SsidResult, VaultError, VaultResult},
+    parser::message::WebSocketMessage,
+    pocket_client::PocketOption,
+    types::data::PocketData,
+    ws::{regions::ServerSelection, ssid::Ssid},
+};
+
+/// Path of the vault used by `PocketOption::from_profile`, defaults to `~/.pocketoption/vault.json`
+pub const VAULT_PATH_VAR: &str = "POCKET_OPTION_VAULT";
+/// Passphrase of the vault used by `PocketOption::from_profile`
+pub const VAULT_PASSPHRASE_VAR: &str = "POCKET_OPTION_VAULT_PASSPHRASE";
+/// Key file of the vault used by `PocketOption::from_profile`, used when no passphrase is set
+pub const VAULT_KEY_FILE_VAR: &str = "POCKET_OPTION_VAULT_KEY_FILE";
+
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+/// Highest Argon2 memory cost in KiB accepted from a vault file, 1 GiB
+const MAX_KDF_MEMORY: u32 = 1 << 20;
+/// Highest Argon2 time cost accepted from a vault file
+const MAX_KDF_ITERATIONS: u32 = 16;
+/// Highest Argon2 parallelism accepted from a vault file
+const MAX_KDF_PARALLELISM: u32 = 16;
+
+/// Secret that unlocks a `Vault`
+#[derive(Clone)]
+pub enum VaultKey {
+    Passphrase(String),
+    /// Every byte of the file is used as the secret
+    KeyFile(PathBuf),
+}
+
+/// Values of the client configuration stored in a profile, `None` keeps the value of `PocketOption::default_config`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigOverrides {
+    pub max_allowed_loops: Option<u32>,
+    pub sleep_interval: Option<u64>,
+    pub reconnect_time: Option<u64>,
+    pub timeout: Option<Duration>,
+    pub connection_initialization_timeout: Option<Duration>,
+    pub default_connection_url: Vec<Url>,
+    pub connection: Option<ConnectionConfig>,
+}
+
+/// Everything needed to create a client for an account
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub ssid: String,
+    /// Name of the only region the client connects to, see `ServerSelection::Region`
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub market_connections: usize,
+    #[serde(default)]
+    pub config: ConfigOverrides,
+}
+
+/// Encrypted file with named profiles, the file is only written by `Vault::save`
+pub struct Vault {
+    path: PathBuf,
+    kdf: KdfParams,
+    salt: Vec<u8>,
+    key: [u8; 32],
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// Argon2id parameters used to derive the encryption key from the `VaultKey`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct KdfParams {
+    memory: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u8,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn vault_error(message: impl fmt::Display) -> VaultError {
+    VaultError(message.to_string())
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    rng().fill(&mut bytes[..]);
+    bytes
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Bounds the parameters read from a file, a modified vault could otherwise make `derive`
+    /// allocate any amount of memory or run for hours before the key is checked
+    fn clamped(self) -> Self {
+        let clamped = Self {
+            memory: self.memory.clamp(Params::MIN_M_COST, MAX_KDF_MEMORY),
+            iterations: self
+                .iterations
+                .clamp(Params::MIN_T_COST, MAX_KDF_ITERATIONS),
+            parallelism: self
+                .parallelism
+                .clamp(Params::MIN_P_COST, MAX_KDF_PARALLELISM),
+        };
+        if clamped != self {
+            warn!(target: "Vault", "Key derivation parameters {self:?} are out of bounds, using {clamped:?}");
+        }
+        clamped
+    }
+
+    fn derive(&self, key: &VaultKey, salt: &[u8]) -> VaultResult<[u8; 32]> {
+        let params = Params::new(self.memory, self.iterations, self.parallelism, Some(32))
+            .map_err(vault_error)?;
+        let mut derived = [0; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(&key.secret()?, salt, &mut derived)
+            .map_err(vault_error)?;
+        Ok(derived)
+    }
+}
+
+impl VaultKey {
+    fn secret(&self) -> VaultResult<Vec<u8>> {
+        match self {
+            Self::Passphrase(passphrase) if passphrase.is_empty() => {
+                Err(vault_error("The passphrase is empty"))
+            }
+            Self::Passphrase(passphrase) => Ok(passphrase.as_bytes().to_vec()),
+            Self::KeyFile(path) => {
+                let secret = fs::read(path).map_err(|e| {
+                    vault_error(format!("Failed to read key file '{}', {e}", path.display()))
+                })?;
+                if secret.is_empty() {
+                    return Err(vault_error(format!(
+                        "Key file '{}' is empty",
+                        path.display()
+                    )));
+                }
+                Ok(secret)
+            }
+        }
+    }
+
+    /// Key set in `POCKET_OPTION_VAULT_PASSPHRASE` or `POCKET_OPTION_VAULT_KEY_FILE`
+    pub fn from_env() -> VaultResult<Self> {
+        if let Ok(passphrase) = env::var(VAULT_PASSPHRASE_VAR) {
+            Ok(Self::Passphrase(passphrase))
+        } else if let Ok(path) = env::var(VAULT_KEY_FILE_VAR) {
+            Ok(Self::KeyFile(path.into()))
+        } else {
+            Err(vault_error(format!(
+                "Neither {VAULT_PASSPHRASE_VAR} nor {VAULT_KEY_FILE_VAR} is set"
+            )))
+        }
+    }
+}
+
+impl ConfigOverrides {
+    pub fn apply(
+        &self,
+        mut builder: ConfigBuilder<PocketData, WebSocketMessage, ()>,
+    ) -> ConfigBuilder<PocketData, WebSocketMessage, ()> {
+        if let Some(loops) = self.max_allowed_loops {
+            builder = builder.max_allowed_loops(loops);
+        }
+        if let Some(interval) = self.sleep_interval {
+            builder = builder.sleep_interval(interval);
+        }
+        if let Some(time) = self.reconnect_time {
+            builder = builder.reconnect_time(time);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connection_initialization_timeout {
+            builder = builder.connection_initialization_timeout(timeout);
+        }
+        if !self.default_connection_url.is_empty() {
+            builder = builder
+                .default_connection_url(self.default_connection_url.iter().cloned().collect());
+        }
+        if let Some(connection) = &self.connection {
+            builder = builder.connection(connection.clone());
+        }
+        builder
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("ssid", &REDACTED)
+            .field("region", &self.region)
+            .field("market_connections", &self.market_connections)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Profile {
+    /// Creates a profile for `ssid`, it fails if the ssid can't be parsed or is incomplete
+    pub fn new(ssid: impl ToString) -> SsidResult<Self> {
+        let ssid = ssid.to_string();
+        Ssid::parse(&ssid)?.validate()?;
+        Ok(Self {
+            ssid,
+            region: None,
+            market_connections: 0,
+            config: ConfigOverrides::default(),
+        })
+    }
+
+    pub fn region(mut self, region: impl ToString) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+
+    pub fn market_connections(mut self, market_connections: usize) -> Self {
+        self.market_connections = market_connections;
+        self
+    }
+
+    pub fn config(mut self, config: ConfigOverrides) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn servers(&self) -> ServerSelection {
+        self.region
+            .clone()
+            .map(ServerSelection::Region)
+            .unwrap_or_default()
+    }
+
+    /// Creates a client with the ssid, region and configuration of the profile
+    pub async fn connect(&self) -> PocketResult<PocketOption> {
+        let config = self.config.apply(PocketOption::default_config()).build()?;
+        PocketOption::new_with_servers(&self.ssid, config, self.market_connections, self.servers())
+            .await
+    }
+}
+
+impl Vault {
+    /// Creates an empty vault, nothing is written to `path` until `save` is called
+    pub fn create(path: impl Into<PathBuf>, key: &VaultKey) -> VaultResult<Self> {
+        let kdf = KdfParams::default();
+        let salt = random_bytes::<SALT_LEN>().to_vec();
+        Ok(Self {
+            path: path.into(),
+            key: kdf.derive(key, &salt)?,
+            kdf,
+            salt,
+            profiles: BTreeMap::new(),
+        })
+    }
+
+    /// Decrypts the vault at `path`, fails if the key is wrong or the file was modified
+    pub fn open(path: impl Into<PathBuf>, key: &VaultKey) -> VaultResult<Self> {
+        let path = path.into();
+        let text = fs::read_to_string(&path)
+            .map_err(|e| vault_error(format!("Failed to read vault '{}', {e}", path.display())))?;
+        let file: VaultFile = serde_json::from_str(&text).map_err(vault_error)?;
+        if file.version != VERSION {
+            return Err(vault_error(format!(
+                "Unsupported vault version {}",
+                file.version
+            )));
+        }
+        let decode = |value: &str| STANDARD.decode(value).map_err(vault_error);
+        let salt = decode(&file.salt)?;
+        let nonce = decode(&file.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(vault_error("Invalid nonce length"));
+        }
+        let kdf = file.kdf.clamped();
+        let key = kdf.derive(key, &salt)?;
+        let plaintext = XChaCha20Poly1305::new(&key.into())
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                decode(&file.ciphertext)?.as_ref(),
+            )
+            .map_err(|_| vault_error("Wrong key or corrupted vault"))?;
+        Ok(Self {
+            path,
+            kdf,
+            salt,
+            key,
+            profiles: serde_json::from_slice(&plaintext).map_err(vault_error)?,
+        })
+    }
+
+    /// Opens the vault at `path` if it exists, otherwise creates an empty one
+    pub fn open_or_create(path: impl Into<PathBuf>, key: &VaultKey) -> VaultResult<Self> {
+        let path = path.into();
+        if path.exists() {
+            Self::open(path, key)
+        } else {
+            Self::create(path, key)
+        }
+    }
+
+    /// Path set in `POCKET_OPTION_VAULT`, or `~/.pocketoption/vault.json`
+    pub fn default_path() -> VaultResult<PathBuf> {
+        if let Ok(path) = env::var(VAULT_PATH_VAR) {
+            return Ok(path.into());
+        }
+        env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(|home| Path::new(&home).join(".pocketoption").join("vault.json"))
+            .ok_or_else(|| {
+                vault_error(format!(
+                    "Couldn't find the home directory, set {VAULT_PATH_VAR}"
+                ))
+            })
+    }
+
+    /// Opens the vault at `Vault::default_path` with the key from `VaultKey::from_env`
+    pub fn open_default() -> VaultResult<Self> {
+        Self::open(Self::default_path()?, &VaultKey::from_env()?)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Names of the stored profiles in alphabetical order
+    pub fn profiles(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    pub fn profile(&self, name: &str) -> VaultResult<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| vault_error(format!("Profile '{name}' doesn't exist")))
+    }
+
+    /// Adds or replaces the profile `name`
+    pub fn set_profile(&mut self, name: impl ToString, profile: Profile) {
+        self.profiles.insert(name.to_string(), profile);
+    }
+
+    pub fn remove_profile(&mut self, name: &str) -> Option<Profile> {
+        self.profiles.remove(name)
+    }
+
+    /// Encrypts the vault with `key` from now on, call `save` to write it
+    pub fn change_key(&mut self, key: &VaultKey) -> VaultResult<()> {
+        let salt = random_bytes::<SALT_LEN>().to_vec();
+        self.key = self.kdf.derive(key, &salt)?;
+        self.salt = salt;
+        Ok(())
+    }
+
+    /// Encrypts the profiles and replaces the file, only the owner can read it on unix
+    pub fn save(&self) -> VaultResult<()> {
+        let nonce = random_bytes::<NONCE_LEN>();
+        let plaintext = serde_json::to_vec(&self.profiles).map_err(vault_error)?;
+        let ciphertext = XChaCha20Poly1305::new(&self.key.into())
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(vault_error)?;
+        let file = VaultFile {
+            version: VERSION,
+            kdf: self.kdf,
+            salt: STANDARD.encode(&self.salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        let write_error = |e: std::io::Error| {
+            vault_error(format!(
+                "Failed to write vault '{}', {e}",
+                self.path.display()
+            ))
+        };
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        // Written next to the vault and renamed so a failed write never corrupts the previous file
+        let temporary = self.path.with_extension("tmp");
+        fs::write(
+            &temporary,
+            serde_json::to_vec_pretty(&file).map_err(vault_error)?,
+        )
+        .map_err(write_error)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&temporary, fs::Permissions::from_mode(0o600))
+                .map_err(write_error)?;
+        }
+        fs::rename(&temporary, &self.path).map_err(write_error)?;
+        info!(target: "Vault", "Saved {} profiles to '{}'", self.profiles.len(), self.path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SSID: &str =
+        r#"42["auth",{"session":"a1b2c3d4e5f6","isDemo":1,"uid":12345678,"platform":2}]"#;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("vault-test-{name}-{}.json", std::process::id()))
+    }
+
+    #[test]
+    fn test_vault_roundtrip() -> anyhow::Result<()> {
+        let path = temp_path("roundtrip");
+        let key = VaultKey::Passphrase("correct horse battery staple".into());
+        let mut vault = Vault::create(&path, &key)?;
+        let profile = Profile::new(SSID)?
+            .region("EUROPE")
+            .market_connections(2)
+            .config(ConfigOverrides {
+                reconnect_time: Some(10),
+                ..Default::default()
+            });
+        vault.set_profile("demo", profile.clone());
+        vault.save()?;
+
+        let text = fs::read_to_string(&path)?;
+        assert!(!text.contains("a1b2c3d4e5f6"));
+        let vault = Vault::open(&path, &key)?;
+        assert_eq!(vault.profiles(), vec!["demo".to_string()]);
+        assert!(*vault.profile("demo")? == profile);
+        assert!(vault.profile("real").is_err());
+        assert!(!format!("{profile:?}").contains("a1b2c3d4e5f6"));
+
+        assert!(Vault::open(&path, &VaultKey::Passphrase("wrong".into())).is_err());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_key_file() -> anyhow::Result<()> {
+        let path = temp_path("key-file");
+        let key_path = temp_path("key");
+        fs::write(&key_path, random_bytes::<32>())?;
+        let key = VaultKey::KeyFile(key_path.clone());
+        let mut vault = Vault::open_or_create(&path, &key)?;
+        vault.set_profile("real", Profile::new(SSID)?);
+        vault.save()?;
+
+        let mut vault = Vault::open_or_create(&path, &key)?;
+        let passphrase = VaultKey::Passphrase("new passphrase".into());
+        vault.change_key(&passphrase)?;
+        vault.save()?;
+        assert!(Vault::open(&path, &key).is_err());
+        assert_eq!(
+            Vault::open(&path, &passphrase)?.profiles(),
+            vec!["real".to_string()]
+        );
+
+        assert!(Profile::new("not an ssid").is_err());
+        fs::remove_file(&path)?;
+        fs::remove_file(&key_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_kdf_params_are_clamped() -> anyhow::Result<()> {
+        let huge = KdfParams {
+            memory: u32::MAX,
+            iterations: u32::MAX,
+            parallelism: u32::MAX,
+        };
+        let clamped = huge.clamped();
+        assert_eq!(clamped.memory, MAX_KDF_MEMORY);
+        assert_eq!(clamped.iterations, MAX_KDF_ITERATIONS);
+        assert_eq!(clamped.parallelism, MAX_KDF_PARALLELISM);
+        assert_eq!(KdfParams::default().clamped(), KdfParams::default());
+
+        // A vault whose parameters were raised fails like a wrong key instead of running for hours
+        let path = temp_path("kdf");
+        let key = VaultKey::Passphrase("correct horse battery staple".into());
+        Vault::create(&path, &key)?.save()?;
+        let mut file: VaultFile = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        file.kdf.iterations = u32::MAX;
+        fs::write(&path, serde_json::to_vec(&file)?)?;
+        assert!(Vault::open(&path, &key).is_err());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use binary_options_tools_core::{error::BinaryOptionsToolsError, utils::redact::register_secret};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{
+    error::PocketResult,
+    pocket_client::PocketOption,
+    types::order::{Action, Deal},
+};
+
+/// Header with the hex encoded HMAC-SHA256 of the body, signed with the shared secret
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// Shortest duration accepted by default, in seconds
+const DEFAULT_MIN_DURATION: u32 = 5;
+/// Longest duration accepted by default, in seconds
+const DEFAULT_MAX_DURATION: u32 = 4 * 3600;
+/// Alerts sent longer ago than this are refused by default
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Trade alert in the json format of TradingView webhooks, like
+/// `{"asset": "EURUSD_otc", "direction": "buy", "amount": 1.0, "duration": 60, "timestamp": "{{timenow}}", "secret": "..."}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub asset: String,
+    /// "call" or "put", TradingView's "buy" and "sell" are also accepted
+    #[serde(alias = "action")]
+    pub direction: Action,
+    pub amount: f64,
+    /// Duration of the trade in seconds
+    pub duration: u32,
+    /// Moment the alert was sent in RFC 3339, like TradingView's `{{timenow}}`. It's required so a
+    /// captured alert can't be traded again, see `WebhookServer::max_age`
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Random value that tells apart alerts sent with the same content in the same second
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// Shared secret sent in the body, for senders that can't sign the request like TradingView
+    #[serde(default, skip_serializing)]
+    pub secret: Option<String>,
+}
+
+/// Limits every alert must respect before being traded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskSettings {
+    pub min_amount: f64,
+    pub max_amount: Option<f64>,
+    pub min_duration: u32,
+    pub max_duration: u32,
+    /// Assets paying a lower percentage are rejected
+    pub min_payout: Option<i32>,
+    /// Alerts are rejected while this many deals are opened
+    pub max_open_deals: Option<usize>,
+}
+
+/// Where the alerts are traded, implemented by `PocketOption` and by mocks in tests
+#[async_trait]
+pub trait SignalExecutor: Send + Sync + 'static {
+    /// Payout of every available asset
+    async fn payout(&self) -> HashMap<String, i32>;
+
+    async fn opened_deals(&self) -> usize;
+
+    async fn execute(&self, alert: &Alert) -> PocketResult<(Uuid, Deal)>;
+}
+
+/// HTTP server that trades the alerts posted to `/webhook` and answers with the id of the deal.
+/// Each alert must be signed with the `X-Signature` header or carry the shared secret in its `secret` field,
+/// and have a recent `timestamp`, the same alert is never traded twice.
+///
+/// # Examples
+/// ```rust
+/// let client = PocketOption::new(ssid).await?;
+/// let risk = RiskSettings { max_amount: Some(10.0), max_open_deals: Some(3), ..Default::default() };
+/// WebhookServer::new(client, "shared-secret").risk(risk).serve("127.0.0.1:8080").await?;
+/// ```
+pub struct WebhookServer<E: SignalExecutor = PocketOption> {
+    executor: Arc<E>,
+    secret: Arc<String>,
+    risk: Arc<RiskSettings>,
+    max_age: Duration,
+    /// Digest and timestamp of the alerts accepted in the last `max_age`
+    seen: Arc<Mutex<HashMap<[u8; 32], DateTime<Utc>>>>,
+    /// Held from the risk check until the trade is opened while `max_open_deals` is set
+    trading: Arc<tokio::sync::Mutex<()>>,
+}
+
+enum Rejection {
+    Unauthorized,
+    BadRequest(String),
+    /// The alert is too old or was already recieved
+    Replayed(String),
+    /// The alert is valid but breaks the risk settings or uses an unknown asset
+    Refused(String),
+    TradeFailed(String),
+}
+
+impl Default for RiskSettings {
+    fn default() -> Self {
+        Self {
+            min_amount: 1.0,
+            max_amount: None,
+            min_duration: DEFAULT_MIN_DURATION,
+            max_duration: DEFAULT_MAX_DURATION,
+            min_payout: None,
+            max_open_deals: None,
+        }
+    }
+}
+
+impl RiskSettings {
+    /// Reason to refuse `alert`, `payout` is `None` for assets missing from the asset list
+    fn check(&self, alert: &Alert, payout: Option<i32>, opened_deals: usize) -> Result<(), String> {
+        let Some(payout) = payout else {
+            return Err(format!("Unknown asset '{}'", alert.asset));
+        };
+        if let Some(min_payout) = self.min_payout.filter(|min| payout < *min) {
+            return Err(format!(
+                "Payout of '{}' is {payout}%, the minimum is {min_payout}%",
+                alert.asset
+            ));
+        }
+        if !alert.amount.is_finite()
+            || alert.amount < self.min_amount
+            || self.max_amount.is_some_and(|max| alert.amount > max)
+        {
+            return Err(format!(
+                "Amount {} is out of the allowed range",
+                alert.amount
+            ));
+        }
+        if alert.duration < self.min_duration || alert.duration > self.max_duration {
+            return Err(format!(
+                "Duration of {}s is out of the allowed range",
+                alert.duration
+            ));
+        }
+        if self.max_open_deals.is_some_and(|max| opened_deals >= max) {
+            return Err(format!("There are already {opened_deals} opened deals"));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SignalExecutor for PocketOption {
+    async fn payout(&self) -> HashMap<String, i32> {
+        self.get_payout().await
+    }
+
+    async fn opened_deals(&self) -> usize {
+        self.get_opened_deals().await.len()
+    }
+
+    async fn execute(&self, alert: &Alert) -> PocketResult<(Uuid, Deal)> {
+        self.trade(
+            &alert.asset,
+            alert.direction.clone(),
+            alert.amount,
+            alert.duration,
+        )
+        .await
+    }
+}
+
+impl<E: SignalExecutor> Clone for WebhookServer<E> {
+    fn clone(&self) -> Self {
+        Self {
+            executor: self.executor.clone(),
+            secret: self.secret.clone(),
+            risk: self.risk.clone(),
+            max_age: self.max_age,
+            seen: self.seen.clone(),
+            trading: self.trading.clone(),
+        }
+    }
+}
+
+impl<E: SignalExecutor> WebhookServer<E> {
+    /// Creates a server that trades on `executor`, with an empty `secret` every alert is refused
+    pub fn new(executor: E, secret: impl ToString) -> Self {
+        let secret = secret.to_string();
+        register_secret(&secret);
+        Self {
+            executor: Arc::new(executor),
+            secret: Arc::new(secret),
+            risk: Arc::new(RiskSettings::default()),
+            max_age: DEFAULT_MAX_AGE,
+            seen: Arc::new(Mutex::new(HashMap::new())),
+            trading: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    pub fn risk(mut self, risk: RiskSettings) -> Self {
+        self.risk = Arc::new(risk);
+        self
+    }
+
+    /// Alerts whose `timestamp` is further than `max_age` from now are refused, defaults to a minute
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Router with the `POST /webhook` route, to mount it inside an existing axum application
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/webhook", post(webhook::<E>))
+            .with_state(self)
+    }
+
+    /// Listens on `addr` until the task is dropped
+    pub async fn serve(self, addr: impl ToSocketAddrs) -> PocketResult<()> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(BinaryOptionsToolsError::from)?;
+        self.serve_on(listener).await
+    }
+
+    /// Same as `serve` with an already bound listener, useful to listen on a random port
+    pub async fn serve_on(self, listener: TcpListener) -> PocketResult<()> {
+        if let Ok(addr) = listener.local_addr() {
+            info!(target: "Webhook", "Listening for alerts on http://{addr}/webhook");
+        }
+        axum::serve(listener, self.router())
+            .await
+            .map_err(BinaryOptionsToolsError::from)?;
+        Ok(())
+    }
+
+    fn is_signed(&self, signature: &str, body: &[u8]) -> bool {
+        let signature = signature.trim();
+        let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+        let (Ok(signature), Ok(mut mac)) = (
+            hex::decode(signature),
+            Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()),
+        ) else {
+            return false;
+        };
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Accepts each alert once while its timestamp is recent, older alerts are forgotten since they are stale anyway
+    fn check_replay(&self, alert: &Alert, body: &[u8]) -> Result<(), Rejection> {
+        let timestamp = alert
+            .timestamp
+            .ok_or_else(|| Rejection::BadRequest("Missing the timestamp of the alert".into()))?;
+        let now = Utc::now();
+        // `max_age` is set by the user, an absurd value accepts every timestamp
+        let max_age = chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX);
+        if (now - timestamp).abs() > max_age {
+            return Err(Rejection::Replayed(format!(
+                "Alert sent at {timestamp} is too old"
+            )));
+        }
+        let digest: [u8; 32] = Sha256::digest(body).into();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, sent| now - *sent <= max_age);
+        if seen.insert(digest, timestamp).is_some() {
+            return Err(Rejection::Replayed("Alert was already recieved".into()));
+        }
+        Ok(())
+    }
+
+    async fn handle(&self, headers: &HeaderMap, body: &[u8]) -> Result<Uuid, Rejection> {
+        if self.secret.is_empty() {
+            return Err(Rejection::Unauthorized);
+        }
+        let signature = headers
+            .get(SIGNATURE_HEADER)
+            .map(|s| s.to_str().unwrap_or_default());
+        if signature.is_some_and(|s| !self.is_signed(s, body)) {
+            return Err(Rejection::Unauthorized);
+        }
+        let alert: Alert = serde_json::from_slice(body)
+            .map_err(|e| Rejection::BadRequest(format!("Invalid alert, {e}")))?;
+        if signature.is_none() {
+            let secret = alert.secret.as_deref().unwrap_or_default();
+            if !bool::from(secret.as_bytes().ct_eq(self.secret.as_bytes())) {
+                return Err(Rejection::Unauthorized);
+            }
+        }
+        self.check_replay(&alert, body)?;
+
+        // Two alerts checked at the same time could both take the last allowed deal
+        let _trading = match self.risk.max_open_deals {
+            Some(_) => Some(self.trading.lock().await),
+            None => None,
+        };
+        let payout = self.executor.payout().await.get(&alert.asset).copied();
+        let opened_deals = self.executor.opened_deals().await;
+        self.risk
+            .check(&alert, payout, opened_deals)
+            .map_err(Rejection::Refused)?;
+        info!(target: "Webhook", "Trading {:?} of {} on '{}' for {}s", alert.direction, alert.amount, alert.asset, alert.duration);
+        let (id, _) = self
+            .executor
+            .execute(&alert)
+            .await
+            .map_err(|e| Rejection::TradeFailed(e.to_string()))?;
+        Ok(id)
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            Self::Unauthorized => (StatusCode::UNAUTHORIZED, "Invalid signature".to_string()),
+            Self::BadRequest(e) => (StatusCode::BAD_REQUEST, e),
+            Self::Replayed(e) => (StatusCode::CONFLICT, e),
+            Self::Refused(e) => (StatusCode::UNPROCESSABLE_ENTITY, e),
+            Self::TradeFailed(e) => (StatusCode::BAD_GATEWAY, e),
+        };
+        warn!(target: "Webhook", "Alert rejected, {error}");
+        (status, Json(json!({ "error": error }))).into_response()
+    }
+}
+
+async fn webhook<E: SignalExecutor>(
+    State(server): State<WebhookServer<E>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    match server.handle(&headers, &body).await {
+        Ok(id) => Json(json!({ "id": id })).into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::pocketoption::testing::MockServer;
+
+    const SECRET: &str = "webhook-test-secret";
+
+    struct MockExecutor {
+        deal: Deal,
+        alerts: Mutex<Vec<Alert>>,
+    }
+
+    #[async_trait]
+    impl SignalExecutor for MockExecutor {
+        async fn payout(&self) -> HashMap<String, i32> {
+            HashMap::from([
+                ("EURUSD_otc".to_string(), 92),
+                ("AUDCAD_otc".to_string(), 40),
+            ])
+        }
+
+        async fn opened_deals(&self) -> usize {
+            self.alerts.lock().unwrap().len()
+        }
+
+        async fn execute(&self, alert: &Alert) -> PocketResult<(Uuid, Deal)> {
+            self.alerts.lock().unwrap().push(alert.clone());
+            Ok((self.deal.id, self.deal.clone()))
+        }
+    }
+
+    #[test]
+    fn test_risk_settings() -> anyhow::Result<()> {
+        let risk = RiskSettings {
+            max_amount: Some(10.0),
+            min_payout: Some(80),
+            max_open_deals: Some(2),
+            ..Default::default()
+        };
+        let alert: Alert = serde_json::from_str(
+            r#"{"asset": "EURUSD_otc", "direction": "buy", "amount": 5, "duration": 60}"#,
+        )?;
+        assert_eq!(alert.direction, Action::Call);
+        assert!(risk.check(&alert, Some(92), 0).is_ok());
+        assert!(risk.check(&alert, None, 0).is_err());
+        assert!(risk.check(&alert, Some(40), 0).is_err());
+        assert!(risk.check(&alert, Some(92), 2).is_err());
+        let large = Alert {
+            amount: 50.0,
+            ..alert.clone()
+        };
+        assert!(risk.check(&large, Some(92), 0).is_err());
+        let short = Alert {
+            duration: 1,
+            ..alert
+        };
+        assert!(risk.check(&short, Some(92), 0).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_webhook_server() -> anyhow::Result<()> {
+        let deal: Deal =
+            serde_json::from_str(&std::fs::read_to_string("tests/success_open_order.json")?)?;
+        let executor = MockExecutor {
+            deal: deal.clone(),
+            alerts: Mutex::new(Vec::new()),
+        };
+        let risk = RiskSettings {
+            max_amount: Some(10.0),
+            min_payout: Some(80),
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/webhook", listener.local_addr()?);
+        tokio::spawn(
+            WebhookServer::new(executor, SECRET)
+                .risk(risk)
+                .serve_on(listener),
+        );
+
+        let http = reqwest::Client::new();
+        let post = |body: String, signature: Option<String>| {
+            let mut request = http.post(&url).body(body);
+            if let Some(signature) = signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+            async move {
+                let response = request.send().await?;
+                let status = response.status().as_u16();
+                let body: Value = serde_json::from_str(&response.text().await?)?;
+                anyhow::Ok((status, body))
+            }
+        };
+
+        let alert = json!({"asset": "EURUSD_otc", "direction": "sell", "amount": 2, "duration": 60, "timestamp": Utc::now()});
+        let mut with_secret = alert.clone();
+        with_secret["secret"] = json!(SECRET);
+        let (status, body) = post(with_secret.to_string(), None).await?;
+        assert_eq!(status, 200);
+        assert_eq!(body["id"], json!(deal.id));
+
+        let sign = |body: &str| -> anyhow::Result<String> {
+            let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes())?;
+            mac.update(body.as_bytes());
+            Ok(hex::encode(mac.finalize().into_bytes()))
+        };
+        let signature = sign(&alert.to_string())?;
+        assert_eq!(
+            post(alert.to_string(), Some(format!("sha256={signature}")))
+                .await?
+                .0,
+            200
+        );
+        assert_eq!(post(alert.to_string(), Some("00".repeat(32))).await?.0, 401);
+        assert_eq!(post(alert.to_string(), None).await?.0, 401);
+        assert_eq!(post("not json".into(), Some(signature)).await?.0, 401);
+        assert_eq!(
+            post("not json".into(), Some(sign("not json")?)).await?.0,
+            400
+        );
+
+        // A captured alert can't be traded again, even with a valid signature
+        assert_eq!(
+            post(alert.to_string(), Some(sign(&alert.to_string())?))
+                .await?
+                .0,
+            409
+        );
+        let mut other = alert.clone();
+        other["nonce"] = json!("2");
+        assert_eq!(
+            post(other.to_string(), Some(sign(&other.to_string())?))
+                .await?
+                .0,
+            200
+        );
+        let mut stale = alert.clone();
+        stale["timestamp"] = json!(Utc::now() - chrono::Duration::minutes(5));
+        assert_eq!(
+            post(stale.to_string(), Some(sign(&stale.to_string())?))
+                .await?
+                .0,
+            409
+        );
+        let mut undated = alert.clone();
+        undated.as_object_mut().unwrap().remove("timestamp");
+        assert_eq!(
+            post(undated.to_string(), Some(sign(&undated.to_string())?))
+                .await?
+                .0,
+            400
+        );
+
+        let mut refused = with_secret;
+        refused["asset"] = json!("AUDCAD_otc");
+        assert_eq!(post(refused.to_string(), None).await?.0, 422);
+        refused["asset"] = json!("UNKNOWN");
+        assert_eq!(post(refused.to_string(), None).await?.0, 422);
+        refused["asset"] = json!("EURUSD_otc");
+        refused["amount"] = json!(100);
+        assert_eq!(post(refused.to_string(), None).await?.0, 422);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_webhook_on_mock_server() -> anyhow::Result<()> {
+        let server = MockServer::start().await?;
+        let (client, mut connection) = server.connect(server.config().build()?).await?;
+        let risk = RiskSettings {
+            max_open_deals: Some(1),
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/webhook", listener.local_addr()?);
+        // The mock server sends no assets
+        struct Payout(PocketOption);
+        #[async_trait]
+        impl SignalExecutor for Payout {
+            async fn payout(&self) -> HashMap<String, i32> {
+                HashMap::from([("EURUSD_otc".to_string(), 92)])
+            }
+
+            async fn opened_deals(&self) -> usize {
+                self.0.opened_deals().await
+            }
+
+            async fn execute(&self, alert: &Alert) -> PocketResult<(Uuid, Deal)> {
+                self.0.execute(alert).await
+            }
+        }
+        tokio::spawn(
+            WebhookServer::new(Payout(client), SECRET)
+                .risk(risk)
+                .serve_on(listener),
+        );
+
+        let http = reqwest::Client::new();
+        let post = |nonce: &str| {
+            let alert = json!({
+                "asset": "EURUSD_otc", "direction": "buy", "amount": 1, "duration": 60,
+                "timestamp": Utc::now(), "nonce": nonce, "secret": SECRET,
+            });
+            let request = http.post(&url).body(alert.to_string());
+            async move { anyhow::Ok(request.send().await?.status().as_u16()) }
+        };
+        // Both alerts are checked while no deal is opened, only one of them can be traded
+        let (first, second, deal) = tokio::join!(post("1"), post("2"), connection.open_order());
+        deal?;
+        let mut statuses = [first?, second?];
+        statuses.sort();
+        assert_eq!(statuses, [200, 422]);
+        assert!(
+            connection
+                .recv_event_within("openOrder", Duration::from_millis(300))
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+}
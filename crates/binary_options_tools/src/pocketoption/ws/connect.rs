@@ -1,43 +1,84 @@
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_channel::{Sender, bounded};
 use async_trait::async_trait;
 use futures_util::future::join_all;
 use tokio::net::TcpStream;
-use tracing::info;
+use tracing::{info, warn};
 use url::Url;
 
-use crate::pocketoption::{error::PocketOptionError, utils::connect::try_connect};
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    utils::connect::try_connect,
+};
 use binary_options_tools_core::{
     error::{BinaryOptionsResult, BinaryOptionsToolsError},
     general::{
         config::Config,
+        connection::ConnectionConfig,
         traits::{Connect, DataHandler, InnerConfig, MessageTransfer},
     },
     reimports::{MaybeTlsStream, WebSocketStream},
 };
 
-use super::ssid::Ssid;
+use super::{
+    health::HealthTable,
+    regions::{Regions, ServerSelection},
+    ssid::Ssid,
+};
 
-#[derive(Clone)]
-pub struct PocketConnect;
+#[derive(Clone, Default)]
+pub struct PocketConnect {
+    /// How the server is chosen when none of the default connection urls works
+    pub servers: ServerSelection,
+    /// Success rate, latency and cool-down of every server tried so far
+    pub health: HealthTable,
+}
+
+impl PocketConnect {
+    /// Connects to `url` and records the result in the health table
+    async fn attempt(
+        health: &HealthTable,
+        creds: Ssid,
+        url: &str,
+        connection: &ConnectionConfig,
+    ) -> PocketResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+        let start = Instant::now();
+        match try_connect(creds, url.to_string(), connection).await {
+            Ok(websocket) => {
+                health.record_success(url, start.elapsed()).await;
+                Ok(websocket)
+            }
+            Err(e) => {
+                health.record_failure(url).await;
+                Err(e)
+            }
+        }
+    }
+}
 
 #[async_trait]
 impl Connect for PocketConnect {
     type Creds = Ssid;
+    type Stream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
     async fn connect<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig>(
         &self,
         creds: Self::Creds,
         config: &Config<T, Transfer, U>,
-    ) -> BinaryOptionsResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    ) -> BinaryOptionsResult<Self::Stream> {
         async fn send_ws(
             creds: Ssid,
             url: String,
+            connection: Arc<ConnectionConfig>,
+            health: HealthTable,
             sender: Sender<(WebSocketStream<MaybeTlsStream<TcpStream>>, String)>,
         ) -> BinaryOptionsResult<()> {
             info!(target: "TryConnect", "Trying to connecto to {}", url);
-            if let Ok(connect) = try_connect(creds, url.clone()).await {
+            if let Ok(connect) = PocketConnect::attempt(&health, creds, &url, &connection).await {
                 info!(target: "SuccessConnect", "Succesfully connected to {}", url);
                 sender.send((connect, url.clone())).await.map_err(|e| {
                     BinaryOptionsToolsError::GeneralMessageSendingError(e.to_string())
@@ -48,23 +89,98 @@ impl Connect for PocketConnect {
                 url,
             ))
         }
+        let connection = config.load_connection();
+        // After switching between the demo and the real account the servers of the previous account are skipped
+        let preferred = self
+            .health
+            .preferred()
+            .await
+            .filter(|url| !Regions::is_other_account(url, creds.demo()));
+        if let Some(url) = &preferred {
+            info!(target: "TryConnect", "Trying last healthy server {}", url);
+            match Self::attempt(&self.health, creds.clone(), url, &connection).await {
+                Ok(connect) => {
+                    self.health.record_connected(url).await;
+                    return Ok(connect);
+                }
+                Err(e) => warn!(target: "TryConnect", "Failed to connect to {url}, {e}"),
+            }
+        }
         let (sender, reciever) = bounded(1); // It should stop after recieving only one message
-        let default_urls = config.get_default_connection_url()?;
-        let default_connections = default_urls
+        let default_urls = config
+            .get_default_connection_url()
             .iter()
-            .map(|url| tokio::spawn(send_ws(creds.clone(), url.to_string(), sender.clone())));
+            .map(|url| url.to_string())
+            .filter(|url| Some(url) != preferred.as_ref())
+            .filter(|url| !Regions::is_other_account(url, creds.demo()))
+            .collect();
+        let default_urls = self.health.available(default_urls).await;
+        let default_connections = default_urls.iter().map(|url| {
+            tokio::spawn(send_ws(
+                creds.clone(),
+                url.to_string(),
+                connection.clone(),
+                self.health.clone(),
+                sender.clone(),
+            ))
+        });
         tokio::select! {
-            res = reciever.recv() => return Ok(res.map(|(r, _)| r)?),
+            res = reciever.recv() => {
+                let (connect, url) = res?;
+                self.health.record_connected(&url).await;
+                return Ok(connect);
+            },
             _ = join_all(default_connections) => {}
         }
-        let urls = creds.servers().await?;
-        let connections = urls
-            .iter()
-            .map(|url| tokio::spawn(send_ws(creds.clone(), url.to_owned(), sender.clone())));
+        let urls = match &self.servers {
+            ServerSelection::Latency => creds.servers().await?,
+            ServerSelection::Region(name) => vec![
+                Regions::region_url(name)
+                    .map_err(PocketOptionError::from)?
+                    .to_string(),
+            ],
+            ServerSelection::Distance(provider) => {
+                let location = provider.location().await?;
+                let servers =
+                    Regions::sort_by_distance(Regions::for_account(creds.demo()), location)
+                        .into_iter()
+                        .map(String::from)
+                        .collect();
+                let servers = self.health.available(servers).await;
+                for url in servers.iter() {
+                    match Self::attempt(&self.health, creds.clone(), url, &connection).await {
+                        Ok(connect) => {
+                            config.add_default_connection_url(Url::parse(url)?);
+                            self.health.record_connected(url).await;
+                            return Ok(connect);
+                        }
+                        Err(e) => warn!(target: "TryConnect", "Failed to connect to {url}, {e}"),
+                    }
+                }
+                return Err(
+                    PocketOptionError::WebsocketMultipleAttemptsConnectionError(format!(
+                        "Couldn't connect to server after {} attempts.",
+                        servers.len()
+                    ))
+                    .into(),
+                );
+            }
+        };
+        let urls = self.health.available(urls).await;
+        let connections = urls.iter().map(|url| {
+            tokio::spawn(send_ws(
+                creds.clone(),
+                url.to_owned(),
+                connection.clone(),
+                self.health.clone(),
+                sender.clone(),
+            ))
+        });
         tokio::select! {
             res = reciever.recv() => match res {
                 Ok((res, url)) => {
-                    config.add_default_connection_url(Url::parse(&url)?)?;
+                    config.add_default_connection_url(Url::parse(&url)?);
+                    self.health.record_connected(&url).await;
                     Ok(res)
                 },
                 Err(e) => Err(e.into())
@@ -83,7 +199,7 @@ impl Connect for PocketConnect {
     //     creds: Self::Creds,
     //     config: &Config<T, Transfer>,
     // ) -> BinaryOptionsResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-    //     for url in config.get_default_connection_url()? {
+    //     for url in config.get_default_connection_url() {
     //         info!("Using default connection url...");
     //         if let Ok(connect) = try_connect(creds.clone(), url.to_string()).await {
     //             return Ok(connect);
@@ -0,0 +1,185 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Consecutive failed handshakes after which a server is skipped
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// How long a server is skipped after reaching `MAX_CONSECUTIVE_FAILURES`
+pub const COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Connection history of a single server
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServerHealth {
+    pub successes: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_failure: Option<DateTime<Utc>>,
+    /// Duration of the last successful websocket handshake
+    pub latency: Option<Duration>,
+    /// The server is skipped until this moment
+    pub cooldown_until: Option<DateTime<Utc>>,
+}
+
+impl ServerHealth {
+    /// Ratio of successful handshakes, `None` if the server was never used
+    pub fn success_rate(&self) -> Option<f64> {
+        let total = self.successes + self.failures;
+        (total > 0).then(|| self.successes as f64 / total as f64)
+    }
+
+    pub fn is_cooling_down(&self, now: DateTime<Utc>) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    servers: HashMap<String, ServerHealth>,
+    last_healthy: Option<String>,
+}
+
+/// Health of every server used by the client, shared between all of its connections
+#[derive(Debug, Clone, Default)]
+pub struct HealthTable {
+    inner: Arc<Mutex<Table>>,
+}
+
+impl HealthTable {
+    /// Records a successful handshake, servers that lose a connection race are recorded too
+    pub async fn record_success(&self, url: &str, latency: Duration) {
+        let mut table = self.inner.lock().await;
+        let health = table.servers.entry(url.to_string()).or_default();
+        health.successes += 1;
+        health.consecutive_failures = 0;
+        health.last_success = Some(Utc::now());
+        health.latency = Some(latency);
+        health.cooldown_until = None;
+    }
+
+    /// Records the server the client kept the connection of, it's the `preferred` one from now on
+    pub async fn record_connected(&self, url: &str) {
+        self.inner.lock().await.last_healthy = Some(url.to_string());
+    }
+
+    pub async fn record_failure(&self, url: &str) {
+        self.record_failure_at(url, Utc::now()).await
+    }
+
+    async fn record_failure_at(&self, url: &str, now: DateTime<Utc>) {
+        let mut table = self.inner.lock().await;
+        let health = table.servers.entry(url.to_string()).or_default();
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        health.last_failure = Some(now);
+        if health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            // `COOLDOWN` is a small constant so the conversion never fails
+            health.cooldown_until =
+                Some(now + chrono::Duration::from_std(COOLDOWN).unwrap_or_default());
+            warn!(target: "ServerHealth", "Skipping '{url}' for {}s after {} failed attempts", COOLDOWN.as_secs(), health.consecutive_failures);
+        }
+        if table.last_healthy.as_deref() == Some(url) {
+            table.last_healthy = None;
+        }
+    }
+
+    /// Last server that accepted a connection and didn't fail since then
+    pub async fn preferred(&self) -> Option<String> {
+        self.inner.lock().await.last_healthy.clone()
+    }
+
+    /// Removes the servers that are cooling down, if every server is cooling down all of them are kept
+    /// so the client never runs out of servers to try
+    pub async fn available(&self, urls: Vec<String>) -> Vec<String> {
+        self.available_at(urls, Utc::now()).await
+    }
+
+    async fn available_at(&self, urls: Vec<String>, now: DateTime<Utc>) -> Vec<String> {
+        let table = self.inner.lock().await;
+        let available: Vec<String> = urls
+            .iter()
+            .filter(|url| {
+                !table
+                    .servers
+                    .get(url.as_str())
+                    .is_some_and(|health| health.is_cooling_down(now))
+            })
+            .cloned()
+            .collect();
+        if available.is_empty() {
+            urls
+        } else {
+            available
+        }
+    }
+
+    /// Copy of the health of every server used so far
+    pub async fn snapshot(&self) -> HashMap<String, ServerHealth> {
+        self.inner.lock().await.servers.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cooldown() {
+        let table = HealthTable::default();
+        let urls = vec!["wss://a".to_string(), "wss://b".to_string()];
+        let now = Utc::now();
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            table.record_failure_at("wss://a", now).await;
+        }
+        assert_eq!(
+            table.available_at(urls.clone(), now).await,
+            vec!["wss://b".to_string()]
+        );
+        let later =
+            now + chrono::Duration::from_std(COOLDOWN).unwrap() + chrono::Duration::seconds(1);
+        assert_eq!(table.available_at(urls.clone(), later).await, urls);
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            table.record_failure_at("wss://b", now).await;
+        }
+        // Every server is cooling down so all of them are tried
+        assert_eq!(table.available_at(urls.clone(), now).await, urls);
+
+        table
+            .record_success("wss://a", Duration::from_millis(120))
+            .await;
+        assert_eq!(
+            table.available_at(urls.clone(), now).await,
+            vec!["wss://a".to_string()]
+        );
+        let health = table.snapshot().await["wss://a"].clone();
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(
+            health.success_rate(),
+            Some(1.0 / (MAX_CONSECUTIVE_FAILURES + 1) as f64)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preferred() {
+        let table = HealthTable::default();
+        assert_eq!(table.preferred().await, None);
+        table
+            .record_success("wss://a", Duration::from_millis(80))
+            .await;
+        // Only the server that was kept is preferred
+        assert_eq!(table.preferred().await, None);
+        table
+            .record_success("wss://b", Duration::from_millis(90))
+            .await;
+        table.record_connected("wss://b").await;
+        assert_eq!(table.preferred().await.as_deref(), Some("wss://b"));
+        table.record_failure("wss://a").await;
+        assert_eq!(table.preferred().await.as_deref(), Some("wss://b"));
+        table.record_failure("wss://b").await;
+        assert_eq!(table.preferred().await, None);
+    }
+}
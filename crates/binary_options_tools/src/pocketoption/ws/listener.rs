@@ -4,6 +4,7 @@ use serde_json::Value;
 use binary_options_tools_core::{
     error::{BinaryOptionsResult, BinaryOptionsToolsError},
     general::{
+        credentials::CredentialStore,
         send::SenderMessage,
         traits::{MessageHandler, MessageTransfer},
         types::MessageType,
@@ -19,13 +20,36 @@ use crate::pocketoption::{
 
 use super::ssid::Ssid;
 
+/// Events the server sends when the ssid is wrong or expired
+const AUTH_FAILURE_EVENTS: [&str; 2] = ["NotAuthorized", "unauthorized"];
+
+/// Reason of the rejection if `text` is an authorization failure event, like `42["NotAuthorized"]`
+fn auth_failure(text: &str) -> Option<String> {
+    let event = text
+        .strip_prefix("451-")
+        .or_else(|| text.strip_prefix("42"))?;
+    let event: Vec<Value> = serde_json::from_str(event).ok()?;
+    let name = event.first()?.as_str()?;
+    if !AUTH_FAILURE_EVENTS
+        .iter()
+        .any(|e| e.eq_ignore_ascii_case(name))
+    {
+        return None;
+    }
+    Some(match event.get(1) {
+        Some(Value::String(reason)) => reason.to_owned(),
+        Some(detail) => detail.to_string(),
+        None => name.to_string(),
+    })
+}
+
 #[derive(Clone)]
 pub struct Handler {
-    ssid: Ssid,
+    ssid: CredentialStore<Ssid>,
 }
 
 impl Handler {
-    pub fn new(ssid: Ssid) -> Self {
+    pub fn new(ssid: CredentialStore<Ssid>) -> Self {
         Self { ssid }
     }
 
@@ -57,7 +81,7 @@ impl Handler {
             }
             _ if text.starts_with("40") && text.contains("sid") => {
                 sender
-                    .priority_send(Message::text(self.ssid.to_string()))
+                    .priority_send(Message::text(self.ssid.load().expose_secret()))
                     .await?;
             }
             _ if text == "2" => {
@@ -111,6 +135,14 @@ impl MessageHandler for Handler {
                 return Ok((Some(MessageType::Transfer(msg)), false));
             }
             Message::Text(text) => {
+                if let Some(reason) = auth_failure(text.as_ref()) {
+                    return Ok((
+                        Some(MessageType::Transfer(WebSocketMessage::NotAuthorized(
+                            reason,
+                        ))),
+                        false,
+                    ));
+                }
                 let res = self
                     .handle_text_msg::<WebSocketMessage>(text.as_ref(), sender)
                     .await?;
@@ -132,3 +164,26 @@ impl MessageHandler for Handler {
         Ok((None, false))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_failure() {
+        assert_eq!(
+            auth_failure(r#"42["NotAuthorized"]"#).as_deref(),
+            Some("NotAuthorized")
+        );
+        assert_eq!(
+            auth_failure(r#"42["unauthorized","Session expired"]"#).as_deref(),
+            Some("Session expired")
+        );
+        assert_eq!(
+            auth_failure(r#"451-["successauth",{"_placeholder":true,"num":0}]"#),
+            None
+        );
+        assert_eq!(auth_failure(r#"0{"sid":"abc"}"#), None);
+        assert_eq!(auth_failure("2"), None);
+    }
+}
@@ -1,6 +1,7 @@
 // pub mod api;
 // pub mod basic;
 pub mod connect;
+pub mod health;
 pub mod listener;
 pub mod regions;
 pub mod ssid;
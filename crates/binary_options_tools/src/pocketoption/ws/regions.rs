@@ -1,64 +1,179 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use binary_options_tools_core::general::connection::ConnectionConfig;
 use binary_options_tools_macros::RegionImpl;
+use futures_util::future::join_all;
+use tracing::debug;
 
 use crate::pocketoption::{
-    error::PocketResult,
-    utils::location::{calculate_distance, get_public_ip, get_user_location},
+    error::{PocketOptionError, PocketResult, UnknownRegion},
+    utils::{
+        connect::try_connect,
+        location::{LocationProvider, calculate_distance},
+    },
 };
 
+use super::ssid::Ssid;
+
 #[derive(RegionImpl)]
 #[region(path = "src/pocketoption/ws/regions.json")]
 pub struct Regions;
 
+/// Decides which servers of `regions.json` are used when none of the `default_connection_url` works
+#[derive(Clone, Default)]
+pub enum ServerSelection {
+    /// Connects to every server at once and keeps the first websocket handshake that completes
+    #[default]
+    Latency,
+    /// Tries the servers one at a time, from the closest to the farthest from the given location
+    Distance(Arc<dyn LocationProvider>),
+    /// Only uses the server of the region with this name, for example `EUROPE`
+    Region(String),
+}
+
 impl Regions {
-    async fn get_closest_server(&self, ip_address: &str) -> PocketResult<(&str, f64)> {
-        let user_location = get_user_location(ip_address).await?;
-
-        let mut closest = ("", f64::INFINITY);
-        Self::regions().iter().for_each(|(server, lat, lon)| {
-            let distance = calculate_distance(user_location.0, user_location.1, *lat, *lon);
-            if distance < closest.1 {
-                closest = (*server, distance)
-            }
-        });
-        Ok(closest)
+    /// Servers available for the account, the real ones or the demo ones
+    pub fn for_account(demo: bool) -> Vec<(&'static str, f64, f64)> {
+        if demo {
+            Self::demo_regions()
+        } else {
+            Self::regions()
+        }
+    }
+
+    /// Checks if `url` is a known server of the other kind of account, custom urls are never rejected
+    pub fn is_other_account(url: &str, demo: bool) -> bool {
+        Self::for_account(!demo)
+            .into_iter()
+            .any(|(server, _, _)| server.trim_end_matches('/') == url.trim_end_matches('/'))
+    }
+
+    /// Url of the region with the given name
+    pub fn region_url(name: &str) -> Result<&'static str, UnknownRegion> {
+        Self::by_name(name)
+            .map(|(url, _, _)| url)
+            .ok_or_else(|| UnknownRegion(name.to_string()))
     }
 
-    async fn sort_servers(&self, ip_address: &str) -> PocketResult<Vec<&str>> {
-        let user_location = get_user_location(ip_address).await?;
-        let mut distances = Self::regions()
-            .iter()
+    /// Sorts the servers from the closest to the farthest from `location`
+    pub fn sort_by_distance(
+        servers: Vec<(&'static str, f64, f64)>,
+        location: (f64, f64),
+    ) -> Vec<&'static str> {
+        let mut distances = servers
+            .into_iter()
             .map(|(server, lat, lon)| {
-                (
-                    *server,
-                    calculate_distance(user_location.0, user_location.1, *lat, *lon),
-                )
+                (server, calculate_distance(location.0, location.1, lat, lon))
             })
             .collect::<Vec<(&str, f64)>>();
-        distances.sort_by(|(_, a), (_, b)| b.total_cmp(a));
-        Ok(distances.into_iter().map(|(s, _)| s).collect())
+        distances.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        distances.into_iter().map(|(s, _)| s).collect()
     }
 
-    pub async fn get_server(&self) -> PocketResult<&str> {
-        let ip = get_public_ip().await?;
-        let server = self.get_closest_server(&ip).await?;
-        Ok(server.0)
+    /// Measures the websocket handshake latency of every server, unreachable servers are left out.
+    /// The result is sorted from the fastest to the slowest server.
+    ///
+    /// `ServerSelection::Latency` doesn't use it, the client connects to every server at once and keeps
+    /// the first connection instead of closing them and connecting again.
+    pub async fn measure_latency(
+        ssid: &Ssid,
+        connection: &ConnectionConfig,
+        servers: &[&'static str],
+    ) -> Vec<(&'static str, Duration)> {
+        let measures = servers.iter().map(|server| async move {
+            let start = Instant::now();
+            match try_connect(ssid.clone(), server.to_string(), connection).await {
+                Ok(mut websocket) => {
+                    let latency = start.elapsed();
+                    websocket.close(None).await.ok();
+                    Some((*server, latency))
+                }
+                Err(e) => {
+                    debug!(target: "Regions", "Server '{server}' is unreachable, {e}");
+                    None
+                }
+            }
+        });
+        let mut latencies: Vec<_> = join_all(measures).await.into_iter().flatten().collect();
+        latencies.sort_by_key(|(_, latency)| *latency);
+        latencies
     }
 
-    pub async fn get_servers(&self) -> PocketResult<Vec<&str>> {
-        let ip = get_public_ip().await?;
-        self.sort_servers(&ip).await
+    /// Servers of the account sorted by websocket handshake latency
+    pub async fn get_servers(
+        ssid: &Ssid,
+        connection: &ConnectionConfig,
+    ) -> PocketResult<Vec<&'static str>> {
+        let servers: Vec<_> = Self::for_account(ssid.demo())
+            .into_iter()
+            .map(|(url, _, _)| url)
+            .collect();
+        let latencies = Self::measure_latency(ssid, connection, &servers).await;
+        if latencies.is_empty() {
+            return Err(PocketOptionError::WebsocketMultipleAttemptsConnectionError(
+                format!("None of the {} servers is reachable", servers.len()),
+            ));
+        }
+        Ok(latencies.into_iter().map(|(server, _)| server).collect())
+    }
+
+    /// Server of the account with the lowest websocket handshake latency
+    pub async fn get_server(
+        ssid: &Ssid,
+        connection: &ConnectionConfig,
+    ) -> PocketResult<&'static str> {
+        Ok(Self::get_servers(ssid, connection).await?[0])
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::pocketoption::utils::location::StaticLocation;
+
     use super::*;
 
+    #[test]
+    fn test_region_by_name() -> anyhow::Result<()> {
+        assert_eq!(Regions::region_url("europe")?, Regions::EUROPE.0);
+        assert_eq!(Regions::region_url("Hong_Kong")?, Regions::HONG_KONG.0);
+        assert!(Regions::region_url("ATLANTIS").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_other_account() {
+        let demo = Regions::demo_regions()[0].0;
+        assert!(Regions::is_other_account(demo, false));
+        assert!(!Regions::is_other_account(demo, true));
+        assert!(Regions::is_other_account(Regions::EUROPE.0, true));
+        assert!(!Regions::is_other_account(
+            "wss://example.com/socket.io/?EIO=4&transport=websocket",
+            true
+        ));
+    }
+
     #[tokio::test]
-    async fn test_get_closest_server() -> anyhow::Result<()> {
-        // let ip = get_public_ip().await?;
-        let server = Regions.get_server().await?;
-        dbg!(server);
+    async fn test_sort_by_distance() -> anyhow::Result<()> {
+        let location = StaticLocation {
+            latitude: Regions::HONG_KONG.1,
+            longitude: Regions::HONG_KONG.2,
+        }
+        .location()
+        .await?;
+        let servers = Regions::sort_by_distance(Regions::regions(), location);
+        assert_eq!(servers.len(), Regions::regions().len());
+        assert_eq!(servers[0], Regions::HONG_KONG.0);
+        let distance = |server: &str| {
+            let (_, lat, lon) = Regions::regions()
+                .into_iter()
+                .find(|(s, _, _)| *s == server)
+                .unwrap();
+            calculate_distance(location.0, location.1, lat, lon)
+        };
+        assert!(servers.windows(2).all(|w| distance(w[0]) <= distance(w[1])));
         Ok(())
     }
 }
@@ -1,14 +1,22 @@
 use core::fmt;
+use std::{sync::Arc, time::Duration};
 
+use async_trait::async_trait;
+
+use percent_encoding::percent_decode_str;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::pocketoption::error::{PocketOptionError, PocketResult};
-use binary_options_tools_core::general::traits::Credentials;
+use crate::pocketoption::error::{PocketOptionError, PocketResult, SsidError, SsidResult};
+use binary_options_tools_core::{
+    error::BinaryOptionsResult,
+    general::{connection::ConnectionConfig, credentials::CredentialProvider, traits::Credentials},
+    utils::redact::{REDACTED, register_secret},
+};
 
 use super::regions::Regions;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct SessionData {
     session_id: String,
     ip_address: String,
@@ -16,16 +24,18 @@ pub struct SessionData {
     last_activity: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Demo {
     session: String,
     is_demo: u32,
     uid: u32,
     platform: u32,
+    #[serde(skip)]
+    user_agent: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Real {
     session: SessionData,
@@ -33,6 +43,8 @@ pub struct Real {
     uid: u32,
     platform: u32,
     raw: String,
+    #[serde(skip)]
+    user_agent: Option<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -42,63 +54,287 @@ pub enum Ssid {
     Real(Real),
 }
 
+/// Body of the `auth` message, only `session` is required
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthPayload {
+    session: String,
+    is_demo: Option<u32>,
+    #[serde(default)]
+    uid: u32,
+    platform: Option<u32>,
+}
+
+/// Builds an `Ssid` from its fields, created with `Ssid::builder()`
+#[derive(Debug, Clone, Default)]
+pub struct SsidBuilder {
+    session: Option<String>,
+    uid: Option<u32>,
+    demo: Option<bool>,
+    platform: Option<u32>,
+    user_agent: Option<String>,
+}
+
+const DEFAULT_PLATFORM: u32 = 2;
+const DEMO_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// Real accounts use the php-serialized session, like `a:4:{s:10:"session_id";...}<hash>`
+fn is_php_session(session: &str) -> bool {
+    session.starts_with("a:") && session.contains('{')
+}
+
+fn parse_number(key: &str, value: &str) -> SsidResult<u32> {
+    value
+        .parse()
+        .map_err(|e| SsidError::Parsing(format!("Invalid '{key}' value '{value}', {e}")))
+}
+
+impl SsidBuilder {
+    /// Session id of a demo account or php-serialized session of a real account
+    pub fn session(mut self, session: impl ToString) -> Self {
+        self.session = Some(session.to_string());
+        self
+    }
+
+    pub fn uid(mut self, uid: u32) -> Self {
+        self.uid = Some(uid);
+        self
+    }
+
+    /// Defaults to `true` unless the session is php-serialized
+    pub fn demo(mut self, demo: bool) -> Self {
+        self.demo = Some(demo);
+        self
+    }
+
+    pub fn platform(mut self, platform: u32) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// User agent sent in the websocket handshake, by default the one of the session is used
+    pub fn user_agent(mut self, user_agent: impl ToString) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Builds the `Ssid` and checks it with `Ssid::validate`
+    pub fn build(self) -> SsidResult<Ssid> {
+        let session = self
+            .session
+            .ok_or_else(|| SsidError::Validation(vec!["missing session".into()]))?;
+        let demo = self.demo.unwrap_or_else(|| !is_php_session(&session));
+        let ssid = Ssid::from_parts(
+            session,
+            demo as u32,
+            self.uid.unwrap_or_default(),
+            self.platform.unwrap_or(DEFAULT_PLATFORM),
+            None,
+            self.user_agent,
+        )?;
+        ssid.validate()?;
+        Ok(ssid)
+    }
+}
+
 impl Ssid {
-    pub fn parse(data: impl ToString) -> PocketResult<Self> {
+    /// Parses the ssid from any of these formats:
+    /// * The `auth` message, `42["auth",{"session":"...","isDemo":1,"uid":12345,"platform":2}]`
+    /// * The json object of the `auth` message
+    /// * A cookie string, like `ci_session=...; uid=12345`
+    /// * The php-serialized session of a real account
+    ///
+    /// Missing fields don't make the parsing fail, use `Ssid::validate` to check them.
+    pub fn parse(data: impl ToString) -> SsidResult<Self> {
         let data = data.to_string();
-        let parsed = data
-            .trim()
-            .strip_prefix(r#"42["auth","#)
-            .ok_or(PocketOptionError::SsidParsingError(
-                "Error parsing ssid string into object".into(),
-            ))?
-            .strip_suffix("]")
-            .ok_or(PocketOptionError::SsidParsingError(
-                "Error parsing ssid string into object".into(),
-            ))?;
-        let ssid: Demo = serde_json::from_str(parsed)
-            .map_err(|e| PocketOptionError::SsidParsingError(e.to_string()))?;
-        if ssid.is_demo == 1 {
-            Ok(Self::Demo(ssid))
+        let data = data.trim();
+        if let Some(message) = data.strip_prefix("42") {
+            let (event, payload): (String, Value) = serde_json::from_str(message).map_err(|e| {
+                SsidError::Parsing(format!("Error parsing ssid string into object, {e}"))
+            })?;
+            if event != "auth" {
+                return Err(SsidError::Parsing(format!(
+                    "Expected an 'auth' message, got '{event}'"
+                )));
+            }
+            Self::from_payload(payload, Some(data.to_string()))
+        } else if data.starts_with('{') {
+            let payload: Value =
+                serde_json::from_str(data).map_err(|e| SsidError::Parsing(e.to_string()))?;
+            Self::from_payload(payload, Some(format!(r#"42["auth",{data}]"#)))
+        } else if is_php_session(data) {
+            Self::from_parts(data.to_string(), 0, 0, DEFAULT_PLATFORM, None, None)
+        } else if data.contains('=') {
+            Self::from_cookies(data)
+        } else {
+            Err(SsidError::Parsing(
+                "Expected an auth message, a json object, a cookie string or a php-serialized session".into(),
+            ))
+        }
+    }
+
+    pub fn builder() -> SsidBuilder {
+        SsidBuilder::default()
+    }
+
+    fn from_payload(payload: Value, raw: Option<String>) -> SsidResult<Self> {
+        let payload: AuthPayload =
+            serde_json::from_value(payload).map_err(|e| SsidError::Parsing(e.to_string()))?;
+        let is_demo = payload
+            .is_demo
+            .unwrap_or_else(|| !is_php_session(&payload.session) as u32);
+        Self::from_parts(
+            payload.session,
+            is_demo,
+            payload.uid,
+            payload.platform.unwrap_or(DEFAULT_PLATFORM),
+            raw,
+            None,
+        )
+    }
+
+    fn from_cookies(data: &str) -> SsidResult<Self> {
+        let mut session = None;
+        let mut uid = 0;
+        let mut is_demo = None;
+        let mut platform = DEFAULT_PLATFORM;
+        for (key, value) in data.split(';').filter_map(|pair| pair.split_once('=')) {
+            let key = key.trim();
+            let value = percent_decode_str(value.trim().trim_matches('"'))
+                .decode_utf8()
+                .map_err(|e| SsidError::Parsing(format!("Invalid '{key}' cookie, {e}")))?;
+            match key.to_lowercase().as_str() {
+                "ci_session" | "session" => session = Some(value.into_owned()),
+                "uid" | "user_id" => uid = parse_number(key, &value)?,
+                "isdemo" | "is_demo" => is_demo = Some(parse_number(key, &value)?),
+                "platform" => platform = parse_number(key, &value)?,
+                _ => {}
+            }
+        }
+        let session = session
+            .ok_or_else(|| SsidError::Parsing("Missing 'ci_session' or 'session' cookie".into()))?;
+        let is_demo = is_demo.unwrap_or_else(|| !is_php_session(&session) as u32);
+        Self::from_parts(session, is_demo, uid, platform, None, None)
+    }
+
+    fn from_parts(
+        session: String,
+        is_demo: u32,
+        uid: u32,
+        platform: u32,
+        raw: Option<String>,
+        user_agent: Option<String>,
+    ) -> SsidResult<Self> {
+        register_secret(&session);
+        let demo = Demo {
+            session,
+            is_demo,
+            uid,
+            platform,
+            user_agent,
+        };
+        if is_demo == 1 {
+            return Ok(Self::Demo(demo));
+        }
+        let session: SessionData = php_serde::from_bytes(demo.session.as_bytes())
+            .map_err(|e| SsidError::Parsing(format!("Error parsing session data, {e}")))?;
+        // Inside the json of the `auth` message the quotes of the php session are escaped,
+        // so the session id and the signature after it are scrubbed on their own
+        register_secret(&session.session_id);
+        if let Some((_, signature)) = demo.session.rsplit_once('}') {
+            register_secret(signature);
+        }
+        Ok(Self::Real(Real {
+            session,
+            is_demo,
+            uid,
+            platform,
+            // Real accounts share the format of the demo `auth` message
+            raw: raw.unwrap_or_else(|| demo.auth_message()),
+            user_agent: demo.user_agent,
+        }))
+    }
+
+    /// Reports the missing or inconsistent fields, `PocketOption` checks the ssid before connecting
+    pub fn validate(&self) -> SsidResult<()> {
+        let mut problems = Vec::new();
+        match self {
+            Self::Demo(demo) => {
+                if demo.session.trim().is_empty() {
+                    problems.push("missing session".to_string());
+                }
+                if is_php_session(&demo.session) {
+                    problems
+                        .push("isDemo is 1 but the session belongs to a real account".to_string());
+                }
+                if demo.uid == 0 {
+                    problems.push("missing uid".to_string());
+                }
+            }
+            Self::Real(real) => {
+                if real.session.session_id.is_empty() {
+                    problems.push("missing session_id in the session".to_string());
+                }
+                if real.session.user_agent.is_empty() {
+                    problems.push("missing user_agent in the session".to_string());
+                }
+                if real.user_agent.as_ref().is_some_and(|agent| {
+                    !real.session.user_agent.is_empty() && *agent != real.session.user_agent
+                }) {
+                    problems
+                        .push("the user agent doesn't match the one of the session".to_string());
+                }
+                if real.is_demo != 0 {
+                    problems.push(format!("isDemo should be 0 or 1, got {}", real.is_demo));
+                }
+                if real.uid == 0 {
+                    problems.push("missing uid".to_string());
+                }
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
         } else {
-            let real = Real {
-                raw: data,
-                is_demo: ssid.is_demo,
-                session: php_serde::from_bytes(ssid.session.as_bytes()).map_err(|e| {
-                    PocketOptionError::SsidParsingError(format!("Error parsing session data, {e}"))
-                })?,
-                uid: ssid.uid,
-                platform: ssid.platform,
-            };
-            Ok(Self::Real(real))
+            Err(SsidError::Validation(problems))
         }
     }
 
-    pub async fn server(&self) -> PocketResult<String> {
+    pub fn uid(&self) -> u32 {
         match self {
-            Self::Demo(_) => Ok(Regions::DEMO.0.to_string()),
-            Self::Real(_) => Regions.get_server().await.map(|s| s.to_string()),
+            Self::Demo(demo) => demo.uid,
+            Self::Real(real) => real.uid,
         }
     }
 
-    pub async fn servers(&self) -> PocketResult<Vec<String>> {
+    /// Server of the account with the lowest websocket handshake latency, measured through the proxy
+    /// and TLS settings of `connection`
+    pub async fn server(&self, connection: &ConnectionConfig) -> PocketResult<String> {
         match self {
-            Self::Demo(_) => Ok(Regions::demo_regions_str()
-                .iter()
-                .map(|r| r.to_string())
-                .collect()),
-            Self::Real(_) => Ok(Regions
-                .get_servers()
-                .await?
-                .iter()
-                .map(|s| s.to_string())
-                .collect()),
+            Self::Demo(_) => Ok(Regions::DEMO.0.to_string()),
+            Self::Real(_) => Regions::get_server(self, connection)
+                .await
+                .map(|s| s.to_string()),
         }
     }
 
+    /// Every server available for the account, doesn't make any network request
+    pub async fn servers(&self) -> PocketResult<Vec<String>> {
+        Ok(Regions::for_account(self.demo())
+            .into_iter()
+            .map(|(url, _, _)| url.to_string())
+            .collect())
+    }
+
     pub fn user_agent(&self) -> String {
         match self {
-            Self::Demo(_) => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36".into(),
-            Self::Real(real) => real.session.user_agent.clone()
+            Self::Demo(demo) => demo
+                .user_agent
+                .clone()
+                .unwrap_or_else(|| DEMO_USER_AGENT.into()),
+            Self::Real(real) => real
+                .user_agent
+                .clone()
+                .unwrap_or_else(|| real.session.user_agent.clone()),
         }
     }
 
@@ -108,17 +344,41 @@ impl Ssid {
             Self::Real(_) => false,
         }
     }
+
+    /// The `auth` message with the full session, `Display` and `Debug` hide the session
+    pub fn expose_secret(&self) -> String {
+        match self {
+            Self::Demo(demo) => demo.auth_message(),
+            Self::Real(real) => real.raw.clone(),
+        }
+    }
+}
+
+impl Demo {
+    fn auth_message(&self) -> String {
+        // Serializing a struct of strings and numbers can't fail
+        let payload = serde_json::to_string(&self).unwrap_or_default();
+        format!(r#"42["auth",{payload}]"#)
+    }
+}
+
+/// Writes the `auth` message with the session replaced by `REDACTED`
+fn fmt_redacted(f: &mut fmt::Formatter<'_>, is_demo: u32, uid: u32, platform: u32) -> fmt::Result {
+    write!(
+        f,
+        r#"42["auth",{{"session":"{REDACTED}","isDemo":{is_demo},"uid":{uid},"platform":{platform}}}]"#
+    )
 }
+
 impl fmt::Display for Demo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ssid = serde_json::to_string(&self).map_err(|_| fmt::Error)?;
-        write!(f, r#"42["auth",{}]"#, ssid)
+        fmt_redacted(f, self.is_demo, self.uid, self.platform)
     }
 }
 
 impl fmt::Display for Real {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.raw)
+        fmt_redacted(f, self.is_demo, self.uid, self.platform)
     }
 }
 
@@ -131,21 +391,76 @@ impl fmt::Display for Ssid {
     }
 }
 
+impl fmt::Debug for SessionData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionData")
+            .field("session_id", &REDACTED)
+            .field("ip_address", &self.ip_address)
+            .field("user_agent", &self.user_agent)
+            .field("last_activity", &self.last_activity)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Demo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Demo")
+            .field("session", &REDACTED)
+            .field("is_demo", &self.is_demo)
+            .field("uid", &self.uid)
+            .field("platform", &self.platform)
+            .field("user_agent", &self.user_agent)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Real {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Real")
+            .field("session", &self.session)
+            .field("is_demo", &self.is_demo)
+            .field("uid", &self.uid)
+            .field("platform", &self.platform)
+            .field("user_agent", &self.user_agent)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<'de> Deserialize<'de> for Ssid {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let data: Value = Value::deserialize(deserializer)?;
-        Ssid::parse(data).map_err(serde::de::Error::custom)
+        match Value::deserialize(deserializer)? {
+            Value::String(data) => Ssid::parse(data),
+            data => Ssid::parse(data),
+        }
+        .map_err(serde::de::Error::custom)
     }
 }
 
 impl Credentials for Ssid {}
 
+/// Wraps a provider so the `Ssid`s that fail `Ssid::validate` are rejected
+pub(crate) struct ValidatedSsid(pub Arc<dyn CredentialProvider<Ssid>>);
+
+#[async_trait]
+impl CredentialProvider<Ssid> for ValidatedSsid {
+    async fn credentials(&self) -> BinaryOptionsResult<Ssid> {
+        let ssid = self.0.credentials().await?;
+        ssid.validate().map_err(PocketOptionError::from)?;
+        Ok(ssid)
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.0.refresh_interval()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use binary_options_tools_core::utils::redact::redact;
     use std::error::Error;
 
     #[test]
@@ -167,7 +482,7 @@ mod tests {
             r#"42["auth",{"session":"a:4:{s:10:\"session_id\";s:32:\"f10395d38f61039ea0a20ba26222895a\";s:10:\"ip_address\";s:12:\"79.177.168.1\";s:10:\"user_agent\";s:111:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/133.0.0.0 Safari/537.36\";s:13:\"last_activity\";i:1740261136;}9bef184e52d025d1f07068eeaf555637","isDemo":0,"uid":89028022,"platform":2}]"#,
             r#"42["auth",{"session":"a:4:{s:10:\"session_id\";s:32:\"bebb6bb272efc3b8be0e37ae5eb814c6\";s:10:\"ip_address\";s:14:\"191.113.152.39\";s:10:\"user_agent\";s:120:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36 OPR/117.\";s:13:\"last_activity\";i:1742420144;}56b1857cbcf8d66f9bd81900e36803d4","isDemo":0,"uid":87742848,"platform":2}]"#,
             r#"42["auth",{"session":"a:4:{s:10:\"session_id\";s:32:\"f729997775af4ad480d5787c5bc94584\";s:10:\"ip_address\";s:14:\"191.113.152.39\";s:10:\"user_agent\";s:120:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36 OPR/117.\";s:13:\"last_activity\";i:1742422103;}20db11eee2b7f75a5244e9faf5cd4f4a","isDemo":0,"uid":96669015,"platform":2}]    "#,
-            r#"42["auth",{"session":"a:4:{s:10:\"session_id\";s:32:\"256a82f814e5a1ecca6f2c337262b4d6\";s:10:\"ip_address\";s:12:\"89.172.73.91\";s:10:\"user_agent\";s:80:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:136.0) Gecko/20100101 Firefox/136.0\";s:13:\"last_activity\";i:1742422004;}a3e2ef2e4084593ec39d023337564e37","isDemo":0,"uid":96669015,"platform":2}]"#
+            r#"42["auth",{"session":"a:4:{s:10:\"session_id\";s:32:\"256a82f814e5a1ecca6f2c337262b4d6\";s:10:\"ip_address\";s:12:\"89.172.73.91\";s:10:\"user_agent\";s:80:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:136.0) Gecko/20100101 Firefox/136.0\";s:13:\"last_activity\";i:1742422004;}a3e2ef2e4084593ec39d023337564e37","isDemo":0,"uid":96669015,"platform":2}]"#,
         ];
         for ssid in ssids {
             let valid = Ssid::parse(ssid)?;
@@ -175,4 +490,99 @@ mod tests {
         }
         Ok(())
     }
+
+    const SESSION: &str = "a:4:{s:10:\"session_id\";s:32:\"ae3aa847add89c341ec18d8ae5bf8527\";s:10:\"ip_address\";s:15:\"191.113.157.139\";s:10:\"user_agent\";s:120:\"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/128.0.0.0 Safari/537.36 OPR/114.\";s:13:\"last_activity\";i:1732926685;}31666d2dc07fdd866353937b97901e2b";
+
+    #[test]
+    fn test_parse_formats() -> Result<(), Box<dyn Error>> {
+        let object =
+            r#"{"session":"vtftn12e6f5f5008moitsd6skl","isDemo":1,"uid":27658142,"platform":2}"#;
+        let demo = Ssid::parse(object)?;
+        assert!(demo.demo());
+        assert_eq!(demo.uid(), 27658142);
+        assert_eq!(demo.expose_secret(), format!(r#"42["auth",{object}]"#));
+
+        let real =
+            Ssid::parse(serde_json::json!({"session": SESSION, "uid": 87742848}).to_string())?;
+        assert!(!real.demo());
+        real.validate()?;
+
+        let blob = Ssid::parse(SESSION)?;
+        assert!(!blob.demo());
+        assert!(
+            matches!(blob.validate(), Err(SsidError::Validation(problems)) if problems == ["missing uid"])
+        );
+
+        let encoded =
+            percent_encoding::utf8_percent_encode(SESSION, percent_encoding::NON_ALPHANUMERIC);
+        let cookie = Ssid::parse(format!("lang=en; ci_session={encoded}; uid=87742848"))?;
+        assert!(!cookie.demo());
+        assert_eq!(cookie.uid(), 87742848);
+        cookie.validate()?;
+        let auth = Ssid::parse(cookie.expose_secret())?;
+        assert_eq!(auth.expose_secret(), cookie.expose_secret());
+
+        assert!(Ssid::parse("lang=en").is_err());
+        assert!(Ssid::parse(r#"42["login",{"session":"abc"}]"#).is_err());
+        assert!(Ssid::parse("not an ssid").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_redacted_output() -> Result<(), Box<dyn Error>> {
+        let demo = Ssid::parse(
+            r#"42["auth",{"session":"vtftn12e6f5f5008moitsd6skl","isDemo":1,"uid":27658142,"platform":2}]"#,
+        )?;
+        assert_eq!(
+            demo.to_string(),
+            r#"42["auth",{"session":"<redacted>","isDemo":1,"uid":27658142,"platform":2}]"#
+        );
+        assert!(!format!("{demo:?}").contains("vtftn12e6f5f5008moitsd6skl"));
+        assert!(demo.expose_secret().contains("vtftn12e6f5f5008moitsd6skl"));
+
+        let real = Ssid::builder().session(SESSION).uid(87742848).build()?;
+        for output in [real.to_string(), format!("{real:?}")] {
+            assert!(
+                !output.contains("ae3aa847add89c341ec18d8ae5bf8527"),
+                "{output}"
+            );
+        }
+        let logged = redact(&real.expose_secret());
+        assert!(!logged.contains("ae3aa847add89c341ec18d8ae5bf8527"));
+        assert!(!logged.contains("31666d2dc07fdd866353937b97901e2b"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder() -> Result<(), Box<dyn Error>> {
+        let demo = Ssid::builder()
+            .session("vtftn12e6f5f5008moitsd6skl")
+            .uid(27658142)
+            .build()?;
+        assert!(demo.demo());
+        let real = Ssid::builder()
+            .session(SESSION)
+            .uid(87742848)
+            .platform(3)
+            .build()?;
+        assert!(!real.demo());
+        assert!(real.expose_secret().contains(r#""platform":3"#));
+
+        let Err(SsidError::Validation(problems)) =
+            Ssid::builder().session(SESSION).demo(true).build()
+        else {
+            panic!("a demo ssid with a real session should be invalid");
+        };
+        assert_eq!(problems.len(), 2);
+        assert!(Ssid::builder().uid(1).build().is_err());
+        assert!(
+            Ssid::builder()
+                .session(SESSION)
+                .uid(87742848)
+                .user_agent("curl/8.0")
+                .build()
+                .is_err()
+        );
+        Ok(())
+    }
 }
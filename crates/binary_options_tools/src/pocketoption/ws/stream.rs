@@ -2,7 +2,6 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::pocketoption::error::PocketOptionError;
-use binary_options_tools_core::error::BinaryOptionsToolsError;
 use chrono::{DateTime, Utc};
 use tracing::debug;
 // use pin_project_lite::pin_project;
@@ -10,13 +9,13 @@ use crate::pocketoption::{
     error::PocketResult, parser::message::WebSocketMessage, types::update::DataCandle,
 };
 
-use async_channel::{Receiver, RecvError};
+use binary_options_tools_core::general::channel::PolicyReceiver;
 use futures_util::Stream;
 use futures_util::stream::unfold;
 
 #[derive(Clone)]
 pub struct StreamAsset {
-    reciever: Receiver<WebSocketMessage>,
+    reciever: PolicyReceiver<WebSocketMessage>,
     asset: String,
     condition: ConditonnalUpdate,
 }
@@ -117,7 +116,7 @@ impl ConditonnalUpdate {
 }
 
 impl StreamAsset {
-    pub fn new(reciever: Receiver<WebSocketMessage>, asset: String) -> Self {
+    pub fn new(reciever: PolicyReceiver<WebSocketMessage>, asset: String) -> Self {
         Self {
             reciever,
             asset,
@@ -126,7 +125,7 @@ impl StreamAsset {
     }
 
     pub fn new_chuncked(
-        reciever: Receiver<WebSocketMessage>,
+        reciever: PolicyReceiver<WebSocketMessage>,
         asset: String,
         chunk_size: usize,
     ) -> Self {
@@ -137,7 +136,11 @@ impl StreamAsset {
         }
     }
 
-    pub fn new_timed(reciever: Receiver<WebSocketMessage>, asset: String, time: Duration) -> Self {
+    pub fn new_timed(
+        reciever: PolicyReceiver<WebSocketMessage>,
+        asset: String,
+        time: Duration,
+    ) -> Self {
         Self {
             reciever,
             asset,
@@ -147,8 +150,10 @@ impl StreamAsset {
 
     pub async fn recieve(&self) -> PocketResult<DataCandle> {
         let mut condition = self.condition.clone();
-        
-        while let Ok(msg) = self.reciever.recv().await {
+
+        // Dropped updates are reported as `BinaryOptionsToolsError::ChannelLagged`, the stream keeps working afterwards
+        loop {
+            let msg = self.reciever.recv().await?;
             debug!(target: "StreamAsset", "Received UpdateStream!");
             if let WebSocketMessage::UpdateStream(stream) = msg {
                 if let Some(candle) = stream.0.first().take_if(|x| x.active == self.asset) {
@@ -159,10 +164,11 @@ impl StreamAsset {
                 }
             }
         }
+    }
 
-        Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-            RecvError,
-        ).into())
+    /// Number of market data updates dropped since the last read
+    pub fn lagged(&self) -> u64 {
+        self.reciever.lagged()
     }
 
     // pub async fn _recieve(&self) -> PocketResult<DataCandle> {
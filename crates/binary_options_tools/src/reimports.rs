@@ -1,5 +1,14 @@
 pub use binary_options_tools_core::general::traits::ValidatorTrait;
 pub use binary_options_tools_core::general::stream::FilteredRecieverStream;
 pub use binary_options_tools_core::general::config::ConfigBuilder;
+pub use binary_options_tools_core::general::connection::{ConnectionConfig, Proxy, ProxyAuth};
+pub use binary_options_tools_core::general::credentials::{
+    CredentialProvider, CredentialStore, EnvCredentials, FileCredentials, StaticCredentials,
+};
+pub use binary_options_tools_core::general::tls::TlsConfig;
+pub use binary_options_tools_core::general::hooks::{LifecycleEvent, LifecycleHook};
+pub use binary_options_tools_core::general::ready::ConnectionState;
+pub use binary_options_tools_core::general::send::SenderMessage;
+pub use binary_options_tools_core::general::types::Data;
 
 pub use binary_options_tools_macros::Config;
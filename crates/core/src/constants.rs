@@ -5,3 +5,7 @@ pub const RECONNECT_CALLBACK: u64 = 5;
 pub const TIMEOUT_TIME: u64 = 16;
 pub const MAX_ALLOWED_LOOPS: u32 = 8;
 pub const SLEEP_INTERVAL: u64 = 2;
+/// Maximum time to open a connection, in seconds
+pub const CONNECT_TIMEOUT: u64 = 10;
+/// Maximum time a lifecycle hook can run before it's aborted, in seconds
+pub const HOOK_TIMEOUT: u64 = 30;
@@ -40,6 +40,8 @@ pub enum BinaryOptionsToolsError {
     ChannelRequestRecievingError(#[from] async_channel::RecvError),
     #[error("Failed to send message to request channel, {0}")]
     ChannelRequestSendingError(String),
+    #[error("Channel lagged behind, '{0}' messages were dropped")]
+    ChannelLagged(u64),
     #[error("Error recieving response from server, {0}")]
     WebSocketMessageError(String),
     #[error("Failed to parse data: {0}")]
@@ -52,6 +54,24 @@ pub enum BinaryOptionsToolsError {
     TaskJoinError(#[from] tokio::task::JoinError),
     #[error("Failed to execute '{task}' task before the maximum allowed time of '{duration:?}'")]
     TimeoutError { task: String, duration: Duration },
+    #[error(
+        "Connection lost while waiting for the response of '{0}', the outcome of the request is unknown"
+    )]
+    ConnectionLost(String),
+    #[error("Task '{0}' was cancelled")]
+    Cancelled(String),
+    #[error("Lifecycle hook failed, {0}")]
+    HookError(String),
+    #[error("Proxy error, {0}")]
+    ProxyError(String),
+    #[error("TLS error, {0}")]
+    TlsError(String),
+    #[error("Credentials error, {0}")]
+    CredentialsError(String),
+    #[error("Authentication failed, {0}")]
+    AuthenticationFailed(String),
+    #[error("IO error, {0}")]
+    IoError(#[from] std::io::Error),
     #[error("Failed to parse duration, error {0}")]
     ChronoDurationParsingError(#[from] chrono::OutOfRangeError),
     #[error("Unknown error during execution, error {0}")]
@@ -60,6 +80,39 @@ pub enum BinaryOptionsToolsError {
 
 pub type BinaryOptionsResult<T> = Result<T, BinaryOptionsToolsError>;
 
+/// Error of the proxy helpers, converted to `BinaryOptionsToolsError::ProxyError`
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct ProxyError(pub String);
+
+/// Error of the TLS configuration helpers, converted to `BinaryOptionsToolsError::TlsError`
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct TlsError(pub String);
+
+/// Error of the credential parsing helpers, converted to `BinaryOptionsToolsError::CredentialsError`
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct CredentialsError(pub String);
+
+impl From<ProxyError> for BinaryOptionsToolsError {
+    fn from(value: ProxyError) -> Self {
+        Self::ProxyError(value.0)
+    }
+}
+
+impl From<TlsError> for BinaryOptionsToolsError {
+    fn from(value: TlsError) -> Self {
+        Self::TlsError(value.0)
+    }
+}
+
+impl From<CredentialsError> for BinaryOptionsToolsError {
+    fn from(value: CredentialsError) -> Self {
+        Self::CredentialsError(value.0)
+    }
+}
+
 impl<Transfer> From<Transfer> for BinaryOptionsToolsError
 where
     Transfer: MessageTransfer,
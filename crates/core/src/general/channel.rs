@@ -0,0 +1,266 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use async_channel::{Receiver, Sender, TrySendError, bounded};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    constants::MAX_CHANNEL_CAPACITY,
+    error::{BinaryOptionsResult, BinaryOptionsToolsError},
+};
+
+/// Decides what happens when a message is sent to a channel that is already full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressurePolicy {
+    /// Removes the oldest queued message to make room for the new one
+    #[default]
+    DropOldest,
+    /// Discards the new message and keeps the queued ones
+    DropNewest,
+    /// Waits until the consumer frees a slot, this stalls the producer
+    Block,
+}
+
+/// Capacity and backpressure policy of a single channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    pub capacity: usize,
+    pub policy: BackpressurePolicy,
+}
+
+/// Capacities and policies of every internal channel used by the `WebSocketClient`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelsConfig {
+    /// Capacity of the outgoing message queues (normal and priority)
+    pub sender: usize,
+    /// Channels used to send the responses of pending requests to the callers, `BackpressurePolicy::Block` by default
+    pub requests: ChannelConfig,
+    /// Buffer of every raw message subscription, each raw iterator gets its own
+    pub raw: ChannelConfig,
+    /// Channels used to forward market data to the streams, they are fed by the listener of the connection so with
+    /// `BackpressurePolicy::Block` a stream that isn't read stops every other message too
+    pub stream: ChannelConfig,
+}
+
+/// Sending half of a channel that applies a `BackpressurePolicy` when it's full
+#[derive(Debug)]
+pub struct PolicySender<T> {
+    inner: Sender<T>,
+    policy: BackpressurePolicy,
+    lagged: Arc<AtomicU64>,
+}
+
+/// Receiving half of a channel created with `channel`, reports the messages dropped since the last read
+#[derive(Debug)]
+pub struct PolicyReceiver<T> {
+    inner: Receiver<T>,
+    lagged: Arc<AtomicU64>,
+}
+
+/// Creates a bounded channel that follows the given `ChannelConfig`
+pub fn channel<T>(config: ChannelConfig) -> (PolicySender<T>, PolicyReceiver<T>) {
+    let (s, r) = bounded(config.capacity.max(1));
+    let lagged = Arc::new(AtomicU64::new(0));
+    (
+        PolicySender {
+            inner: s,
+            policy: config.policy,
+            lagged: lagged.clone(),
+        },
+        PolicyReceiver { inner: r, lagged },
+    )
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            capacity: MAX_CHANNEL_CAPACITY,
+            policy: BackpressurePolicy::default(),
+        }
+    }
+}
+
+impl ChannelConfig {
+    pub fn new(capacity: usize, policy: BackpressurePolicy) -> Self {
+        Self { capacity, policy }
+    }
+}
+
+impl ChannelsConfig {
+    pub fn new(
+        sender: usize,
+        requests: ChannelConfig,
+        raw: ChannelConfig,
+        stream: ChannelConfig,
+    ) -> Self {
+        Self {
+            sender,
+            requests,
+            raw,
+            stream,
+        }
+    }
+}
+
+impl<T> PolicySender<T> {
+    /// Sends a message following the channel policy, only `BackpressurePolicy::Block` can wait for the consumer
+    pub async fn send(&self, msg: T) -> BinaryOptionsResult<()> {
+        match self.policy {
+            BackpressurePolicy::Block => self
+                .inner
+                .send(msg)
+                .await
+                .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string())),
+            BackpressurePolicy::DropNewest => match self.inner.try_send(msg) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.lag();
+                    Ok(())
+                }
+                Err(e) => Err(BinaryOptionsToolsError::ChannelRequestSendingError(
+                    e.to_string(),
+                )),
+            },
+            BackpressurePolicy::DropOldest => match self.inner.force_send(msg) {
+                Ok(Some(_)) => {
+                    self.lag();
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(BinaryOptionsToolsError::ChannelRequestSendingError(
+                    e.to_string(),
+                )),
+            },
+        }
+    }
+
+    fn lag(&self) {
+        let lagged = self.lagged.fetch_add(1, Ordering::Relaxed) + 1;
+        warn!(target: "Channel", "Channel full, dropped a message ({lagged} dropped since last read)");
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.inner.receiver_count()
+    }
+
+    pub fn policy(&self) -> BackpressurePolicy {
+        self.policy
+    }
+}
+
+impl<T> PolicyReceiver<T> {
+    /// Recieves the next message, if messages were dropped since the last call it returns `BinaryOptionsToolsError::ChannelLagged` first
+    pub async fn recv(&self) -> BinaryOptionsResult<T> {
+        let lagged = self.lagged.swap(0, Ordering::Relaxed);
+        if lagged > 0 {
+            return Err(BinaryOptionsToolsError::ChannelLagged(lagged));
+        }
+        Ok(self.inner.recv().await?)
+    }
+
+    /// Recieves the next message ignoring any dropped messages
+    pub async fn recv_skip_lag(&self) -> BinaryOptionsResult<T> {
+        self.lagged.store(0, Ordering::Relaxed);
+        Ok(self.inner.recv().await?)
+    }
+
+    /// Number of messages dropped since the last read
+    pub fn lagged(&self) -> u64 {
+        self.lagged.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<T> Clone for PolicySender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            policy: self.policy,
+            lagged: self.lagged.clone(),
+        }
+    }
+}
+
+impl<T> Clone for PolicyReceiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            lagged: self.lagged.clone(),
+        }
+    }
+}
+
+impl<T> From<Receiver<T>> for PolicyReceiver<T> {
+    fn from(inner: Receiver<T>) -> Self {
+        Self {
+            inner,
+            lagged: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl Default for ChannelsConfig {
+    fn default() -> Self {
+        Self {
+            sender: MAX_CHANNEL_CAPACITY,
+            // Responses are never dropped, a waiting request would miss its answer
+            requests: ChannelConfig::new(MAX_CHANNEL_CAPACITY, BackpressurePolicy::Block),
+            raw: ChannelConfig::default(),
+            stream: ChannelConfig::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drop_oldest_reports_lag() -> anyhow::Result<()> {
+        let (s, r) = channel(ChannelConfig::new(2, BackpressurePolicy::DropOldest));
+        for i in 0..5 {
+            s.send(i).await?;
+        }
+        assert!(matches!(
+            r.recv().await,
+            Err(BinaryOptionsToolsError::ChannelLagged(3))
+        ));
+        assert_eq!(r.recv().await?, 3);
+        assert_eq!(r.recv().await?, 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_keeps_queue() -> anyhow::Result<()> {
+        let (s, r) = channel(ChannelConfig::new(2, BackpressurePolicy::DropNewest));
+        for i in 0..5 {
+            s.send(i).await?;
+        }
+        assert_eq!(r.lagged(), 3);
+        assert_eq!(r.recv_skip_lag().await?, 0);
+        assert_eq!(r.recv().await?, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_consumer() -> anyhow::Result<()> {
+        let (s, r) = channel(ChannelConfig::new(1, BackpressurePolicy::Block));
+        s.send(0).await?;
+        let blocked = tokio::time::timeout(std::time::Duration::from_millis(50), s.send(1)).await;
+        assert!(blocked.is_err());
+        assert_eq!(r.recv().await?, 0);
+        assert_eq!(r.lagged(), 0);
+        Ok(())
+    }
+}
@@ -1,28 +1,29 @@
 use std::ops::Deref;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_channel::{Receiver, RecvError};
-use futures_util::future::try_join3;
-use futures_util::stream::{SplitSink, SplitStream, select_all};
+use futures_util::future::try_join4;
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
-use tokio::net::TcpStream;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 
-use crate::constants::MAX_CHANNEL_CAPACITY;
 use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError};
-use crate::general::stream::RecieverStream;
 use crate::general::types::MessageType;
 
 use super::config::Config;
-use super::send::SenderMessage;
+use super::credentials::CredentialStore;
+use super::hooks::LifecycleEvent;
+use super::limiter::{DelayQueue, RateLimiter};
+use super::middleware::Middlewares;
+use super::send::{Outgoing, ReconnectPolicy, SendTicket, SenderMessage, SenderQueues};
 use super::stream::FilteredRecieverStream;
 use super::traits::{
-    Connect, Credentials, DataHandler, InnerConfig, MessageHandler, MessageTransfer, ValidatorTrait, WCallback
+    Connect, Credentials, DataHandler, InnerConfig, MessageHandler, MessageTransfer, Transport,
+    ValidatorTrait, WCallback,
 };
 use super::types::{Callback, Data};
 
@@ -48,7 +49,7 @@ where
     T: DataHandler,
     U: InnerConfig,
 {
-    pub credentials: Creds,
+    pub credentials: CredentialStore<Creds>,
     pub connector: Connector,
     pub handler: Handler,
     pub data: Data<T, Transfer>,
@@ -86,7 +87,7 @@ where
     U: InnerConfig + 'static,
 {
     pub async fn init(
-        credentials: Creds,
+        credentials: impl Into<CredentialStore<Creds>>,
         connector: Connector,
         data: Data<T, Transfer>,
         handler: Handler,
@@ -119,14 +120,15 @@ where
     U: InnerConfig + 'static,
 {
     pub async fn init(
-        credentials: Creds,
+        credentials: impl Into<CredentialStore<Creds>>,
         connector: Connector,
         data: Data<T, Transfer>,
         handler: Handler,
         reconnect_callback: Option<Callback<T, Transfer, U>>,
         config: Config<T, Transfer, U>,
     ) -> BinaryOptionsResult<Self> {
-        let _connection = connector.connect(credentials.clone(), &config).await?; // Check if it's possible to connect before building the struct
+        let credentials = credentials.into();
+        let _connection = connector.connect(credentials.load(), &config).await?; // Check if it's possible to connect before building the struct
         let (_event_loop, sender) = Self::start_loops(
             handler.clone(),
             credentials.clone(),
@@ -137,7 +139,19 @@ where
         )
         .await?;
         info!("Started WebSocketClient");
-        sleep(config.get_connection_initialization_timeout()?).await;
+        let max_wait = config.get_connection_initialization_timeout();
+        if let Ok(Err(reason)) =
+            tokio::time::timeout(max_wait, data.readiness().wait_authenticated()).await
+        {
+            _event_loop.abort();
+            return Err(BinaryOptionsToolsError::AuthenticationFailed(reason));
+        }
+        if !data.readiness().is_ready() {
+            warn!(
+                "Connection not ready after {max_wait:?}, still missing: {:?}",
+                data.readiness().missing()
+            );
+        }
         Ok(Self {
             credentials,
             connector,
@@ -152,22 +166,26 @@ where
 
     async fn start_loops(
         handler: Handler,
-        credentials: Creds,
+        credentials: CredentialStore<Creds>,
         data: Data<T, Transfer>,
         connector: Connector,
         reconnect_callback: Option<Callback<T, Transfer, U>>,
         config: Config<T, Transfer, U>,
     ) -> BinaryOptionsResult<(JoinHandle<BinaryOptionsResult<()>>, SenderMessage)> {
         let (mut write, mut read) = connector
-            .connect(credentials.clone(), &config)
+            .connect(credentials.load(), &config)
             .await?
             .split();
-        let (sender, (reciever, reciever_priority)) = SenderMessage::new(MAX_CHANNEL_CAPACITY);
+        let (sender, queues) =
+            SenderMessage::new(config.get_channels().sender, config.get_rate_limits());
         let loop_sender = sender.clone();
         let task = tokio::task::spawn(async move {
             let previous: Option<<Transfer as MessageTransfer>::Info> = None;
-            let loops = 0;
+            // Failed attempts in a row, only a connection that gets ready resets it
+            let mut loops = 0;
             let mut reconnected = false;
+            // Messages waiting for the rate limiter or that couldn't be written, kept across connections
+            let mut delayed = DelayQueue::default();
             loop {
                 match WebSocketInnerClient::<Transfer, Handler, Connector, Creds, T, U>::step(
                     &previous,
@@ -176,14 +194,14 @@ where
                     &loop_sender,
                     &mut read,
                     &mut write,
-                    &reciever,
-                    &reciever_priority,
+                    &queues,
+                    &mut delayed,
                     &config,
                     &reconnect_callback,
                     reconnected,
                     &connector,
                     &credentials,
-                    loops,
+                    &mut loops,
                 )
                 .await
                 {
@@ -193,9 +211,14 @@ where
                         reconnected = true;
                     }
                     Err(e) => {
+                        config
+                            .load_hooks()
+                            .emit(LifecycleEvent::Close, &data, &loop_sender);
                         if let BinaryOptionsToolsError::MaxReconnectAttemptsReached(_) = e {
                             panic!("Error: {}", e);
                         }
+                        error!("Stopped the event loop, {e}");
+                        return Err(e);
                     }
                 }
             }
@@ -203,23 +226,31 @@ where
         Ok((task, sender))
     }
 
+    /// Runs the event loops on one connection until it's lost and opens the next one, connections that never get ready
+    /// and failed connection attempts count towards `max_allowed_loops`.
+    /// Fails with `AuthenticationFailed` when the credentials are rejected and the provider has no new ones
     #[allow(clippy::too_many_arguments)]
     async fn step(
         previous: &Option<<<Handler as MessageHandler>::Transfer as MessageTransfer>::Info>,
         data: &Data<T, Transfer>,
         handler: Handler,
         loop_sender: &SenderMessage,
-        read: &mut SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-        write: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-        reciever: &Receiver<Message>,
-        reciever_priority: &Receiver<Message>,
+        read: &mut SplitStream<Connector::Stream>,
+        write: &mut SplitSink<Connector::Stream, Message>,
+        queues: &SenderQueues,
+        delayed: &mut DelayQueue<Outgoing>,
         config: &Config<T, Transfer, U>,
         reconnect_callback: &Option<Callback<T, Transfer, U>>,
         reconnected: bool,
         connector: &Connector,
-        credentials: &Creds,
-        mut loops: u32,
-    ) -> BinaryOptionsResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+        credentials: &CredentialStore<Creds>,
+        loops: &mut u32,
+    ) -> BinaryOptionsResult<Connector::Stream> {
+        let connected_with = credentials.current();
+        data.readiness().reset(config.get_bootstrap());
+        let hooks = config.load_hooks();
+        hooks.emit(LifecycleEvent::Connect, data, loop_sender);
+        let middlewares = config.load_middlewares();
         let listener_future =
             WebSocketInnerClient::<Transfer, Handler, Connector, Creds, T, U>::listener_loop(
                 previous.clone(),
@@ -227,13 +258,18 @@ where
                 handler.clone(),
                 loop_sender,
                 read,
+                &middlewares,
             );
         let sender_future =
             WebSocketInnerClient::<Transfer, Handler, Connector, Creds, T, U>::sender_loop(
                 write,
-                reciever,
-                reciever_priority,
-                config.get_reconnect_time()?,
+                queues,
+                delayed,
+                loop_sender.limiter(),
+                data,
+                config.get_connection_initialization_timeout(),
+                config.get_channels().sender,
+                &middlewares,
             );
 
         let callback =
@@ -242,55 +278,87 @@ where
                 data.clone(),
                 loop_sender.clone(),
                 reconnected,
-                config.get_reconnect_time()?,
+                config.get_reconnect_time(),
                 config.clone(),
             );
 
-        match try_join3(listener_future, sender_future, callback).await {
-            Ok(_) => {
-                if let Ok(websocket) = connector.connect(credentials.clone(), config).await {
-                    return Ok(websocket);
-                } else {
-                    loops += 1;
-                    let sleep_interval = config.get_sleep_interval()?;
-                    let max_loops = config.get_max_allowed_loops()?;
-                    warn!(
-                        "Error reconnecting... trying again in {sleep_interval} seconds (try {loops} of {max_loops}"
-                    );
-                    sleep(Duration::from_secs(config.get_sleep_interval()?)).await;
-                    if loops >= max_loops {
-                        return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
-                            max_loops,
-                        ));
-                    }
-                }
-            }
+        let refresh =
+            WebSocketInnerClient::<Transfer, Handler, Connector, Creds, T, U>::refresh_credentials(
+                credentials,
+                loop_sender,
+            );
+
+        let res = try_join4(listener_future, sender_future, callback, refresh).await;
+        data.readiness().disconnected();
+        let reason = match &res {
+            Ok(_) => "Connection closed".to_string(),
             Err(e) => {
                 warn!("Error in event loop, {e}, reconnecting...");
-                // println!("Reconnecting...");
-                if let Ok(websocket) = connector.connect(credentials.clone(), config).await {
-                    return Ok(websocket);
-                } else {
-                    loops += 1;
-                    let sleep_interval = config.get_sleep_interval()?;
-                    let max_loops = config.get_max_allowed_loops()?;
-                    warn!(
-                        "Error reconnecting... trying again in {sleep_interval} seconds (try {loops} of {max_loops}"
-                    );
-                    sleep(Duration::from_secs(config.get_sleep_interval()?)).await;
-                    if loops >= max_loops {
+                let error = LifecycleEvent::Error {
+                    error: e.to_string(),
+                };
+                hooks.emit(error, data, loop_sender);
+                e.to_string()
+            }
+        };
+        hooks.emit(LifecycleEvent::Disconnect { reason }, data, loop_sender);
+        let max_loops = config.get_max_allowed_loops();
+        if data.readiness().is_ready() {
+            *loops = 0;
+        } else {
+            let rejected = matches!(res, Err(BinaryOptionsToolsError::AuthenticationFailed(_)));
+            if rejected {
+                credentials.rollback();
+            }
+            // The session may have expired, the provider gets a chance to replace it before reconnecting.
+            // Credentials replaced while the connection was open, like a rollback, count as new ones too
+            let refreshed = !Arc::ptr_eq(&connected_with, &credentials.current())
+                || credentials.try_refresh().await;
+            if let Err(BinaryOptionsToolsError::AuthenticationFailed(reason)) = res
+                && !refreshed
+            {
+                warn!(
+                    "The credentials were rejected and there are no new ones, stopping the client"
+                );
+                return Err(BinaryOptionsToolsError::AuthenticationFailed(reason));
+            }
+            *loops += 1;
+            if *loops >= max_loops {
+                return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
+                    max_loops,
+                ));
+            }
+            // New credentials are tried right away unless the server is rejecting the connections
+            if rejected || !refreshed {
+                let reconnect_time = config.get_reconnect_time();
+                warn!(
+                    "Connection closed before getting ready, trying again in {reconnect_time} seconds (try {loops} of {max_loops})"
+                );
+                sleep(Duration::from_secs(reconnect_time)).await;
+            }
+        }
+        loop {
+            match connector.connect(credentials.load(), config).await {
+                Ok(websocket) => return Ok(websocket),
+                Err(e) => {
+                    *loops += 1;
+                    let error = LifecycleEvent::Error {
+                        error: format!("Failed to reconnect, {e}"),
+                    };
+                    hooks.emit(error, data, loop_sender);
+                    if *loops >= max_loops {
                         return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
                             max_loops,
                         ));
                     }
+                    let sleep_interval = config.get_sleep_interval();
+                    warn!(
+                        "Error reconnecting, {e}, trying again in {sleep_interval} seconds (try {loops} of {max_loops})"
+                    );
+                    sleep(Duration::from_secs(sleep_interval)).await;
                 }
             }
         }
-        Err(BinaryOptionsToolsError::ReconnectionAttemptFailure {
-            number: loops,
-            max: config.get_max_allowed_loops()?,
-        })
-        // unreachable!("Please contact @Rick-29 on github.com this error is completely unexpected and should not happen.")
     }
 
     /// Recieves all the messages from the websocket connection and handles it
@@ -299,97 +367,207 @@ where
         data: &Data<T, Transfer>,
         handler: Handler,
         sender: &SenderMessage,
-        ws: &mut SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+        ws: &mut SplitStream<Connector::Stream>,
+        middlewares: &Middlewares<Transfer>,
     ) -> BinaryOptionsResult<()> {
-        while let Some(msg) = &ws.next().await {
+        while let Some(msg) = ws.next().await {
             let msg = msg
-                .as_ref()
                 .inspect_err(|e| warn!("Error recieving websocket message, {e}"))
                 .map_err(|e| {
                     BinaryOptionsToolsError::WebsocketRecievingConnectionError(e.to_string())
                 })?;
-            match handler.process_message(msg, &previous, sender).await {
-                Ok((msg, close)) => {
-                    if close {
-                        info!("Recieved closing frame");
-                        return Err(BinaryOptionsToolsError::WebsocketConnectionClosed(
-                            "Recieved closing frame".into(),
-                        ));
-                    }
-                    if let Some(msg) = msg {
-                        match msg {
-                            MessageType::Info(info) => {
-                                debug!("Recieved info: {}", info);
-                                previous = Some(info);
+            for msg in middlewares.on_recieve(msg).await? {
+                Self::handle_message(&msg, &mut previous, data, &handler, sender, middlewares)
+                    .await?;
+            }
+        }
+        Err(BinaryOptionsToolsError::WebSocketMessageError("Unexpected error encountered while recieving data from websocket connection. Loop terminated unexpectedly".to_string()))
+    }
+
+    async fn handle_message(
+        msg: &Message,
+        previous: &mut Option<<<Handler as MessageHandler>::Transfer as MessageTransfer>::Info>,
+        data: &Data<T, Transfer>,
+        handler: &Handler,
+        sender: &SenderMessage,
+        middlewares: &Middlewares<Transfer>,
+    ) -> BinaryOptionsResult<()> {
+        match handler.process_message(msg, previous, sender).await {
+            Ok((msg, close)) => {
+                if close {
+                    info!("Recieved closing frame");
+                    return Err(BinaryOptionsToolsError::WebsocketConnectionClosed(
+                        "Recieved closing frame".into(),
+                    ));
+                }
+                if let Some(msg) = msg {
+                    match msg {
+                        MessageType::Info(info) => {
+                            debug!("Recieved info: {}", info);
+                            *previous = Some(info);
+                        }
+                        MessageType::Transfer(transfer) => {
+                            debug!("Recieved data of type: {}", transfer.info());
+                            if transfer.is_rate_limited() {
+                                sender.limiter().slow_down();
+                            }
+                            if let Some(reason) = transfer.auth_failure() {
+                                warn!("The server rejected the credentials, {reason}");
+                                data.readiness().auth_failed(&reason);
+                                return Err(BinaryOptionsToolsError::AuthenticationFailed(reason));
                             }
-                            MessageType::Transfer(transfer) => {
-                                debug!("Recieved data of type: {}", transfer.info());
-                                if let Some(senders) = data.update_data(transfer.clone()).await? {
+                            // Middlewares run before the update so dropped, modified or injected messages are the ones stored
+                            for transfer in middlewares.on_update(transfer).await? {
+                                data.update(&transfer).await?;
+                                if let Some(senders) = data.get_sender(&transfer).await {
+                                    // Only channels with `BackpressurePolicy::Block` can stall the listener here
                                     for sender in senders {
-                                        sender.send(transfer.clone()).await.map_err(|e| {
-                                            BinaryOptionsToolsError::ChannelRequestSendingError(
-                                                e.to_string(),
-                                            )
-                                        })?;
+                                        sender.send(transfer.clone()).await?;
                                     }
                                 }
+                                // Marked after the update so the data is available once the client is ready
+                                data.readiness().recieved(&transfer.info());
                             }
-                            MessageType::Raw(raw) => {
-                                debug!("Recieved raw message: {:?}", raw);
-                                data.raw_send(raw).await?;
-                            }
+                        }
+                        MessageType::Raw(raw) => {
+                            debug!("Recieved raw message: {:?}", raw);
+                            data.raw_send(raw).await?;
                         }
                     }
                 }
-                Err(e) => {
-                    debug!("Error processing message, {e}");
-                }
+            }
+            Err(e) => {
+                debug!("Error processing message, {e}");
             }
         }
-        Err(BinaryOptionsToolsError::WebSocketMessageError("Unexpected error encountered while recieving data from websocket connection. Loop terminated unexpectedly".to_string()))
+        Ok(())
     }
 
-    /// Recieves all the messages and sends them to the websocket
+    /// Recieves all the messages and sends them to the websocket, messages held back by the rate limiter never delay the rest.
+    /// `delayed` outlives the connection so the messages waiting in it when the connection breaks are not lost
+    #[allow(clippy::too_many_arguments)]
     async fn sender_loop(
-        ws: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-        reciever: &Receiver<Message>,
-        reciever_priority: &Receiver<Message>,
-        time: u64,
+        ws: &mut SplitSink<Connector::Stream, Message>,
+        queues: &SenderQueues,
+        delayed: &mut DelayQueue<Outgoing>,
+        limiter: &RateLimiter,
+        data: &Data<T, Transfer>,
+        max_wait: Duration,
+        max_delayed: usize,
+        middlewares: &Middlewares<Transfer>,
     ) -> BinaryOptionsResult<()> {
-        async fn priority_mesages(
-            ws: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-            reciever_priority: &Receiver<Message>,
+        async fn write<S: Transport>(
+            ws: &mut SplitSink<S, Message>,
+            frames: Vec<Message>,
         ) -> BinaryOptionsResult<()> {
-            while let Ok(msg) = reciever_priority.recv().await {
+            for msg in frames {
                 ws.send(msg)
                     .await
                     .inspect_err(|e| warn!("Error sending message to websocket, {e}"))?;
-                ws.flush().await?;
-                debug!("Sent message to websocket!");
+            }
+            ws.flush().await?;
+            debug!("Sent message to websocket!");
+            Ok(())
+        }
+
+        async fn send<S: Transport, Transfer: MessageTransfer>(
+            ws: &mut SplitSink<S, Message>,
+            msg: Message,
+            middlewares: &Middlewares<Transfer>,
+        ) -> BinaryOptionsResult<()> {
+            write(ws, middlewares.on_send(msg).await?).await
+        }
+
+        /// Sends a message taken from the queues, if the connection breaks while writing it the whole message
+        /// goes back to `delayed` so it's sent first on the next connection
+        async fn send_queued<S: Transport, Transfer: MessageTransfer>(
+            ws: &mut SplitSink<S, Message>,
+            msg: Outgoing,
+            delayed: &mut DelayQueue<Outgoing>,
+            middlewares: &Middlewares<Transfer>,
+        ) -> BinaryOptionsResult<()> {
+            if msg
+                .ticket
+                .as_ref()
+                .is_some_and(|ticket| !ticket.mark_written())
+            {
+                debug!("Dropping cancelled message '{:?}'", msg.kind);
+                return Ok(());
+            }
+            let frames = middlewares.on_send(msg.message.clone()).await?;
+            write(ws, frames)
+                .await
+                .inspect_err(|_| delayed.retry(msg.kind.clone(), msg, Instant::now()))
+        }
+
+        async fn priority_mesages<S: Transport, Transfer: MessageTransfer>(
+            ws: &mut SplitSink<S, Message>,
+            reciever_priority: &Receiver<Message>,
+            middlewares: &Middlewares<Transfer>,
+        ) -> BinaryOptionsResult<()> {
+            while let Ok(msg) = reciever_priority.recv().await {
+                send(ws, msg, middlewares).await?;
             }
             Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
                 RecvError,
             ))
         }
 
-        tokio::select! {
-            res = priority_mesages(ws, reciever_priority) => res?,
-            _ = sleep(Duration::from_secs(time)) => {}
+        /// Sends `msg` if the limiter allows it, otherwise it waits in `delayed` without blocking the rest of the messages
+        async fn send_or_delay<S: Transport, Transfer: MessageTransfer>(
+            ws: &mut SplitSink<S, Message>,
+            msg: Outgoing,
+            limiter: &RateLimiter,
+            delayed: &mut DelayQueue<Outgoing>,
+            middlewares: &Middlewares<Transfer>,
+        ) -> BinaryOptionsResult<()> {
+            let now = Instant::now();
+            // Messages of a type that is already waiting go behind the others so the order is kept
+            if delayed.is_waiting(&msg.kind) {
+                delayed.push(msg.kind.clone(), msg, now);
+                return Ok(());
+            }
+            match limiter.try_acquire(msg.kind.as_deref(), now) {
+                Some(wait) => {
+                    debug!(
+                        "Rate limit reached for '{:?}', sending it in {wait:?}",
+                        msg.kind
+                    );
+                    delayed.push(msg.kind.clone(), msg, now + wait);
+                    Ok(())
+                }
+                None => send_queued(ws, msg, delayed, middlewares).await,
+            }
         }
-        let stream1 = RecieverStream::new(reciever.to_owned());
-        let stream2 = RecieverStream::new(reciever_priority.to_owned());
-        let mut fused_streams = select_all([stream1.to_stream(), stream2.to_stream()]);
 
-        while let Some(Ok(msg)) = fused_streams.next().await {
-            ws.send(msg)
-                .await
-                .inspect_err(|e| warn!("Error sending message to websocket, {e}"))?;
-            ws.flush().await?;
-            debug!("Sent message to websocket!");
+        // Normal messages stay queued until the connection is ready, priority messages are needed to get there
+        tokio::select! {
+            res = priority_mesages(ws, &queues.priority, middlewares) => res?,
+            _ = Self::wait_ready(data, max_wait) => {}
+        }
+        loop {
+            while let Some((kind, msg)) = delayed.pop_due(Instant::now()) {
+                match limiter.try_acquire(kind.as_deref(), Instant::now()) {
+                    Some(wait) => delayed.retry(kind, msg, Instant::now() + wait),
+                    None => send_queued(ws, msg, delayed, middlewares).await?,
+                }
+            }
+            let next = delayed
+                .next_due()
+                .map(|at| at.saturating_duration_since(Instant::now()));
+            tokio::select! {
+                biased;
+                msg = queues.priority.recv() => send(ws, msg?, middlewares).await?,
+                msg = queues.recv(), if delayed.len() < max_delayed => {
+                    send_or_delay(ws, msg?, limiter, delayed, middlewares).await?;
+                }
+                // Once too many messages are waiting only the high priority ones are taken from the queues
+                msg = queues.high.recv() => {
+                    send_or_delay(ws, msg?, limiter, delayed, middlewares).await?;
+                }
+                _ = sleep(next.unwrap_or_default()), if next.is_some() => {}
+            }
         }
-        Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-            RecvError,
-        ))
     }
 
     // async fn api_loop(
@@ -411,7 +589,16 @@ where
         config: Config<T, Transfer, U>,
     ) -> BinaryOptionsResult<BinaryOptionsResult<()>> {
         Ok(tokio::spawn(async move {
-            sleep(Duration::from_secs(reconnect_time)).await;
+            let max_wait = if reconnect {
+                Duration::from_secs(reconnect_time)
+            } else {
+                config.get_connection_initialization_timeout()
+            };
+            Self::wait_ready(&data, max_wait).await;
+            let hooks = config.load_hooks();
+            if data.readiness().is_ready() {
+                hooks.emit(LifecycleEvent::Authenticated, &data, &sender);
+            }
             if reconnect {
                 if let Some(callback) = &reconnect_callback {
                     callback
@@ -421,22 +608,66 @@ where
                             |e| error!(target: "EventLoop","Error calling callback, {e}"),
                         )?;
                 }
+                hooks.emit(LifecycleEvent::Reconnect, &data, &sender);
             }
             Ok(())
         })
         .await?)
     }
+
+    /// Refreshes the credentials every `refresh_interval` of the provider and closes the connection when they change,
+    /// so the client reconnects with the new ones
+    async fn refresh_credentials(
+        credentials: &CredentialStore<Creds>,
+        sender: &SenderMessage,
+    ) -> BinaryOptionsResult<()> {
+        let Some(interval) = credentials.refresh_interval() else {
+            return std::future::pending().await;
+        };
+        loop {
+            sleep(interval).await;
+            if credentials.try_refresh().await {
+                info!("Credentials changed, reconnecting with the new ones");
+                sender.priority_send(Message::Close(None)).await?;
+            }
+        }
+    }
+
+    /// Waits until the bootstrap messages are recieved or `max_wait` passes
+    async fn wait_ready(data: &Data<T, Transfer>, max_wait: Duration) {
+        if tokio::time::timeout(max_wait, data.readiness().wait())
+            .await
+            .is_err()
+        {
+            warn!(
+                "Connection not ready after {max_wait:?}, still missing: {:?}",
+                data.readiness().missing()
+            );
+        }
+    }
+
     pub async fn send_message(
         &self,
         msg: Transfer,
         response_type: Transfer::Info,
         validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
     ) -> BinaryOptionsResult<Transfer> {
         self.sender
-            .send_message(&self.data, msg, response_type, validator)
+            .send_message(&self.data, msg, response_type, validator, policy)
             .await
     }
 
+    /// Checks if every bootstrap message of the current connection was recieved
+    pub fn is_ready(&self) -> bool {
+        self.data.readiness().is_ready()
+    }
+
+    /// Waits until every bootstrap message of the current connection is recieved
+    pub async fn wait_until_ready(&self) {
+        self.data.readiness().wait().await
+    }
+
     pub async fn raw_send(&self, msg: Transfer::Raw) -> BinaryOptionsResult<()> {
         self.sender.raw_send::<Transfer>(msg).await
     }
@@ -458,9 +689,44 @@ where
         msg: Transfer,
         response_type: Transfer::Info,
         validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
     ) -> BinaryOptionsResult<Transfer> {
         self.sender
-            .send_message_with_timout(timeout, task, &self.data, msg, response_type, validator)
+            .send_message_with_timout(
+                timeout,
+                task,
+                &self.data,
+                msg,
+                response_type,
+                validator,
+                policy,
+            )
+            .await
+    }
+
+    /// Same as `send_message_with_timout`, once it returns `ticket` tells whether the request was written
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_tracked_message_with_timout(
+        &self,
+        timeout: Duration,
+        task: impl ToString,
+        msg: Transfer,
+        response_type: Transfer::Info,
+        validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
+        ticket: SendTicket,
+    ) -> BinaryOptionsResult<Transfer> {
+        self.sender
+            .send_tracked_message_with_timout(
+                timeout,
+                task,
+                &self.data,
+                msg,
+                response_type,
+                validator,
+                policy,
+                Some(ticket),
+            )
             .await
     }
 
@@ -483,6 +749,7 @@ where
         msg: Transfer,
         response_type: Transfer::Info,
         validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
     ) -> BinaryOptionsResult<Transfer> {
         self.sender
             .send_message_with_timeout_and_retry(
@@ -492,6 +759,7 @@ where
                 msg,
                 response_type,
                 validator,
+                policy,
             )
             .await
     }
@@ -547,10 +815,29 @@ mod tests {
         stream::{select_all, unfold},
     };
     use rand::{Rng, distr::Alphanumeric};
-    use tokio::time::sleep;
+    use tokio::time::{Instant, sleep};
     use tracing::info;
 
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use async_trait::async_trait;
+
+    use crate::error::BinaryOptionsResult;
+    use crate::general::{
+        credentials::{CredentialProvider, CredentialStore},
+        hooks::{Hooks, LifecycleHook},
+        limiter::{RateLimit, RateLimitConfig},
+        middleware::Middlewares,
+        ready::ConnectionState,
+        send::ReconnectPolicy,
+        testing::{Rewrite, TestCreds, TestData, TestMessage, WAIT, start, test_config},
+        types::Data,
+    };
     use crate::utils::tracing::start_tracing;
+    use tokio_tungstenite::tungstenite::Message;
 
     struct RecieverStream<T> {
         inner: Receiver<T>,
@@ -666,4 +953,171 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_rate_limited_messages_dont_block_the_rest() -> anyhow::Result<()> {
+        let config = test_config()
+            .rate_limits(RateLimitConfig::default().with_limit("slow", RateLimit::new(1, 0.1)))
+            .build()?;
+        let (client, _server, mut connection) = start(TestCreds("secret".into()), config).await?;
+        client.sender.send(TestMessage::new("slow", "1")).await?;
+        client.sender.send(TestMessage::new("slow", "2")).await?;
+        assert_eq!(connection.recv().await?, TestMessage::new("slow", "1"));
+
+        // The second message has to wait 10 seconds, the rest are sent right away
+        client.sender.send(TestMessage::new("order", "1")).await?;
+        client
+            .sender
+            .priority_send(Message::text("raw:priority"))
+            .await?;
+        let received = tokio::time::timeout(Duration::from_secs(1), async {
+            Ok::<_, anyhow::Error>([connection.recv_text().await?, connection.recv_text().await?])
+        })
+        .await??;
+        assert!(received.contains(&"raw:priority".to_string()));
+        assert!(received.iter().any(|text| text.contains("order")));
+        assert!(
+            connection
+                .recv_text_within(Duration::from_millis(200))
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_middlewares_run_before_the_data_update() -> anyhow::Result<()> {
+        let mut middlewares = Middlewares::new();
+        middlewares.push(Arc::new(Rewrite));
+        let config = test_config().middlewares(middlewares).build()?;
+        let (client, _server, mut connection) = start(TestCreds("secret".into()), config).await?;
+
+        // The request only gets the message injected by the middleware
+        let request = client.send_message_with_timout(
+            WAIT,
+            "Inject",
+            TestMessage::new("inject", "ping"),
+            "extra".into(),
+            Box::new(|_: &TestMessage| true),
+            ReconnectPolicy::Resend,
+        );
+        let server = async {
+            connection.recv().await?;
+            connection.send(TestMessage::new("drop", "a")).await?;
+            connection.send(TestMessage::new("modify", "b")).await?;
+            connection.send(TestMessage::new("inject", "c")).await
+        };
+        let (response, served) = tokio::join!(request, server);
+        served?;
+        assert_eq!(response?, TestMessage::new("extra", ""));
+
+        let updates = client.data.updates();
+        assert!(!updates.iter().any(|m| m.kind == "drop"));
+        assert!(updates.contains(&TestMessage::new("modify", "B")));
+        assert!(!updates.contains(&TestMessage::new("modify", "b")));
+        assert!(updates.contains(&TestMessage::new("inject", "c")));
+        assert!(updates.contains(&TestMessage::new("extra", "")));
+        Ok(())
+    }
+
+    struct HangingDisconnect;
+
+    #[async_trait]
+    impl LifecycleHook<TestData, TestMessage> for HangingDisconnect {
+        async fn on_disconnect(
+            &self,
+            _data: &Data<TestData, TestMessage>,
+            _reason: &str,
+        ) -> BinaryOptionsResult<()> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hanging_hooks_dont_block_the_reconnection() -> anyhow::Result<()> {
+        let mut hooks = Hooks::new();
+        hooks.push(Arc::new(HangingDisconnect));
+        let config = test_config().hooks(hooks).build()?;
+        let (client, server, connection) = start(TestCreds("secret".into()), config).await?;
+        drop(connection);
+        let mut connection = server.accept().await?;
+        connection.send(TestMessage::new("ready", "")).await?;
+        tokio::time::timeout(WAIT, client.data.readiness().wait()).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_connections_that_never_get_ready_are_limited() -> anyhow::Result<()> {
+        let config = test_config().max_allowed_loops(3).build()?;
+        let (_client, server, connection) = start(TestCreds("secret".into()), config).await?;
+        // Losing a connection that was ready doesn't count
+        drop(connection);
+        let mut last = Instant::now();
+        for attempt in 0..3 {
+            drop(server.accept().await?);
+            if attempt > 0 {
+                // Same credentials, so it waits `reconnect_time` before trying again
+                assert!(last.elapsed() >= Duration::from_millis(900));
+            }
+            last = Instant::now();
+        }
+        assert!(server.accept_within(Duration::from_secs(2)).await.is_none());
+        Ok(())
+    }
+
+    struct RotatingCredentials(AtomicU32);
+
+    #[async_trait]
+    impl CredentialProvider<TestCreds> for RotatingCredentials {
+        async fn credentials(&self) -> BinaryOptionsResult<TestCreds> {
+            Ok(TestCreds(format!(
+                "secret-{}",
+                self.0.fetch_add(1, Ordering::SeqCst)
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_credentials_stop_the_client() -> anyhow::Result<()> {
+        let (client, server, connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        drop(connection);
+        let mut connection = server.accept().await?;
+        connection
+            .send(TestMessage::new("not_authorized", "Session expired"))
+            .await?;
+
+        // The same credentials would be rejected again
+        assert!(server.accept_within(Duration::from_secs(2)).await.is_none());
+        assert_eq!(
+            client.data.readiness().state(),
+            ConnectionState::AuthFailed("Session expired".into())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejected_connections_are_limited() -> anyhow::Result<()> {
+        let credentials =
+            CredentialStore::from_provider(Arc::new(RotatingCredentials(AtomicU32::new(0))))
+                .await?;
+        let config = test_config().max_allowed_loops(2).build()?;
+        let (_client, server, connection) = start(credentials, config).await?;
+        drop(connection);
+
+        let mut connection = server.accept().await?;
+        connection
+            .send(TestMessage::new("not_authorized", ""))
+            .await?;
+        let rejected = Instant::now();
+        let mut connection = server.accept().await?;
+        // New credentials are used, after waiting `reconnect_time`
+        assert!(rejected.elapsed() >= Duration::from_millis(900));
+        assert_ne!(connection.creds, TestCreds("secret-0".into()));
+        connection
+            .send(TestMessage::new("not_authorized", ""))
+            .await?;
+        assert!(server.accept_within(Duration::from_secs(2)).await.is_none());
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, time::Duration};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -6,6 +6,11 @@ use url::Url;
 use crate::constants::{MAX_ALLOWED_LOOPS, RECONNECT_CALLBACK, SLEEP_INTERVAL, TIMEOUT_TIME};
 
 use super::{
+    channel::ChannelsConfig,
+    connection::ConnectionConfig,
+    hooks::{Hooks, LifecycleHook},
+    limiter::RateLimitConfig,
+    middleware::{Middleware, Middlewares},
     traits::{DataHandler, InnerConfig, MessageTransfer},
     types::Callback,
 };
@@ -23,6 +28,27 @@ pub struct _Config<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig> {
     pub callbacks: Vec<Callback<T, Transfer, U>>,
     pub connection_initialization_timeout: Duration,
     pub timeout: Duration, // General timeout
+    #[serde(default)]
+    #[config(extra(default))]
+    pub channels: ChannelsConfig,
+    #[serde(default)]
+    #[config(extra(default))]
+    pub rate_limits: RateLimitConfig,
+    /// Proxy, handshake headers and timeouts used to open the websocket connections
+    #[serde(default)]
+    #[config(extra(default))]
+    pub connection: ConnectionConfig,
+    /// Messages that need to be recieved after every (re)connection before the client is considered ready
+    #[serde(default)]
+    #[config(extra(iterator(dtype = "Transfer::Info", add_fn = "insert")))]
+    pub bootstrap: HashSet<Transfer::Info>,
+    #[serde(skip)]
+    #[config(extra(iterator(dtype = "Arc<dyn Middleware<Transfer>>")))]
+    pub middlewares: Middlewares<Transfer>,
+    /// Lifecycle hooks, hooks added after the client started are called from the next event on
+    #[serde(skip)]
+    #[config(extra(iterator(dtype = "Arc<dyn LifecycleHook<T, Transfer>>")))]
+    pub hooks: Hooks<T, Transfer>,
     #[serde(bound = "U: Serialize + for<'d> Deserialize<'d>")]
     pub extra: U,
     // #[serde(skip)]
@@ -39,7 +65,58 @@ impl<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig> _Config<T, Trans
             callbacks,
             timeout: Duration::from_secs(TIMEOUT_TIME),
             connection_initialization_timeout: initialization_timeout,
+            channels: ChannelsConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            connection: ConnectionConfig::default(),
+            bootstrap: HashSet::new(),
+            middlewares: Middlewares::new(),
+            hooks: Hooks::new(),
             extra,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::general::testing::{Rewrite, TestInfo, test_config};
+
+    use super::*;
+
+    #[test]
+    fn test_generated_getters() -> anyhow::Result<()> {
+        let config = test_config().max_allowed_loops(3).build()?;
+        assert_eq!(config.get_max_allowed_loops(), 3);
+        assert_eq!(config.get_reconnect_time(), 1);
+        assert!(config.get_bootstrap().contains(&TestInfo::from("ready")));
+        // Fields without a value in the builder take their default
+        assert_eq!(config.get_channels(), ChannelsConfig::default());
+
+        config.set_max_allowed_loops(5);
+        assert_eq!(config.get_max_allowed_loops(), 5);
+        config.add_bootstrap(TestInfo::from("balance"));
+        assert_eq!(config.get_bootstrap().len(), 2);
+        config.add_middlewares(Arc::new(Rewrite));
+        assert_eq!(config.get_middlewares().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_reads() -> anyhow::Result<()> {
+        let config = test_config().build()?;
+        let bootstrap = config.load_bootstrap();
+        let reconnect_time = config.load_reconnect_time();
+        config.add_bootstrap(TestInfo::from("balance"));
+        config.set_reconnect_time(10);
+        // Snapshots never change, the next load sees the new value
+        assert_eq!(bootstrap.len(), 1);
+        assert_eq!(*reconnect_time, 1);
+        assert_eq!(config.load_bootstrap().len(), 2);
+        assert_eq!(*config.load_reconnect_time(), 10);
+
+        // Clones share the values
+        let clone = config.clone();
+        clone.set_reconnect_time(20);
+        assert_eq!(config.get_reconnect_time(), 20);
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use std::{env, marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
+
+use arc_swap::{ArcSwap, ArcSwapOption};
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError, CredentialsError};
+
+use super::traits::Credentials;
+
+/// Source of the credentials used by the `WebSocketClient`, it's asked for fresh credentials
+/// when a connection never authenticates and every `refresh_interval`
+#[async_trait]
+pub trait CredentialProvider<C: Credentials>: Send + Sync {
+    async fn credentials(&self) -> BinaryOptionsResult<C>;
+
+    /// How often the credentials are refreshed while the connection works, `None` only refreshes them after failures
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Always returns the same credentials
+pub struct StaticCredentials<C> {
+    credentials: C,
+}
+
+/// Reads the credentials from a file every time they are needed, the file can contain the credentials
+/// as json or as the string they are parsed from
+pub struct FileCredentials<C> {
+    path: PathBuf,
+    refresh_interval: Option<Duration>,
+    _credentials: PhantomData<fn() -> C>,
+}
+
+/// Reads the credentials from an environment variable every time they are needed
+pub struct EnvCredentials<C> {
+    variable: String,
+    refresh_interval: Option<Duration>,
+    _credentials: PhantomData<fn() -> C>,
+}
+
+/// Provider and credentials restored when the ones that replaced them are rejected
+type Fallback<C> = (Arc<dyn CredentialProvider<C>>, Arc<C>);
+
+/// Credentials currently used by a client, shared between the client and anything that needs to send them
+#[derive(Clone)]
+pub struct CredentialStore<C> {
+    current: Arc<ArcSwap<C>>,
+    provider: Arc<ArcSwap<Arc<dyn CredentialProvider<C>>>>,
+    fallback: Arc<ArcSwapOption<Fallback<C>>>,
+}
+
+impl<C> StaticCredentials<C> {
+    pub fn new(credentials: C) -> Self {
+        Self { credentials }
+    }
+}
+
+impl<C> FileCredentials<C> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            refresh_interval: None,
+            _credentials: PhantomData,
+        }
+    }
+
+    /// Reads the file again every `interval`, a changed file makes the client reconnect with the new credentials
+    pub fn refresh_every(mut self, interval: Duration) -> Self {
+        self.refresh_interval = Some(interval);
+        self
+    }
+}
+
+impl<C> EnvCredentials<C> {
+    pub fn new(variable: impl ToString) -> Self {
+        Self {
+            variable: variable.to_string(),
+            refresh_interval: None,
+            _credentials: PhantomData,
+        }
+    }
+
+    /// Reads the variable again every `interval`, a changed value makes the client reconnect with the new credentials
+    pub fn refresh_every(mut self, interval: Duration) -> Self {
+        self.refresh_interval = Some(interval);
+        self
+    }
+}
+
+/// Parses `text` as json, if it isn't valid json the credentials are deserialized from the string itself
+fn parse_credentials<C: Credentials>(text: &str, source: &str) -> Result<C, CredentialsError> {
+    let text = text.trim();
+    serde_json::from_str(text)
+        .or_else(|_| serde_json::from_value(Value::String(text.to_string())))
+        .map_err(|e| CredentialsError(format!("Invalid credentials in {source}, {e}")))
+}
+
+#[async_trait]
+impl<C: Credentials> CredentialProvider<C> for StaticCredentials<C> {
+    async fn credentials(&self) -> BinaryOptionsResult<C> {
+        Ok(self.credentials.clone())
+    }
+}
+
+#[async_trait]
+impl<C: Credentials> CredentialProvider<C> for FileCredentials<C> {
+    async fn credentials(&self) -> BinaryOptionsResult<C> {
+        let source = format!("file '{}'", self.path.display());
+        let text = tokio::fs::read_to_string(&self.path).await.map_err(|e| {
+            BinaryOptionsToolsError::CredentialsError(format!("Failed to read {source}, {e}"))
+        })?;
+        Ok(parse_credentials(&text, &source)?)
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.refresh_interval
+    }
+}
+
+#[async_trait]
+impl<C: Credentials> CredentialProvider<C> for EnvCredentials<C> {
+    async fn credentials(&self) -> BinaryOptionsResult<C> {
+        let source = format!("environment variable '{}'", self.variable);
+        let text = env::var(&self.variable).map_err(|e| {
+            BinaryOptionsToolsError::CredentialsError(format!("Failed to read {source}, {e}"))
+        })?;
+        Ok(parse_credentials(&text, &source)?)
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.refresh_interval
+    }
+}
+
+impl<C: Credentials + 'static> CredentialStore<C> {
+    /// Asks `provider` for the first credentials
+    pub async fn from_provider(
+        provider: Arc<dyn CredentialProvider<C>>,
+    ) -> BinaryOptionsResult<Self> {
+        let credentials = provider.credentials().await?;
+        Ok(Self {
+            current: Arc::new(ArcSwap::from_pointee(credentials)),
+            provider: Arc::new(ArcSwap::from_pointee(provider)),
+            fallback: Arc::new(ArcSwapOption::empty()),
+        })
+    }
+
+    /// Replaces the provider and loads its credentials, every clone of the store uses them from now on
+    pub async fn replace(
+        &self,
+        provider: Arc<dyn CredentialProvider<C>>,
+    ) -> BinaryOptionsResult<()> {
+        let credentials = provider.credentials().await?;
+        self.provider.store(Arc::new(provider));
+        self.current.store(Arc::new(credentials));
+        self.fallback.store(None);
+        info!(target: "Credentials", "Replaced the credentials provider");
+        Ok(())
+    }
+
+    /// Same as `replace` but the previous provider is kept until `accept` is called,
+    /// the client goes back to it by itself if the server rejects the new credentials
+    pub async fn replace_until_accepted(
+        &self,
+        provider: Arc<dyn CredentialProvider<C>>,
+    ) -> BinaryOptionsResult<()> {
+        let previous = (self.provider(), self.current.load_full());
+        self.replace(provider).await?;
+        self.fallback.store(Some(Arc::new(previous)));
+        Ok(())
+    }
+
+    /// Forgets the provider kept by `replace_until_accepted`
+    pub fn accept(&self) {
+        self.fallback.store(None);
+    }
+
+    /// Restores the provider kept by `replace_until_accepted`, returns `false` if there is none
+    pub fn rollback(&self) -> bool {
+        let Some(previous) = self.fallback.swap(None) else {
+            return false;
+        };
+        let (provider, credentials) = previous.as_ref().clone();
+        self.provider.store(Arc::new(provider));
+        self.current.store(credentials);
+        warn!(target: "Credentials", "The new credentials were rejected, restored the previous ones");
+        true
+    }
+
+    /// Provider the credentials are currently loaded from
+    pub fn provider(&self) -> Arc<dyn CredentialProvider<C>> {
+        self.provider.load().as_ref().clone()
+    }
+
+    /// Copy of the credentials currently used
+    pub fn load(&self) -> C {
+        self.current.load().as_ref().clone()
+    }
+
+    /// Credentials currently used, they changed if a later call returns another pointer
+    pub(crate) fn current(&self) -> Arc<C> {
+        self.current.load_full()
+    }
+
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        self.provider.load().refresh_interval()
+    }
+
+    /// Asks the provider for fresh credentials, returns `true` if they changed
+    pub async fn refresh(&self) -> BinaryOptionsResult<bool> {
+        let credentials = self.provider().credentials().await?;
+        let changed = serde_json::to_value(&credentials)?
+            != serde_json::to_value(self.current.load().as_ref())?;
+        if changed {
+            info!(target: "Credentials", "Loaded new credentials");
+            self.current.store(Arc::new(credentials));
+        }
+        Ok(changed)
+    }
+
+    /// Same as `refresh` but failures are only logged, the current credentials are kept
+    pub async fn try_refresh(&self) -> bool {
+        self.refresh()
+            .await
+            .inspect_err(|e| warn!(target: "Credentials", "Failed to refresh the credentials, {e}"))
+            .unwrap_or(false)
+    }
+}
+
+impl<C: Credentials + 'static> From<C> for CredentialStore<C> {
+    fn from(credentials: C) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(credentials.clone())),
+            provider: Arc::new(ArcSwap::from_pointee(Arc::new(StaticCredentials::new(
+                credentials,
+            )))),
+            fallback: Arc::new(ArcSwapOption::empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(from = "String")]
+    struct Token(String);
+
+    impl From<String> for Token {
+        fn from(value: String) -> Self {
+            Self(value)
+        }
+    }
+
+    impl Credentials for Token {}
+
+    #[tokio::test]
+    async fn test_file_credentials() -> anyhow::Result<()> {
+        let path = env::temp_dir().join(format!("credentials-test-{}.txt", std::process::id()));
+        tokio::fs::write(&path, "first\n").await?;
+        let provider = FileCredentials::<Token>::new(&path).refresh_every(Duration::from_secs(60));
+        assert_eq!(provider.refresh_interval(), Some(Duration::from_secs(60)));
+        let store = CredentialStore::from_provider(Arc::new(provider)).await?;
+        assert_eq!(store.load(), Token("first".into()));
+        assert!(!store.refresh().await?);
+
+        tokio::fs::write(&path, "\"second\"").await?;
+        assert!(store.refresh().await?);
+        assert_eq!(store.load(), Token("second".into()));
+
+        tokio::fs::remove_file(&path).await?;
+        assert!(!store.try_refresh().await);
+        assert_eq!(store.load(), Token("second".into()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_env_credentials() -> anyhow::Result<()> {
+        let variable = format!("CREDENTIALS_TEST_{}", std::process::id());
+        let provider = EnvCredentials::<Token>::new(&variable);
+        assert!(provider.credentials().await.is_err());
+        // Safety: no other test reads or writes this variable
+        unsafe { env::set_var(&variable, "token") };
+        assert_eq!(provider.credentials().await?, Token("token".into()));
+        unsafe { env::remove_var(&variable) };
+
+        let store = CredentialStore::from(Token("static".into()));
+        assert_eq!(store.refresh_interval(), None);
+        assert!(!store.refresh().await?);
+
+        let clone = store.clone();
+        store
+            .replace(Arc::new(StaticCredentials::new(Token("replaced".into()))))
+            .await?;
+        assert_eq!(clone.load(), Token("replaced".into()));
+        assert!(!clone.refresh().await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rollback_restores_the_replaced_credentials() -> anyhow::Result<()> {
+        let store = CredentialStore::from(Token("first".into()));
+        assert!(!store.rollback());
+
+        store
+            .replace_until_accepted(Arc::new(StaticCredentials::new(Token("second".into()))))
+            .await?;
+        let connected_with = store.current();
+        assert_eq!(store.load(), Token("second".into()));
+        assert!(store.rollback());
+        assert_eq!(store.load(), Token("first".into()));
+        assert!(!store.refresh().await?);
+        assert!(!Arc::ptr_eq(&connected_with, &store.current()));
+        assert!(!store.rollback());
+
+        store
+            .replace_until_accepted(Arc::new(StaticCredentials::new(Token("third".into()))))
+            .await?;
+        store.accept();
+        assert!(!store.rollback());
+        assert_eq!(store.load(), Token("third".into()));
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tracing::{error, warn};
+
+use crate::{constants::HOOK_TIMEOUT, error::BinaryOptionsResult};
+
+use super::{
+    send::SenderMessage,
+    traits::{DataHandler, MessageTransfer},
+    types::Data,
+};
+
+/// Events of the connection lifecycle of the `WebSocketClient`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// A websocket connection was established
+    Connect,
+    /// Every bootstrap message of the connection was recieved
+    Authenticated,
+    /// The connection was lost, the client will try to reconnect
+    Disconnect { reason: String },
+    /// The connection is ready again after a disconnection
+    Reconnect,
+    /// The event loop found an error
+    Error { error: String },
+    /// The event loop stopped for good, no more reconnections will be made
+    Close,
+}
+
+/// Hooks called on every event of the connection lifecycle, every method does nothing by default.
+///
+/// Each hook runs in its own task and the event loop never waits for it, errors, panics and hooks running
+/// for longer than the timeout of `Hooks` are logged and never reach the event loop.
+/// Since nothing waits for them, the hooks of consecutive events may run at the same time.
+#[async_trait]
+pub trait LifecycleHook<T: DataHandler, Transfer: MessageTransfer>: Send + Sync {
+    async fn on_connect(&self, _data: &Data<T, Transfer>) -> BinaryOptionsResult<()> {
+        Ok(())
+    }
+
+    async fn on_authenticated(&self, _data: &Data<T, Transfer>) -> BinaryOptionsResult<()> {
+        Ok(())
+    }
+
+    async fn on_disconnect(
+        &self,
+        _data: &Data<T, Transfer>,
+        _reason: &str,
+    ) -> BinaryOptionsResult<()> {
+        Ok(())
+    }
+
+    async fn on_reconnect(
+        &self,
+        _data: &Data<T, Transfer>,
+        _sender: &SenderMessage,
+    ) -> BinaryOptionsResult<()> {
+        Ok(())
+    }
+
+    async fn on_error(&self, _data: &Data<T, Transfer>, _error: &str) -> BinaryOptionsResult<()> {
+        Ok(())
+    }
+
+    async fn on_close(&self, _data: &Data<T, Transfer>) -> BinaryOptionsResult<()> {
+        Ok(())
+    }
+}
+
+/// Registered lifecycle hooks, they are started in the order they were added and aborted after `HOOK_TIMEOUT` seconds
+pub struct Hooks<T: DataHandler, Transfer: MessageTransfer> {
+    inner: Vec<Arc<dyn LifecycleHook<T, Transfer>>>,
+    timeout: Duration,
+}
+
+impl LifecycleEvent {
+    /// Names of every event, in the order they usually happen
+    pub const NAMES: [&'static str; 6] = [
+        "connect",
+        "authenticated",
+        "disconnect",
+        "reconnect",
+        "error",
+        "close",
+    ];
+
+    /// Name of the event, the same used by the python and javascript bindings to register hooks
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Connect => "connect",
+            Self::Authenticated => "authenticated",
+            Self::Disconnect { .. } => "disconnect",
+            Self::Reconnect => "reconnect",
+            Self::Error { .. } => "error",
+            Self::Close => "close",
+        }
+    }
+
+    /// Reason of the disconnection or error description
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            Self::Disconnect { reason } => Some(reason),
+            Self::Error { error } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LifecycleEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.detail() {
+            Some(detail) => write!(f, "{}: {detail}", self.name()),
+            None => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+impl<T: DataHandler, Transfer: MessageTransfer> Hooks<T, Transfer> {
+    pub fn new() -> Self {
+        Self {
+            inner: Vec::new(),
+            timeout: Duration::from_secs(HOOK_TIMEOUT),
+        }
+    }
+
+    pub fn push(&mut self, hook: Arc<dyn LifecycleHook<T, Transfer>>) {
+        self.inner.push(hook);
+    }
+
+    /// Hooks still running after `timeout` are aborted
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<T, Transfer> Hooks<T, Transfer>
+where
+    T: DataHandler<Transfer = Transfer> + 'static,
+    Transfer: MessageTransfer,
+{
+    /// Starts every hook registered for `event` in its own task and returns right away
+    pub fn emit(&self, event: LifecycleEvent, data: &Data<T, Transfer>, sender: &SenderMessage) {
+        for hook in &self.inner {
+            let (hook, event, data, sender) =
+                (hook.clone(), event.clone(), data.clone(), sender.clone());
+            let name = event.name();
+            let timeout = self.timeout;
+            let mut task = tokio::spawn(async move {
+                match &event {
+                    LifecycleEvent::Connect => hook.on_connect(&data).await,
+                    LifecycleEvent::Authenticated => hook.on_authenticated(&data).await,
+                    LifecycleEvent::Disconnect { reason } => {
+                        hook.on_disconnect(&data, reason).await
+                    }
+                    LifecycleEvent::Reconnect => hook.on_reconnect(&data, &sender).await,
+                    LifecycleEvent::Error { error } => hook.on_error(&data, error).await,
+                    LifecycleEvent::Close => hook.on_close(&data).await,
+                }
+            });
+            tokio::spawn(async move {
+                match tokio::time::timeout(timeout, &mut task).await {
+                    Ok(Ok(Ok(()))) => {}
+                    Ok(Ok(Err(e))) => warn!(target: "Hooks", "Error calling '{name}' hook, {e}"),
+                    Ok(Err(e)) => error!(target: "Hooks", "The '{name}' hook panicked, {e}"),
+                    Err(_) => {
+                        task.abort();
+                        warn!(target: "Hooks", "The '{name}' hook didn't finish in {timeout:?}, aborted it");
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl<T: DataHandler, Transfer: MessageTransfer> Default for Hooks<T, Transfer> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: DataHandler, Transfer: MessageTransfer> Clone for Hooks<T, Transfer> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    use crate::general::{
+        limiter::RateLimitConfig,
+        testing::{TestData, TestMessage, WAIT},
+    };
+
+    /// Never finishes its `on_connect` hook, `aborted` is set once the hook is dropped
+    #[derive(Default)]
+    struct Hanging {
+        aborted: Arc<AtomicBool>,
+    }
+
+    #[derive(Default)]
+    struct Notifying(Arc<Notify>);
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl LifecycleHook<TestData, TestMessage> for Hanging {
+        async fn on_connect(&self, _data: &Data<TestData, TestMessage>) -> BinaryOptionsResult<()> {
+            let _guard = SetOnDrop(self.aborted.clone());
+            std::future::pending().await
+        }
+    }
+
+    #[async_trait]
+    impl LifecycleHook<TestData, TestMessage> for Notifying {
+        async fn on_connect(&self, _data: &Data<TestData, TestMessage>) -> BinaryOptionsResult<()> {
+            self.0.notify_one();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hanging_hooks_are_aborted() -> anyhow::Result<()> {
+        let hanging = Arc::new(Hanging::default());
+        let notifying = Arc::new(Notifying::default());
+        let mut hooks = Hooks::new();
+        hooks.set_timeout(Duration::from_millis(200));
+        hooks.push(hanging.clone());
+        hooks.push(notifying.clone());
+
+        let data = Data::new(TestData::default());
+        let (sender, _queues) = SenderMessage::new(8, RateLimitConfig::default());
+        hooks.emit(LifecycleEvent::Connect, &data, &sender);
+        // The hook added after the hanging one still runs
+        tokio::time::timeout(WAIT, notifying.0.notified()).await?;
+        assert!(!hanging.aborted.load(Ordering::SeqCst));
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(hanging.aborted.load(Ordering::SeqCst));
+        Ok(())
+    }
+}
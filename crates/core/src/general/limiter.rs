@@ -0,0 +1,322 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+/// Order in which the queued messages are sent, messages sent with `SenderMessage::priority_send` always go first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessagePriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+/// Token bucket limit, allows bursts of up to `burst` messages and refills `per_second` messages every second
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_second: f64,
+}
+
+/// Outgoing rate limits of the `WebSocketClient`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Limits by message type, the key is the message information formatted with `Display`
+    pub limits: HashMap<String, RateLimit>,
+    /// Minimum time between messages after the server complains about the rate, it doubles with every new complaint
+    pub slow_down: Duration,
+    pub max_slow_down: Duration,
+    /// Time without complaints needed to go back to the normal rate
+    pub cool_down: Duration,
+}
+
+/// Shared rate limiter used by the sender loop
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    state: Arc<Mutex<LimiterState>>,
+}
+
+/// Messages held back by the rate limiter, grouped by type so a limited type never delays the others
+#[derive(Debug)]
+pub struct DelayQueue<M> {
+    waiting: HashMap<Option<String>, (Instant, VecDeque<M>)>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    buckets: HashMap<String, Bucket>,
+    slow_down: Option<Duration>,
+    last_complaint: Option<Instant>,
+    last_sent: Option<Instant>,
+}
+
+impl RateLimit {
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        Self { burst, per_second }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            limits: HashMap::new(),
+            slow_down: Duration::from_millis(250),
+            max_slow_down: Duration::from_secs(5),
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn with_limit(mut self, kind: impl ToString, limit: RateLimit) -> Self {
+        self.limits.insert(kind.to_string(), limit);
+        self
+    }
+}
+
+impl<M> Default for DelayQueue<M> {
+    fn default() -> Self {
+        Self {
+            waiting: HashMap::new(),
+        }
+    }
+}
+
+impl<M> DelayQueue<M> {
+    /// Holds `msg` until `at`, messages of a type that is already waiting keep their order and its time
+    pub fn push(&mut self, kind: Option<String>, msg: M, at: Instant) {
+        self.waiting
+            .entry(kind)
+            .or_insert_with(|| (at, VecDeque::new()))
+            .1
+            .push_back(msg);
+    }
+
+    /// Puts back a message taken with `pop_due` that still can't be sent, it goes first again once `at` passes
+    pub fn retry(&mut self, kind: Option<String>, msg: M, at: Instant) {
+        let (ready_at, queue) = self
+            .waiting
+            .entry(kind)
+            .or_insert_with(|| (at, VecDeque::new()));
+        *ready_at = at;
+        queue.push_front(msg);
+    }
+
+    /// Takes the oldest message of a type whose time already passed
+    pub fn pop_due(&mut self, now: Instant) -> Option<(Option<String>, M)> {
+        let kind = self
+            .waiting
+            .iter()
+            .filter(|(_, (at, _))| *at <= now)
+            .min_by_key(|(_, (at, _))| *at)
+            .map(|(kind, _)| kind.clone())?;
+        let (_, queue) = self.waiting.get_mut(&kind)?;
+        let msg = queue.pop_front()?;
+        if queue.is_empty() {
+            self.waiting.remove(&kind);
+        }
+        Some((kind, msg))
+    }
+
+    /// Checks if messages of type `kind` are waiting, new ones have to wait behind them
+    pub fn is_waiting(&self, kind: &Option<String>) -> bool {
+        self.waiting.contains_key(kind)
+    }
+
+    /// Time when the next type can be sent again
+    pub fn next_due(&self) -> Option<Instant> {
+        self.waiting.values().map(|(at, _)| *at).min()
+    }
+
+    pub fn len(&self) -> usize {
+        self.waiting.values().map(|(_, queue)| queue.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.waiting.is_empty()
+    }
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            updated: now,
+        }
+    }
+
+    /// Takes a token if there is one available, otherwise returns how long to wait for the next one
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        if self.limit.per_second <= 0.0 {
+            return None;
+        }
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst.max(1) as f64);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.limit.per_second,
+            ))
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            state: Arc::new(Mutex::new(LimiterState::default())),
+        }
+    }
+
+    /// Waits until a message of type `kind` can be sent, messages without type are only affected by the slow down
+    pub async fn acquire(&self, kind: Option<&str>) {
+        while let Some(wait) = self.try_acquire(kind, Instant::now()) {
+            sleep(wait).await;
+        }
+    }
+
+    /// Slows down every outgoing message, called when the server answers with a rate limit failure
+    pub fn slow_down(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let slow_down = match state.slow_down {
+            Some(current) => (current * 2).min(self.config.max_slow_down),
+            None => self.config.slow_down,
+        };
+        warn!(target: "RateLimiter", "Server is rate limiting the connection, sending at most one message every {slow_down:?}");
+        state.slow_down = Some(slow_down);
+        state.last_complaint = Some(Instant::now());
+    }
+
+    pub fn is_slowed_down(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .slow_down
+            .is_some()
+    }
+
+    /// Takes the permission to send a message of type `kind` at `now` without waiting, otherwise returns how long to wait
+    pub fn try_acquire(&self, kind: Option<&str>, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state
+            .last_complaint
+            .is_some_and(|last| now.saturating_duration_since(last) >= self.config.cool_down)
+        {
+            info!(target: "RateLimiter", "Going back to the normal sending rate");
+            state.slow_down = None;
+            state.last_complaint = None;
+        }
+        if let (Some(slow_down), Some(last_sent)) = (state.slow_down, state.last_sent) {
+            let next = last_sent + slow_down;
+            if next > now {
+                return Some(next - now);
+            }
+        }
+        if let Some(limit) = kind.and_then(|k| self.config.limits.get(k).map(|l| (k, l))) {
+            let (kind, limit) = limit;
+            let wait = state
+                .buckets
+                .entry(kind.to_string())
+                .or_insert_with(|| Bucket::new(*limit, now))
+                .take(now);
+            if wait.is_some() {
+                return wait;
+            }
+        }
+        state.last_sent = Some(now);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig::default().with_limit("changeSymbol", RateLimit::new(2, 1.0)),
+        );
+        let now = Instant::now();
+        assert!(limiter.try_acquire(Some("changeSymbol"), now).is_none());
+        assert!(limiter.try_acquire(Some("changeSymbol"), now).is_none());
+        let wait = limiter.try_acquire(Some("changeSymbol"), now);
+        assert_eq!(wait, Some(Duration::from_secs(1)));
+        // Other types are not limited
+        assert!(limiter.try_acquire(Some("openOrder"), now).is_none());
+        assert!(limiter.try_acquire(None, now).is_none());
+        assert!(
+            limiter
+                .try_acquire(Some("changeSymbol"), now + Duration::from_secs(1))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_slow_down() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let now = Instant::now();
+        assert!(limiter.try_acquire(None, now).is_none());
+        limiter.slow_down();
+        limiter.slow_down();
+        assert!(limiter.is_slowed_down());
+        assert_eq!(
+            limiter.try_acquire(None, now),
+            Some(Duration::from_millis(500))
+        );
+        let later = now + Duration::from_secs(60);
+        assert!(limiter.try_acquire(None, later).is_none());
+        assert!(!limiter.is_slowed_down());
+    }
+
+    #[test]
+    fn test_delay_queue() {
+        let mut queue = DelayQueue::default();
+        let now = Instant::now();
+        let slow = Some("changeSymbol".to_string());
+        queue.push(slow.clone(), 1, now + Duration::from_secs(1));
+        queue.push(slow.clone(), 2, now);
+        queue.push(None, 3, now);
+        assert!(queue.is_waiting(&slow));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.next_due(), Some(now));
+
+        // The second message waits behind the first one of its type
+        assert_eq!(queue.pop_due(now), Some((None, 3)));
+        assert_eq!(queue.pop_due(now), None);
+        let later = now + Duration::from_secs(1);
+        assert_eq!(queue.pop_due(later), Some((slow.clone(), 1)));
+        queue.retry(slow.clone(), 1, later + Duration::from_secs(1));
+        assert_eq!(queue.pop_due(later), None);
+        assert_eq!(
+            queue.pop_due(later + Duration::from_secs(1)),
+            Some((slow.clone(), 1))
+        );
+        assert_eq!(
+            queue.pop_due(later + Duration::from_secs(1)),
+            Some((slow, 2))
+        );
+        assert!(queue.is_empty());
+    }
+}
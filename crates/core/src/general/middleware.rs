@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::error::BinaryOptionsResult;
+
+use super::traits::MessageTransfer;
+
+/// Allows users to inspect, modify, drop or inject messages flowing through the `WebSocketClient`.
+///
+/// Every hook recieves one message and returns the messages that continue through the client,
+/// an empty vector drops the message and extra items are injected right after it.
+/// Returning an error closes the connection and triggers a reconnection.
+#[async_trait]
+pub trait Middleware<Transfer: MessageTransfer>: Send + Sync {
+    /// Called with every frame recieved from the websocket before `MessageHandler::process_message`
+    async fn on_recieve(&self, message: Message) -> BinaryOptionsResult<Vec<Message>> {
+        Ok(vec![message])
+    }
+
+    /// Called with every parsed message before `DataHandler::update`, only the returned messages update the data,
+    /// reach the pending requests and mark the client as ready.
+    /// Rate limits and authentication failures are checked on the original message before this hook runs
+    async fn on_update(&self, message: Transfer) -> BinaryOptionsResult<Vec<Transfer>> {
+        Ok(vec![message])
+    }
+
+    /// Called with every frame right before it's sent to the websocket
+    async fn on_send(&self, message: Message) -> BinaryOptionsResult<Vec<Message>> {
+        Ok(vec![message])
+    }
+}
+
+/// Ordered list of middlewares, each hook runs the middlewares in the order they were added
+pub struct Middlewares<Transfer: MessageTransfer> {
+    inner: Vec<Arc<dyn Middleware<Transfer>>>,
+}
+
+impl<Transfer: MessageTransfer> Middlewares<Transfer> {
+    pub fn new() -> Self {
+        Self { inner: Vec::new() }
+    }
+
+    pub fn push(&mut self, middleware: Arc<dyn Middleware<Transfer>>) {
+        self.inner.push(middleware);
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub async fn on_recieve(&self, message: Message) -> BinaryOptionsResult<Vec<Message>> {
+        let mut messages = vec![message];
+        for middleware in &self.inner {
+            messages = Self::apply(messages, |m| middleware.on_recieve(m)).await?;
+        }
+        Ok(messages)
+    }
+
+    pub async fn on_update(&self, message: Transfer) -> BinaryOptionsResult<Vec<Transfer>> {
+        let mut messages = vec![message];
+        for middleware in &self.inner {
+            messages = Self::apply(messages, |m| middleware.on_update(m)).await?;
+        }
+        Ok(messages)
+    }
+
+    pub async fn on_send(&self, message: Message) -> BinaryOptionsResult<Vec<Message>> {
+        let mut messages = vec![message];
+        for middleware in &self.inner {
+            messages = Self::apply(messages, |m| middleware.on_send(m)).await?;
+        }
+        Ok(messages)
+    }
+
+    async fn apply<M, F, Fut>(messages: Vec<M>, hook: F) -> BinaryOptionsResult<Vec<M>>
+    where
+        F: Fn(M) -> Fut,
+        Fut: Future<Output = BinaryOptionsResult<Vec<M>>>,
+    {
+        let mut output = Vec::with_capacity(messages.len());
+        for message in messages {
+            output.extend(hook(message).await?);
+        }
+        Ok(output)
+    }
+}
+
+impl<Transfer: MessageTransfer> Default for Middlewares<Transfer> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Transfer: MessageTransfer> Clone for Middlewares<Transfer> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::general::testing::{Rewrite, TestMessage};
+
+    /// Appends its name to the value so the order the middlewares run in can be checked
+    struct Tag(&'static str);
+
+    #[async_trait]
+    impl Middleware<TestMessage> for Tag {
+        async fn on_update(&self, message: TestMessage) -> BinaryOptionsResult<Vec<TestMessage>> {
+            Ok(vec![TestMessage::new(message.kind, message.value + self.0)])
+        }
+
+        async fn on_send(&self, message: Message) -> BinaryOptionsResult<Vec<Message>> {
+            Ok(vec![Message::text(format!(
+                "{}{}",
+                message.to_text()?,
+                self.0
+            ))])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middlewares_drop_modify_and_inject() -> anyhow::Result<()> {
+        let mut middlewares = Middlewares::new();
+        middlewares.push(Arc::new(Rewrite));
+        assert!(
+            middlewares
+                .on_update(TestMessage::new("drop", "a"))
+                .await?
+                .is_empty()
+        );
+        assert_eq!(
+            middlewares
+                .on_update(TestMessage::new("modify", "a"))
+                .await?,
+            vec![TestMessage::new("modify", "A")]
+        );
+        assert_eq!(
+            middlewares
+                .on_update(TestMessage::new("inject", "a"))
+                .await?,
+            vec![
+                TestMessage::new("inject", "a"),
+                TestMessage::new("extra", "")
+            ]
+        );
+        // Hooks that are not implemented let the message through
+        assert_eq!(
+            middlewares.on_send(Message::text("a")).await?,
+            vec![Message::text("a")]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_middlewares_compose_in_order() -> anyhow::Result<()> {
+        let mut middlewares = Middlewares::new();
+        middlewares.push(Arc::new(Tag("-1")));
+        middlewares.push(Arc::new(Rewrite));
+        middlewares.push(Arc::new(Tag("-2")));
+        assert_eq!(
+            middlewares
+                .on_update(TestMessage::new("modify", "a"))
+                .await?,
+            vec![TestMessage::new("modify", "A-1-2")]
+        );
+        // Injected messages only go through the middlewares added after the one injecting them
+        assert_eq!(
+            middlewares
+                .on_update(TestMessage::new("inject", "a"))
+                .await?,
+            vec![
+                TestMessage::new("inject", "a-1-2"),
+                TestMessage::new("extra", "-2")
+            ]
+        );
+        assert!(
+            middlewares
+                .on_update(TestMessage::new("drop", "a"))
+                .await?
+                .is_empty()
+        );
+        assert_eq!(
+            middlewares.on_send(Message::text("a")).await?,
+            vec![Message::text("a-1-2")]
+        );
+        Ok(())
+    }
+}
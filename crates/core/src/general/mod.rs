@@ -1,8 +1,19 @@
+pub mod channel;
 pub mod client;
 pub mod config;
+pub mod connection;
+pub mod credentials;
+pub mod hooks;
+pub mod limiter;
+pub mod middleware;
+pub mod ready;
 pub mod traits;
 pub mod types;
 
 pub mod send;
 pub mod stream;
+pub mod tls;
 pub mod validate;
+
+#[cfg(test)]
+pub(crate) mod testing;
@@ -0,0 +1,203 @@
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use tokio::sync::watch;
+
+/// Keeps track of the bootstrap messages that still need to arrive before the connection is ready to be used
+#[derive(Clone)]
+pub struct Readiness<Info> {
+    pending: Arc<Mutex<HashSet<Info>>>,
+    ready: Arc<watch::Sender<bool>>,
+    connections: Arc<watch::Sender<u64>>,
+    disconnections: Arc<watch::Sender<u64>>,
+    /// Reason of the last rejected authentication, kept across reconnections until a connection gets ready
+    auth_failure: Arc<watch::Sender<Option<String>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ConnectionState {
+    /// Waiting for the bootstrap messages
+    Connecting,
+    Ready,
+    /// The server rejected the credentials
+    AuthFailed(String),
+}
+
+impl<Info> Default for Readiness<Info> {
+    fn default() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(HashSet::new())),
+            ready: Arc::new(watch::Sender::new(false)),
+            connections: Arc::new(watch::Sender::new(0)),
+            disconnections: Arc::new(watch::Sender::new(0)),
+            auth_failure: Arc::new(watch::Sender::new(None)),
+        }
+    }
+}
+
+impl<Info> Readiness<Info>
+where
+    Info: Clone + Eq + Hash,
+{
+    /// Starts a new connection, it's not ready until every message in `bootstrap` is recieved, an empty set makes it ready right away
+    pub fn reset(&self, bootstrap: HashSet<Info>) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let ready = bootstrap.is_empty();
+        *pending = bootstrap;
+        self.ready.send_replace(ready);
+        self.connections
+            .send_modify(|connections| *connections += 1);
+    }
+
+    /// Notifies every time a new connection is established, the value is the number of connections made so far
+    pub fn connections(&self) -> watch::Receiver<u64> {
+        self.connections.subscribe()
+    }
+
+    /// Registers that the current connection was lost
+    pub fn disconnected(&self) {
+        self.disconnections
+            .send_modify(|disconnections| *disconnections += 1);
+    }
+
+    /// Notifies every time a connection is lost, the value is the number of connections lost so far
+    pub fn disconnections(&self) -> watch::Receiver<u64> {
+        self.disconnections.subscribe()
+    }
+
+    /// Registers that a message of type `info` was recieved
+    pub fn recieved(&self, info: &Info) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.remove(info) && pending.is_empty() {
+            self.ready.send_replace(true);
+            self.auth_failure.send_replace(None);
+        }
+    }
+
+    /// Registers that the server rejected the credentials
+    pub fn auth_failed(&self, reason: impl ToString) {
+        self.auth_failure.send_replace(Some(reason.to_string()));
+    }
+
+    pub fn auth_failure(&self) -> Option<String> {
+        self.auth_failure.borrow().clone()
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        if self.is_ready() {
+            ConnectionState::Ready
+        } else if let Some(reason) = self.auth_failure() {
+            ConnectionState::AuthFailed(reason)
+        } else {
+            ConnectionState::Connecting
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        *self.ready.borrow()
+    }
+
+    /// Bootstrap messages that didn't arrive yet
+    pub fn missing(&self) -> Vec<Info> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Waits until every bootstrap message is recieved
+    pub async fn wait(&self) {
+        let mut reciever = self.ready.subscribe();
+        // The sender lives as long as `self` so this can't fail
+        let _ = reciever.wait_for(|ready| *ready).await;
+    }
+
+    /// Waits until every bootstrap message is recieved or the credentials are rejected, returning the reason of the rejection
+    pub async fn wait_authenticated(&self) -> Result<(), String> {
+        let mut failures = self.auth_failure.subscribe();
+        tokio::select! {
+            _ = self.wait() => Ok(()),
+            // The sender lives as long as `self` so this can't fail
+            Ok(reason) = failures.wait_for(Option::is_some) => Err(reason.clone().unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ready_after_bootstrap() -> anyhow::Result<()> {
+        let readiness = Readiness::default();
+        readiness.reset(HashSet::from(["auth", "balance"]));
+        assert!(!readiness.is_ready());
+
+        let waiter = tokio::spawn({
+            let readiness = readiness.clone();
+            async move { readiness.wait().await }
+        });
+        readiness.recieved(&"auth");
+        readiness.recieved(&"other");
+        assert_eq!(readiness.missing(), vec!["balance"]);
+        readiness.recieved(&"balance");
+        assert!(readiness.is_ready());
+        assert!(timeout(Duration::from_secs(1), waiter).await.is_ok());
+
+        let connections = readiness.connections();
+        readiness.reset(HashSet::from(["auth"]));
+        assert!(!readiness.is_ready());
+        assert!(connections.has_changed()?);
+        assert_eq!(*connections.borrow(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure() -> anyhow::Result<()> {
+        let readiness = Readiness::default();
+        readiness.reset(HashSet::from(["auth"]));
+        assert_eq!(readiness.state(), ConnectionState::Connecting);
+
+        let waiter = tokio::spawn({
+            let readiness = readiness.clone();
+            async move { readiness.wait_authenticated().await }
+        });
+        readiness.auth_failed("NotAuthorized");
+        assert_eq!(
+            timeout(Duration::from_secs(1), waiter).await??,
+            Err("NotAuthorized".to_string())
+        );
+
+        // The failure is kept across reconnections until a connection gets ready
+        readiness.reset(HashSet::from(["auth"]));
+        assert_eq!(
+            readiness.state(),
+            ConnectionState::AuthFailed("NotAuthorized".into())
+        );
+        readiness.recieved(&"auth");
+        assert_eq!(readiness.state(), ConnectionState::Ready);
+        assert_eq!(readiness.auth_failure(), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_bootstrap_is_ready() {
+        let readiness: Readiness<&str> = Readiness::default();
+        readiness.reset(HashSet::new());
+        assert!(
+            timeout(Duration::from_millis(50), readiness.wait())
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -1,6 +1,13 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU8, Ordering},
+    },
+    time::Duration,
+};
 
 use async_channel::{Receiver, RecvError, Sender, bounded};
+use tokio::sync::watch;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::{info, warn};
 
@@ -11,28 +18,142 @@ use crate::{
 };
 
 use super::{
-    stream::FilteredRecieverStream, traits::{DataHandler, MessageTransfer, RawMessage, ValidatorTrait}, types::Data
+    channel::PolicyReceiver,
+    limiter::{MessagePriority, RateLimitConfig, RateLimiter},
+    stream::FilteredRecieverStream,
+    traits::{DataHandler, MessageTransfer, RawMessage, ValidatorTrait},
+    types::{Data, PendingRequest},
 };
 
+/// Decides what happens to a request that is still waiting for its response when the connection is lost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectPolicy<Info> {
+    /// Sends the request again once reconnected, only for requests that are safe to repeat
+    Resend,
+    /// Fails with `BinaryOptionsToolsError::ConnectionLost` as soon as the connection is lost, the request may have been
+    /// processed or may still be sent after reconnecting so its outcome is unknown
+    Fail,
+    /// Doesn't send the request again, the response is also searched in the messages of type `Info` that
+    /// arrive after the connection is lost, using the same validator
+    Reconcile(Info),
+}
+
+/// Message waiting to be sent, `kind` is the message type used by the rate limiter
+#[derive(Debug, Clone)]
+pub struct Outgoing {
+    pub message: Message,
+    pub kind: Option<String>,
+    pub ticket: Option<SendTicket>,
+}
+
+/// Follows a queued message until it's written to the connection, a message cancelled before that is never sent
+#[derive(Debug, Clone, Default)]
+pub struct SendTicket(Arc<AtomicU8>);
+
+/// Recieving side of the queues of a `SenderMessage`, queued messages that couldn't be written because the connection
+/// broke are sent first after reconnecting
+pub struct SenderQueues {
+    pub priority: Receiver<Message>,
+    pub high: Receiver<Outgoing>,
+    pub normal: Receiver<Outgoing>,
+    pub low: Receiver<Outgoing>,
+}
+
 #[derive(Clone)]
 pub struct SenderMessage {
-    sender: Sender<Message>,
     sender_priority: Sender<Message>,
+    sender_high: Sender<Outgoing>,
+    sender: Sender<Outgoing>,
+    sender_low: Sender<Outgoing>,
+    limiter: RateLimiter,
+}
+
+impl SendTicket {
+    const QUEUED: u8 = 0;
+    const WRITTEN: u8 = 1;
+    const CANCELLED: u8 = 2;
+
+    /// Called right before the message is written, false if it was cancelled and must be dropped
+    pub fn mark_written(&self) -> bool {
+        let state = self
+            .0
+            .compare_exchange(
+                Self::QUEUED,
+                Self::WRITTEN,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .unwrap_or_else(|state| state);
+        state != Self::CANCELLED
+    }
+
+    /// Makes sure the message is never written, false if it was already written
+    pub fn cancel(&self) -> bool {
+        let state = self
+            .0
+            .compare_exchange(
+                Self::QUEUED,
+                Self::CANCELLED,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .unwrap_or_else(|state| state);
+        state != Self::WRITTEN
+    }
+}
+
+impl SenderQueues {
+    /// Recieves the next queued message starting with the highest `MessagePriority`, doesn't include `priority` messages
+    pub async fn recv(&self) -> Result<Outgoing, RecvError> {
+        tokio::select! {
+            biased;
+            msg = self.high.recv() => msg,
+            msg = self.normal.recv() => msg,
+            msg = self.low.recv() => msg,
+        }
+    }
 }
 
 impl SenderMessage {
-    pub fn new(cap: usize) -> (Self, (Receiver<Message>, Receiver<Message>)) {
-        let (s, r) = bounded(cap);
+    pub fn new(cap: usize, limits: RateLimitConfig) -> (Self, SenderQueues) {
         let (sp, rp) = bounded(cap);
+        let (sh, rh) = bounded(cap);
+        let (s, r) = bounded(cap);
+        let (sl, rl) = bounded(cap);
 
         (
             Self {
-                sender: s,
                 sender_priority: sp,
+                sender_high: sh,
+                sender: s,
+                sender_low: sl,
+                limiter: RateLimiter::new(limits),
+            },
+            SenderQueues {
+                priority: rp,
+                high: rh,
+                normal: r,
+                low: rl,
             },
-            (r, rp),
         )
     }
+
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    async fn queue(&self, priority: MessagePriority, msg: Outgoing) -> BinaryOptionsResult<()> {
+        let sender = match priority {
+            MessagePriority::High => &self.sender_high,
+            MessagePriority::Normal => &self.sender,
+            MessagePriority::Low => &self.sender_low,
+        };
+        sender
+            .send(msg)
+            .await
+            .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string()))
+    }
+
     // pub fn new(sender: Sender<Transfer>) -> Self {
     //     Self { sender }
     // }
@@ -41,10 +162,11 @@ impl SenderMessage {
         data: &Data<T, Transfer>,
         msg: Transfer,
         response_type: Transfer::Info,
-    ) -> BinaryOptionsResult<Receiver<Transfer>> {
+        ticket: Option<SendTicket>,
+    ) -> BinaryOptionsResult<PendingRequest<Transfer>> {
         let reciever = data.add_request(response_type).await;
 
-        self.send(msg)
+        self.send_with_ticket(msg, ticket)
             .await
             .map_err(|e| BinaryOptionsToolsError::GeneralMessageSendingError(e.to_string()))?;
         Ok(reciever)
@@ -54,8 +176,12 @@ impl SenderMessage {
         &self,
         data: &Data<T, Transfer>,
         msg: Transfer::Raw,
-    ) -> BinaryOptionsResult<Receiver<Transfer::Raw>> {
-        let reciever = data.raw_reciever();
+        filter: Option<Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>>,
+    ) -> BinaryOptionsResult<PolicyReceiver<Transfer::Raw>> {
+        let reciever = match filter {
+            Some(filter) => data.raw_subscribe(filter),
+            None => data.raw_reciever(),
+        };
 
         self.raw_send::<Transfer>(msg)
             .await
@@ -68,17 +194,30 @@ impl SenderMessage {
         &self,
         msg: Transfer::Raw,
     ) -> BinaryOptionsResult<()> {
-        self.sender
-            .send(msg.message())
-            .await
-            .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string()))
+        let msg = Outgoing {
+            message: msg.message(),
+            kind: None,
+            ticket: None,
+        };
+        self.queue(MessagePriority::Normal, msg).await
     }
 
     pub async fn send<Transfer: MessageTransfer>(&self, msg: Transfer) -> BinaryOptionsResult<()> {
-        self.sender
-            .send(msg.into())
-            .await
-            .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string()))
+        self.send_with_ticket(msg, None).await
+    }
+
+    async fn send_with_ticket<Transfer: MessageTransfer>(
+        &self,
+        msg: Transfer,
+        ticket: Option<SendTicket>,
+    ) -> BinaryOptionsResult<()> {
+        let priority = msg.priority();
+        let msg = Outgoing {
+            kind: Some(msg.info().to_string()),
+            message: msg.into(),
+            ticket,
+        };
+        self.queue(priority, msg).await
     }
 
     pub async fn priority_send(&self, msg: Message) -> BinaryOptionsResult<()> {
@@ -89,25 +228,94 @@ impl SenderMessage {
         Ok(())
     }
 
-    pub async fn send_message<Transfer: MessageTransfer, T: DataHandler<Transfer = Transfer>>(
+    /// Waits for the response of a request that was already sent, applying `policy` every time a new connection is established
+    async fn wait_response<Transfer: MessageTransfer, T: DataHandler<Transfer = Transfer>>(
         &self,
         data: &Data<T, Transfer>,
-        msg: Transfer,
-        response_type: Transfer::Info,
-        validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        msg: &Transfer,
+        reciever: &PendingRequest<Transfer>,
+        validator: &(dyn ValidatorTrait<Transfer> + Send + Sync),
+        policy: &ReconnectPolicy<Transfer::Info>,
     ) -> BinaryOptionsResult<Transfer> {
-        let reciever = self.reciever(data, msg, response_type).await?;
+        let mut connections = data.readiness().connections();
+        let mut disconnections = data.readiness().disconnections();
+        // Only the messages of the next connections can tell the outcome of the request,
+        // the ones sent before the connection is lost don't know about it yet
+        let mut reconcile: Option<PendingRequest<Transfer>> = None;
+        loop {
+            let response = tokio::select! {
+                response = reciever.recv_skip_lag() => response?,
+                Some(response) = async {
+                    match &reconcile {
+                        Some(reconcile) => reconcile.recv_skip_lag().await.ok(),
+                        None => None,
+                    }
+                } => response,
+                _ = disconnections.changed(), if *policy != ReconnectPolicy::Resend => {
+                    let ReconnectPolicy::Reconcile(info) = policy else {
+                        return Err(BinaryOptionsToolsError::ConnectionLost(msg.info().to_string()));
+                    };
+                    if reconcile.is_none() {
+                        reconcile = Some(data.add_request(info.clone()).await);
+                    }
+                    continue;
+                }
+                changed = connections.changed() => {
+                    changed.map_err(|e| BinaryOptionsToolsError::GeneralMessageSendingError(e.to_string()))?;
+                    match policy {
+                        ReconnectPolicy::Resend => {
+                            info!("Connection lost, sending '{}' request again", msg.info());
+                            self.send(msg.clone()).await?;
+                        }
+                        ReconnectPolicy::Fail => {
+                            return Err(BinaryOptionsToolsError::ConnectionLost(msg.info().to_string()));
+                        }
+                        ReconnectPolicy::Reconcile(info) => {
+                            info!("Connection lost, waiting for '{info}' to find the response of '{}'", msg.info());
+                        }
+                    }
+                    continue;
+                }
+            };
+            if let Some(response) = validate(validator, response)
+                .inspect_err(|e| warn!("Failed to place trade {e}"))
+                .map_err(|e| BinaryOptionsToolsError::WebSocketMessageError(e.to_string()))?
+            {
+                return Ok(response);
+            }
+        }
+    }
 
-        while let Ok(msg) = reciever.recv().await {
-            if let Some(msg) =
-                validate(&validator, msg).inspect_err(|e| warn!("Failed to place trade {e}"))?
+    /// A `ReconnectPolicy::Reconcile` request that timed out after reconnecting never found its response, so its outcome is unknown
+    fn unreconciled<Info>(
+        error: BinaryOptionsToolsError,
+        policy: &ReconnectPolicy<Info>,
+        connections: &watch::Receiver<u64>,
+    ) -> BinaryOptionsToolsError {
+        match error {
+            BinaryOptionsToolsError::TimeoutError { task, .. }
+                if matches!(policy, ReconnectPolicy::Reconcile(_))
+                    && connections.has_changed().unwrap_or(false) =>
             {
-                return Ok(msg);
+                BinaryOptionsToolsError::ConnectionLost(task)
             }
+            error => error,
         }
-        Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-            RecvError,
-        ))
+    }
+
+    pub async fn send_message<Transfer: MessageTransfer, T: DataHandler<Transfer = Transfer>>(
+        &self,
+        data: &Data<T, Transfer>,
+        msg: Transfer,
+        response_type: Transfer::Info,
+        validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
+    ) -> BinaryOptionsResult<Transfer> {
+        let reciever = self
+            .reciever(data, msg.clone(), response_type, None)
+            .await?;
+        self.wait_response(data, &msg, &reciever, validator.as_ref(), &policy)
+            .await
     }
 
     pub async fn send_raw_message<
@@ -119,9 +327,9 @@ impl SenderMessage {
         msg: Transfer::Raw,
         validator: Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>,
     ) -> BinaryOptionsResult<Transfer::Raw> {
-        let reciever = self.raw_reciever(data, msg).await?;
+        let reciever = self.raw_reciever(data, msg, None).await?;
 
-        while let Ok(msg) = reciever.recv().await {
+        while let Ok(msg) = reciever.recv_skip_lag().await {
             if validator.validate(&msg) {
                 return Ok(msg);
             }
@@ -131,6 +339,7 @@ impl SenderMessage {
         ))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn send_message_with_timout<
         Transfer: MessageTransfer,
         T: DataHandler<Transfer = Transfer>,
@@ -142,26 +351,49 @@ impl SenderMessage {
         msg: Transfer,
         response_type: Transfer::Info,
         validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
+    ) -> BinaryOptionsResult<Transfer> {
+        self.send_tracked_message_with_timout(
+            time,
+            task,
+            data,
+            msg,
+            response_type,
+            validator,
+            policy,
+            None,
+        )
+        .await
+    }
+
+    /// Same as `send_message_with_timout`, once it returns `ticket` tells whether the request was written
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_tracked_message_with_timout<
+        Transfer: MessageTransfer,
+        T: DataHandler<Transfer = Transfer>,
+    >(
+        &self,
+        time: Duration,
+        task: impl ToString,
+        data: &Data<T, Transfer>,
+        msg: Transfer,
+        response_type: Transfer::Info,
+        validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
+        ticket: Option<SendTicket>,
     ) -> BinaryOptionsResult<Transfer> {
-        let reciever = self.reciever(data, msg, response_type).await?;
+        let reciever = self
+            .reciever(data, msg.clone(), response_type, ticket)
+            .await?;
+        let connections = data.readiness().connections();
 
         timeout(
             time,
-            async {
-                while let Ok(msg) = reciever.recv().await {
-                    if let Some(msg) = validate(&validator, msg)
-                        .inspect_err(|e| warn!("Failed to place trade {e}"))?
-                    {
-                        return Ok(msg);
-                    }
-                }
-                Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-                    RecvError,
-                ))
-            },
+            self.wait_response(data, &msg, &reciever, validator.as_ref(), &policy),
             task.to_string(),
         )
         .await
+        .map_err(|e| Self::unreconciled(e, &policy, &connections))
     }
     pub async fn send_raw_message_with_timout<
         Transfer: MessageTransfer,
@@ -174,12 +406,12 @@ impl SenderMessage {
         msg: Transfer::Raw,
         validator: Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>,
     ) -> BinaryOptionsResult<Transfer::Raw> {
-        let reciever = self.raw_reciever(data, msg).await?;
+        let reciever = self.raw_reciever(data, msg, None).await?;
 
         timeout(
             time,
             async {
-                while let Ok(msg) = reciever.recv().await {
+                while let Ok(msg) = reciever.recv_skip_lag().await {
                     if validator.validate(&msg) {
                         return Ok(msg);
                     }
@@ -193,6 +425,7 @@ impl SenderMessage {
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn send_message_with_timeout_and_retry<
         Transfer: MessageTransfer,
         T: DataHandler<Transfer = Transfer>,
@@ -204,47 +437,32 @@ impl SenderMessage {
         msg: Transfer,
         response_type: Transfer::Info,
         validator: Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+        policy: ReconnectPolicy<Transfer::Info>,
     ) -> BinaryOptionsResult<Transfer> {
         let reciever = self
-            .reciever(data, msg.clone(), response_type.clone())
+            .reciever(data, msg.clone(), response_type.clone(), None)
             .await?;
+        let connections = data.readiness().connections();
 
         let call1 = timeout(
             time,
-            async {
-                while let Ok(msg) = reciever.recv().await {
-                    if let Some(msg) = validate(&validator, msg)
-                        .inspect_err(|e| warn!("Failed to place trade {e}"))?
-                    {
-                        return Ok(msg);
-                    }
-                }
-                Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-                    RecvError,
-                ))
-            },
+            self.wait_response(data, &msg, &reciever, validator.as_ref(), &policy),
             task.to_string(),
         )
-        .await;
+        .await
+        .map_err(|e| Self::unreconciled(e, &policy, &connections));
         match call1 {
             Ok(res) => Ok(res),
+            // Retrying would send the request again, which the policy doesn't allow
+            Err(e @ BinaryOptionsToolsError::ConnectionLost(_)) => Err(e),
             Err(_) => {
                 info!("Failded once trying again");
-                let reciever = self.reciever(data, msg, response_type).await?;
+                let reciever = self
+                    .reciever(data, msg.clone(), response_type, None)
+                    .await?;
                 timeout(
                     time,
-                    async {
-                        while let Ok(msg) = reciever.recv().await {
-                            if let Some(msg) = validate(&validator, msg)
-                                .inspect_err(|e| warn!("Failed to place trade {e}"))?
-                            {
-                                return Ok(msg);
-                            }
-                        }
-                        Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-                            RecvError,
-                        ))
-                    },
+                    self.wait_response(data, &msg, &reciever, validator.as_ref(), &policy),
                     task.to_string(),
                 )
                 .await
@@ -263,12 +481,12 @@ impl SenderMessage {
         msg: Transfer::Raw,
         validator: Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>,
     ) -> BinaryOptionsResult<Transfer::Raw> {
-        let reciever = self.raw_reciever(data, msg.clone()).await?;
+        let reciever = self.raw_reciever(data, msg.clone(), None).await?;
 
         let call1 = timeout(
             time,
             async {
-                while let Ok(msg) = reciever.recv().await {
+                while let Ok(msg) = reciever.recv_skip_lag().await {
                     if validator.validate(&msg) {
                         return Ok(msg);
                     }
@@ -284,11 +502,11 @@ impl SenderMessage {
             Ok(res) => Ok(res),
             Err(_) => {
                 info!("Failded once trying again");
-                let reciever = self.raw_reciever(data, msg).await?;
+                let reciever = self.raw_reciever(data, msg, None).await?;
                 timeout(
                     time,
                     async {
-                        while let Ok(msg) = reciever.recv().await {
+                        while let Ok(msg) = reciever.recv_skip_lag().await {
                             if validator.validate(&msg) {
                                 return Ok(msg);
                             }
@@ -304,16 +522,175 @@ impl SenderMessage {
         }
     }
 
-    pub async fn send_raw_message_iterator<        Transfer: MessageTransfer,
-    T: DataHandler<Transfer = Transfer>,
->(        &self,
-    timeout: Option<Duration>,
-    data: &Data<T, Transfer>,
-    msg: Transfer::Raw,
-    validator: Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>,
-) -> BinaryOptionsResult<FilteredRecieverStream<Transfer::Raw>> {
-        let reciever = self.raw_reciever(data, msg).await?;
+    pub async fn send_raw_message_iterator<
+        Transfer: MessageTransfer,
+        T: DataHandler<Transfer = Transfer>,
+    >(
+        &self,
+        timeout: Option<Duration>,
+        data: &Data<T, Transfer>,
+        msg: Transfer::Raw,
+        validator: Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>,
+    ) -> BinaryOptionsResult<FilteredRecieverStream<Transfer::Raw>> {
+        // Every iterator gets its own subscription so concurrent iterators don't steal each other's messages
+        let reciever = self.raw_reciever(data, msg, Some(validator)).await?;
         info!("Created new RawStreamIterator");
-        Ok(FilteredRecieverStream::new(reciever, timeout, validator))
+        Ok(FilteredRecieverStream::new(
+            reciever,
+            timeout,
+            Box::new(|_: &Transfer::Raw| true),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::general::testing::{
+        MemoryConnection, MemoryServer, TestClient, TestCreds, TestInfo, TestMessage, WAIT, start,
+        test_config,
+    };
+
+    use super::*;
+
+    /// Sends an `order` request with `policy` and waits until the server recieves it
+    async fn request(
+        client: &TestClient,
+        connection: &mut MemoryConnection,
+        policy: ReconnectPolicy<TestInfo>,
+    ) -> anyhow::Result<tokio::task::JoinHandle<BinaryOptionsResult<TestMessage>>> {
+        let task = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .send_message_with_timout(
+                        WAIT,
+                        "Order",
+                        TestMessage::new("order", "1"),
+                        "order".into(),
+                        Box::new(|msg: &TestMessage| msg.value == "1"),
+                        policy,
+                    )
+                    .await
+            }
+        });
+        assert_eq!(connection.recv().await?, TestMessage::new("order", "1"));
+        Ok(task)
+    }
+
+    /// Accepts the new connection of the client and makes it ready
+    async fn reconnect(server: &MemoryServer) -> anyhow::Result<MemoryConnection> {
+        let mut connection = server.accept().await?;
+        connection.send(TestMessage::new("ready", "")).await?;
+        Ok(connection)
+    }
+
+    #[tokio::test]
+    async fn test_resend_policy() -> anyhow::Result<()> {
+        let (client, server, mut connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        let task = request(&client, &mut connection, ReconnectPolicy::Resend).await?;
+        drop(connection);
+
+        let mut connection = reconnect(&server).await?;
+        assert_eq!(connection.recv().await?, TestMessage::new("order", "1"));
+        connection.send(TestMessage::new("order", "1")).await?;
+        assert_eq!(task.await??, TestMessage::new("order", "1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fail_policy() -> anyhow::Result<()> {
+        let (client, _server, mut connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        let task = request(&client, &mut connection, ReconnectPolicy::Fail).await?;
+        drop(connection);
+
+        // It fails once the connection is lost, without waiting for the client to reconnect
+        let res = tokio::time::timeout(Duration::from_secs(1), task).await??;
+        assert!(
+            matches!(res, Err(BinaryOptionsToolsError::ConnectionLost(task)) if task == "order")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_policy() -> anyhow::Result<()> {
+        let (client, server, mut connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        let task = request(
+            &client,
+            &mut connection,
+            ReconnectPolicy::Reconcile("opened".into()),
+        )
+        .await?;
+        drop(connection);
+
+        let mut connection = reconnect(&server).await?;
+        connection.send(TestMessage::new("opened", "1")).await?;
+        assert_eq!(task.await??, TestMessage::new("opened", "1"));
+        // The request is never sent again
+        assert!(
+            connection
+                .recv_text_within(Duration::from_millis(200))
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_waits_for_the_connection_to_be_lost() -> anyhow::Result<()> {
+        let (client, _server, mut connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        let task = request(
+            &client,
+            &mut connection,
+            ReconnectPolicy::Reconcile("opened".into()),
+        )
+        .await?;
+        // Sent by the same connection before the request was processed, it can't be the response
+        connection.send(TestMessage::new("opened", "1")).await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!task.is_finished());
+        connection.send(TestMessage::new("order", "1")).await?;
+        assert_eq!(task.await??, TestMessage::new("order", "1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_message_is_never_written() -> anyhow::Result<()> {
+        let (client, server, connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        drop(connection);
+        // Messages wait in the queue until the new connection is ready
+        let mut connection = server.accept().await?;
+        let cancelled = SendTicket::default();
+        let written = SendTicket::default();
+        client
+            .sender
+            .send_with_ticket(TestMessage::new("order", "1"), Some(cancelled.clone()))
+            .await?;
+        client
+            .sender
+            .send_with_ticket(TestMessage::new("order", "2"), Some(written.clone()))
+            .await?;
+        assert!(cancelled.cancel());
+
+        connection.send(TestMessage::new("ready", "")).await?;
+        assert_eq!(connection.recv().await?, TestMessage::new("order", "2"));
+        assert!(!written.cancel());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_message_is_kept_when_the_write_fails() -> anyhow::Result<()> {
+        let (client, server, connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        connection.fail_writes();
+        client.sender.send(TestMessage::new("note", "1")).await?;
+
+        let mut connection = reconnect(&server).await?;
+        assert_eq!(connection.recv().await?, TestMessage::new("note", "1"));
+        Ok(())
     }
 }
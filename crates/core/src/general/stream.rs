@@ -1,11 +1,11 @@
 use std::{sync::Arc, time::Duration};
 
-use async_channel::{Receiver, RecvError};
+use async_channel::Receiver;
 use futures_util::{Stream, stream::unfold};
 
-use crate::{error::{BinaryOptionsResult, BinaryOptionsToolsError}, utils::time::timeout};
+use crate::{error::BinaryOptionsResult, utils::time::timeout};
 
-use super::traits::ValidatorTrait;
+use super::{channel::PolicyReceiver, traits::ValidatorTrait};
 
 pub struct RecieverStream<T> {
     inner: Receiver<T>,
@@ -13,7 +13,7 @@ pub struct RecieverStream<T> {
 }
 
 pub struct FilteredRecieverStream<T> {
-    inner: Receiver<T>,
+    inner: PolicyReceiver<T>,
     timeout: Option<Duration>,
     filter: Box<dyn ValidatorTrait<T> + Send + Sync>
 }
@@ -57,36 +57,49 @@ impl<T> RecieverStream<T> {
     }
 }
 
-
 impl<T> FilteredRecieverStream<T> {
-    pub fn new(inner: Receiver<T>, timeout: Option<Duration>, filter: Box<dyn ValidatorTrait<T> + Send + Sync>) -> Self {
-        Self { inner, timeout, filter }
+    pub fn new(
+        inner: impl Into<PolicyReceiver<T>>,
+        timeout: Option<Duration>,
+        filter: Box<dyn ValidatorTrait<T> + Send + Sync>,
+    ) -> Self {
+        Self {
+            inner: inner.into(),
+            timeout,
+            filter,
+        }
     }
 
-    pub fn new_base(inner: Receiver<T>) -> Self {
+    pub fn new_base(inner: impl Into<PolicyReceiver<T>>) -> Self {
         Self::new(inner, None, default_filter())
     }
 
-    pub fn new_filtered(inner: Receiver<T>, filter: Box<dyn ValidatorTrait<T> + Send + Sync>) -> Self {
+    pub fn new_filtered(
+        inner: impl Into<PolicyReceiver<T>>,
+        filter: Box<dyn ValidatorTrait<T> + Send + Sync>,
+    ) -> Self {
         Self::new(inner, None, filter)
     }
 
+    /// Number of messages dropped since the last read because the stream was not consumed fast enough
+    pub fn lagged(&self) -> u64 {
+        self.inner.lagged()
+    }
 
+    /// Returns the next message that passes the filter, dropped messages are reported with `BinaryOptionsToolsError::ChannelLagged`
     async fn recv(&self) -> BinaryOptionsResult<T> {
-        while let Ok(msg) = self.inner.recv().await {
+        loop {
+            let msg = self.inner.recv().await?;
             if self.filter.validate(&msg) {
                 return Ok(msg);
             }
         }
-        Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-            RecvError,
-        ))
     }
 
     async fn receive(&self) -> BinaryOptionsResult<T> {
         match self.timeout {
             Some(time) => timeout(time, self.recv(), "RecieverStream".to_string()).await,
-            None => Ok(self.inner.recv().await?),
+            None => self.recv().await,
         }
     }
 
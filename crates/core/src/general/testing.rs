@@ -0,0 +1,391 @@
+//! In memory server and messages used to run the `WebSocketClient` in tests without a network connection
+
+use std::{
+    collections::HashSet,
+    fmt, io,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use async_channel::{Receiver, Sender, unbounded};
+use async_trait::async_trait;
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::{DuplexStream, duplex};
+use tokio_tungstenite::{
+    WebSocketStream,
+    tungstenite::{Error as TungsteniteError, Message, protocol::Role},
+};
+
+use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError};
+
+use super::{
+    client::WebSocketClient,
+    config::{_Config, Config, ConfigBuilder},
+    credentials::CredentialStore,
+    limiter::MessagePriority,
+    middleware::Middleware,
+    send::SenderMessage,
+    traits::{
+        Connect, Credentials, DataHandler, InnerConfig, MessageHandler, MessageInformation,
+        MessageTransfer, RawMessage,
+    },
+    types::{Data, MessageType},
+};
+
+/// Time the tests wait for something that should happen right away
+pub const WAIT: Duration = Duration::from_secs(5);
+
+/// Message sent as json text, `kind` is used as the message information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("{kind}: {value}")]
+pub struct TestMessage {
+    pub kind: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TestInfo(pub String);
+
+/// Text messages starting with `raw:`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestRaw(pub String);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCreds(pub String);
+
+#[derive(Clone, Default)]
+pub struct TestHandler;
+
+/// Keeps every message that updated the data
+#[derive(Clone, Default)]
+pub struct TestData {
+    updates: Arc<Mutex<Vec<TestMessage>>>,
+}
+
+/// Middleware that drops `drop` messages, upper cases the value of `modify` ones and adds an `extra` message after `inject` ones
+pub struct Rewrite;
+
+/// Connects the client to a `MemoryServer` through an in memory pipe
+#[derive(Clone)]
+pub struct MemoryConnector {
+    connections: Sender<MemoryConnection>,
+}
+
+/// Recieves every connection opened by the `MemoryConnector`
+pub struct MemoryServer {
+    connections: Receiver<MemoryConnection>,
+}
+
+/// Server side of a connection, dropping it closes the connection
+pub struct MemoryConnection {
+    pub creds: TestCreds,
+    socket: WebSocketStream<DuplexStream>,
+    fail_writes: Arc<AtomicBool>,
+}
+
+/// Client side of a connection, writes can be made to fail from the server side
+pub struct MemorySocket {
+    socket: WebSocketStream<DuplexStream>,
+    fail_writes: Arc<AtomicBool>,
+}
+
+pub type TestClient =
+    WebSocketClient<TestMessage, TestHandler, MemoryConnector, TestCreds, TestData, ()>;
+
+impl TestMessage {
+    pub fn new(kind: impl ToString, value: impl ToString) -> Self {
+        Self {
+            kind: kind.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl From<&str> for TestInfo {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Display for TestInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for TestRaw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "raw:{}", self.0)
+    }
+}
+
+impl MessageInformation for TestInfo {}
+
+impl RawMessage for TestRaw {}
+
+impl Credentials for TestCreds {}
+
+impl From<TestMessage> for Message {
+    fn from(value: TestMessage) -> Self {
+        Message::text(serde_json::to_string(&value).expect("Test messages are always serializable"))
+    }
+}
+
+impl MessageTransfer for TestMessage {
+    type Error = TestMessage;
+    type TransferError = TestMessage;
+    type Info = TestInfo;
+    type Raw = TestRaw;
+
+    fn info(&self) -> Self::Info {
+        TestInfo(self.kind.clone())
+    }
+
+    fn error(&self) -> Option<Self::Error> {
+        None
+    }
+
+    fn to_error(&self) -> Self::TransferError {
+        self.clone()
+    }
+
+    fn error_info(&self) -> Option<Vec<Self::Info>> {
+        None
+    }
+
+    fn priority(&self) -> MessagePriority {
+        match self.kind.as_str() {
+            "order" => MessagePriority::High,
+            _ => MessagePriority::Normal,
+        }
+    }
+
+    fn is_rate_limited(&self) -> bool {
+        self.kind == "rate_limited"
+    }
+
+    fn auth_failure(&self) -> Option<String> {
+        (self.kind == "not_authorized").then(|| self.value.clone())
+    }
+}
+
+#[async_trait]
+impl MessageHandler for TestHandler {
+    type Transfer = TestMessage;
+
+    async fn process_message(
+        &self,
+        message: &Message,
+        _previous: &Option<TestInfo>,
+        _sender: &SenderMessage,
+    ) -> BinaryOptionsResult<(Option<MessageType<TestMessage>>, bool)> {
+        match message {
+            Message::Text(text) => match text.strip_prefix("raw:") {
+                Some(raw) => Ok((Some(MessageType::Raw(TestRaw(raw.to_string()))), false)),
+                None => Ok((
+                    Some(MessageType::Transfer(serde_json::from_str(text)?)),
+                    false,
+                )),
+            },
+            Message::Close(_) => Ok((None, true)),
+            _ => Ok((None, false)),
+        }
+    }
+}
+
+#[async_trait]
+impl DataHandler for TestData {
+    type Transfer = TestMessage;
+
+    async fn update(&self, message: &TestMessage) -> BinaryOptionsResult<()> {
+        self.updates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(message.clone());
+        Ok(())
+    }
+}
+
+impl TestData {
+    pub fn updates(&self) -> Vec<TestMessage> {
+        self.updates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[async_trait]
+impl Middleware<TestMessage> for Rewrite {
+    async fn on_update(&self, message: TestMessage) -> BinaryOptionsResult<Vec<TestMessage>> {
+        Ok(match message.kind.as_str() {
+            "drop" => vec![],
+            "modify" => vec![TestMessage::new("modify", message.value.to_uppercase())],
+            "inject" => vec![message, TestMessage::new("extra", "")],
+            _ => vec![message],
+        })
+    }
+}
+
+#[async_trait]
+impl Connect for MemoryConnector {
+    type Creds = TestCreds;
+    type Stream = MemorySocket;
+
+    async fn connect<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig>(
+        &self,
+        creds: Self::Creds,
+        _config: &Config<T, Transfer, U>,
+    ) -> BinaryOptionsResult<Self::Stream> {
+        let (client, server) = duplex(64 * 1024);
+        let fail_writes = Arc::new(AtomicBool::new(false));
+        let connection = MemoryConnection {
+            creds,
+            socket: WebSocketStream::from_raw_socket(server, Role::Server, None).await,
+            fail_writes: fail_writes.clone(),
+        };
+        self.connections.send(connection).await.map_err(|e| {
+            BinaryOptionsToolsError::WebsocketRecievingConnectionError(e.to_string())
+        })?;
+        Ok(MemorySocket {
+            socket: WebSocketStream::from_raw_socket(client, Role::Client, None).await,
+            fail_writes,
+        })
+    }
+}
+
+/// Creates a connector and the server that recieves its connections
+pub fn memory() -> (MemoryConnector, MemoryServer) {
+    let (sender, reciever) = unbounded();
+    (
+        MemoryConnector {
+            connections: sender,
+        },
+        MemoryServer {
+            connections: reciever,
+        },
+    )
+}
+
+impl MemoryServer {
+    /// Waits for the next connection, fails if the client doesn't connect in time
+    pub async fn accept(&self) -> anyhow::Result<MemoryConnection> {
+        self.accept_within(WAIT)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("The client didn't connect"))
+    }
+
+    /// Next connection opened in less than `wait`
+    pub async fn accept_within(&self, wait: Duration) -> Option<MemoryConnection> {
+        tokio::time::timeout(wait, self.connections.recv())
+            .await
+            .ok()?
+            .ok()
+    }
+}
+
+impl MemoryConnection {
+    pub async fn send(&mut self, message: TestMessage) -> anyhow::Result<()> {
+        Ok(self.socket.send(message.into()).await?)
+    }
+
+    /// Next text message sent by the client, fails if the connection is closed or nothing arrives in time
+    pub async fn recv_text(&mut self) -> anyhow::Result<String> {
+        self.recv_text_within(WAIT)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("The client didn't send any message"))
+    }
+
+    /// Next text message sent in less than `wait`
+    pub async fn recv_text_within(&mut self, wait: Duration) -> Option<String> {
+        tokio::time::timeout(wait, async {
+            while let Some(Ok(message)) = self.socket.next().await {
+                if let Message::Text(text) = message {
+                    return Some(text.to_string());
+                }
+            }
+            None
+        })
+        .await
+        .ok()?
+    }
+
+    pub async fn recv(&mut self) -> anyhow::Result<TestMessage> {
+        Ok(serde_json::from_str(&self.recv_text().await?)?)
+    }
+
+    /// Every write of the client fails from now on, as if the connection broke while sending
+    pub fn fail_writes(&self) {
+        self.fail_writes.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Stream for MemorySocket {
+    type Item = Result<Message, TungsteniteError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.socket).poll_next(cx)
+    }
+}
+
+impl Sink<Message> for MemorySocket {
+    type Error = TungsteniteError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.fail_writes.load(Ordering::SeqCst) {
+            return Poll::Ready(Err(TungsteniteError::Io(io::ErrorKind::BrokenPipe.into())));
+        }
+        Pin::new(&mut self.socket).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        Pin::new(&mut self.socket).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.socket).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.socket).poll_close(cx)
+    }
+}
+
+/// Config used by the tests, the client is ready once it recieves a `ready` message
+pub fn test_config() -> ConfigBuilder<TestData, TestMessage, ()> {
+    _Config::new(WAIT, vec![], ())
+        .builder()
+        .bootstrap(HashSet::from([TestInfo::from("ready")]))
+        .reconnect_time(1)
+        .sleep_interval(1)
+}
+
+/// Starts a client on a new `MemoryServer` and makes its first connection ready.
+/// `WebSocketClient::init` connects once to check the connector before opening the connection it runs on, only the second one is returned.
+pub async fn start(
+    credentials: impl Into<CredentialStore<TestCreds>> + Send + 'static,
+    config: Config<TestData, TestMessage, ()>,
+) -> anyhow::Result<(TestClient, MemoryServer, MemoryConnection)> {
+    let (connector, server) = memory();
+    let data = Data::new_with_channels(TestData::default(), config.get_channels());
+    let client = tokio::spawn(TestClient::init(
+        credentials,
+        connector,
+        data,
+        TestHandler,
+        None,
+        config,
+    ));
+    server.accept().await?;
+    let mut connection = server.accept().await?;
+    connection.send(TestMessage::new("ready", "")).await?;
+    let client = client.await??;
+    Ok((client, server, connection))
+}
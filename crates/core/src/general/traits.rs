@@ -1,13 +1,14 @@
 use async_trait::async_trait;
 use core::{error, fmt, hash};
+use futures_util::{Sink, Stream};
 use serde::{Serialize, de::DeserializeOwned};
-use tokio::net::TcpStream;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message};
+use tokio_tungstenite::tungstenite::{Error as TungsteniteError, Message};
 
 use crate::error::BinaryOptionsResult;
 
 use super::{
     config::Config,
+    limiter::MessagePriority,
     send::SenderMessage,
     types::{Data, MessageType},
 };
@@ -16,7 +17,7 @@ use super::{
 pub trait Credentials: Clone + Send + Sync + Serialize + DeserializeOwned {}
 
 /// This trait is used to allow users to pass their own config struct to the `WebsocketClient`
-pub trait InnerConfig: DeserializeOwned + Clone + Send {}
+pub trait InnerConfig: DeserializeOwned + Clone + Send + Sync {}
 
 /// This trait allows users to pass their own way of storing and updating recieved data from the `websocket` connection
 #[async_trait]
@@ -43,7 +44,15 @@ pub trait WCallback: Send + Sync {
 
 /// Main entry point for the `WebsocketClient` struct, this trait is used by the client to handle incoming messages, return data to user and a lot more things
 pub trait MessageTransfer:
-    DeserializeOwned + Clone + Into<Message> + Send + Sync + error::Error + fmt::Debug + fmt::Display
+    DeserializeOwned
+    + Clone
+    + Into<Message>
+    + Send
+    + Sync
+    + error::Error
+    + fmt::Debug
+    + fmt::Display
+    + 'static
 {
     type Error: Into<Self> + Clone + error::Error;
     type TransferError: error::Error;
@@ -57,10 +66,34 @@ pub trait MessageTransfer:
     fn to_error(&self) -> Self::TransferError;
 
     fn error_info(&self) -> Option<Vec<Self::Info>>;
+
+    /// Queue used to send the message, higher priorities are sent first
+    fn priority(&self) -> MessagePriority {
+        MessagePriority::Normal
+    }
+
+    /// Checks if the message is a failure caused by sending messages too fast, it slows down the outgoing messages
+    fn is_rate_limited(&self) -> bool {
+        false
+    }
+
+    /// Checks if the message means the server rejected the credentials, returns the reason
+    fn auth_failure(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait MessageInformation:
-    Serialize + DeserializeOwned + Clone + Send + Sync + Eq + hash::Hash + fmt::Debug + fmt::Display
+    Serialize
+    + DeserializeOwned
+    + Clone
+    + Send
+    + Sync
+    + Eq
+    + hash::Hash
+    + fmt::Debug
+    + fmt::Display
+    + 'static
 {
 }
 
@@ -85,16 +118,37 @@ pub trait MessageHandler: Clone + Send + Sync {
     ) -> BinaryOptionsResult<(Option<MessageType<Self::Transfer>>, bool)>;
 }
 
+/// Any bidirectional stream of websocket messages the `WebSocketClient` can run on, every `WebSocketStream` from `tokio_tungstenite` implements it
+pub trait Transport:
+    Sink<Message, Error = TungsteniteError>
+    + Stream<Item = Result<Message, TungsteniteError>>
+    + Unpin
+    + Send
+    + 'static
+{
+}
+
+impl<S> Transport for S where
+    S: Sink<Message, Error = TungsteniteError>
+        + Stream<Item = Result<Message, TungsteniteError>>
+        + Unpin
+        + Send
+        + 'static
+{
+}
+
 #[async_trait]
 pub trait Connect: Clone + Send + Sync {
     type Creds: Credentials;
+    /// The connection returned by `connect`, usually a `WebSocketStream` but it can be any `Transport`
+    type Stream: Transport;
     // type Uris: Iterator<Item = String>;
 
     async fn connect<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig>(
         &self,
         creds: Self::Creds,
         config: &Config<T, Transfer, U>,
-    ) -> BinaryOptionsResult<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+    ) -> BinaryOptionsResult<Self::Stream>;
 }
 
 pub trait ValidatorTrait<T> {
@@ -110,4 +164,72 @@ where
     }
 }
 
-impl <T> InnerConfig for T where T: DeserializeOwned + Clone + Send {}
\ No newline at end of file
+impl<T> InnerConfig for T where T: DeserializeOwned + Clone + Send + Sync {}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::duplex;
+    use tokio_tungstenite::{WebSocketStream, tungstenite::protocol::Role};
+
+    use crate::general::{
+        send::ReconnectPolicy,
+        testing::{TestCreds, TestMessage, TestRaw, WAIT, start, test_config},
+    };
+
+    use super::*;
+
+    async fn echo<S: Transport>(mut transport: S) -> anyhow::Result<()> {
+        while let Some(msg) = transport.next().await {
+            transport.send(msg?).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_transport() -> anyhow::Result<()> {
+        let (client, server) = duplex(1024);
+        let server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        let mut client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        tokio::spawn(echo(server));
+
+        client.send(Message::text("42[\"ping\"]")).await?;
+        let msg = client.next().await.transpose()?;
+        assert_eq!(msg, Some(Message::text("42[\"ping\"]")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_over_memory_connector() -> anyhow::Result<()> {
+        let (client, _server, mut connection) =
+            start(TestCreds("secret".into()), test_config().build()?).await?;
+        assert_eq!(connection.creds, TestCreds("secret".into()));
+        assert!(client.is_ready());
+
+        let request = client.send_message_with_timout(
+            WAIT,
+            "Echo",
+            TestMessage::new("echo", "ping"),
+            "echo".into(),
+            Box::new(|msg: &TestMessage| msg.value == "pong"),
+            ReconnectPolicy::Resend,
+        );
+        let server = async {
+            assert_eq!(connection.recv().await?, TestMessage::new("echo", "ping"));
+            connection.send(TestMessage::new("echo", "pong")).await
+        };
+        let (response, served) = tokio::join!(request, server);
+        served?;
+        assert_eq!(response?, TestMessage::new("echo", "pong"));
+        assert!(
+            client
+                .data
+                .updates()
+                .contains(&TestMessage::new("echo", "pong"))
+        );
+
+        client.raw_send(TestRaw("hello".into())).await?;
+        assert_eq!(connection.recv_text().await?, "raw:hello");
+        Ok(())
+    }
+}
@@ -1,20 +1,21 @@
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    sync::{Arc, Mutex as SyncMutex, MutexGuard},
+};
 
-use async_channel::Receiver;
-use async_channel::Sender;
-use async_channel::bounded;
 use async_trait::async_trait;
-use tokio::sync::Mutex;
+use tokio::{runtime::Handle, sync::Mutex};
 
-use crate::constants::MAX_CHANNEL_CAPACITY;
 use crate::error::BinaryOptionsResult;
-use crate::error::BinaryOptionsToolsError;
 
+use super::channel::{ChannelConfig, ChannelsConfig, PolicyReceiver, PolicySender, channel};
 use super::config;
+use super::ready::Readiness;
 use super::send::SenderMessage;
 use super::traits::InnerConfig;
 use super::traits::WCallback;
-use super::traits::{DataHandler, MessageTransfer};
+use super::traits::{DataHandler, MessageTransfer, ValidatorTrait};
 
 #[derive(Clone)]
 pub enum MessageType<Transfer>
@@ -26,6 +27,37 @@ where
     Raw(Transfer::Raw),
 }
 
+pub type PendingRequests<Transfer> = Arc<
+    Mutex<
+        HashMap<
+            <Transfer as MessageTransfer>::Info,
+            (PolicySender<Transfer>, PolicyReceiver<Transfer>),
+        >,
+    >,
+>;
+
+/// Reciever of a pending request, the request is unregistered once every `PendingRequest` waiting for the same response is dropped
+pub struct PendingRequest<Transfer>
+where
+    Transfer: MessageTransfer,
+{
+    info: Transfer::Info,
+    reciever: Option<PolicyReceiver<Transfer>>,
+    requests: PendingRequests<Transfer>,
+}
+
+/// A raw message subscriber, only recieves the messages accepted by its filter
+struct RawSubscriber<Raw> {
+    sender: PolicySender<Raw>,
+    filter: Option<Box<dyn ValidatorTrait<Raw> + Send + Sync>>,
+}
+
+/// Raw message subscriptions, every subscriber gets its own bounded channel so it sees every matching message
+pub struct RawSubscriptions<Raw> {
+    config: ChannelConfig,
+    subscribers: SyncMutex<Vec<Arc<RawSubscriber<Raw>>>>,
+}
+
 #[derive(Clone)]
 pub struct Data<T, Transfer>
 where
@@ -33,19 +65,21 @@ where
     T: DataHandler,
 {
     inner: Arc<T>,
-    // #[allow(clippy::type_complexity)]
-    pub pending_requests:
-        Arc<Mutex<HashMap<Transfer::Info, (Sender<Transfer>, Receiver<Transfer>)>>>,
-    pub raw_requests: (Sender<Transfer::Raw>, Receiver<Transfer::Raw>),
+    pub pending_requests: PendingRequests<Transfer>,
+    pub raw_requests: Arc<RawSubscriptions<Transfer::Raw>>,
+    channels: ChannelsConfig,
+    readiness: Readiness<Transfer::Info>,
 }
 
 impl<T: DataHandler + Default, Transfer: MessageTransfer> Default for Data<T, Transfer> {
     fn default() -> Self {
-        let raw_requests = bounded(MAX_CHANNEL_CAPACITY);
+        let channels = ChannelsConfig::default();
         Self {
-            raw_requests,
+            raw_requests: Arc::new(RawSubscriptions::new(channels.raw)),
             inner: Default::default(),
             pending_requests: Default::default(),
+            channels,
+            readiness: Readiness::default(),
         }
     }
 }
@@ -88,36 +122,59 @@ where
     T: DataHandler<Transfer = Transfer>,
 {
     pub fn new(inner: T) -> Self {
-        let raw_requests = bounded(MAX_CHANNEL_CAPACITY);
+        Self::new_with_channels(inner, ChannelsConfig::default())
+    }
+
+    /// Creates a new `Data` instance using the capacities and policies of `channels` for the request and raw channels
+    pub fn new_with_channels(inner: T, channels: ChannelsConfig) -> Self {
         Self {
             inner: Arc::new(inner),
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
-            raw_requests,
+            raw_requests: Arc::new(RawSubscriptions::new(channels.raw)),
+            channels,
+            readiness: Readiness::default(),
         }
     }
 
-    pub fn raw_reciever(&self) -> Receiver<Transfer::Raw> {
-        self.raw_requests.1.clone()
+    pub fn channels(&self) -> ChannelsConfig {
+        self.channels
+    }
+
+    pub fn readiness(&self) -> &Readiness<Transfer::Info> {
+        &self.readiness
+    }
+
+    /// Subscribes to every raw message recieved from now on
+    pub fn raw_reciever(&self) -> PolicyReceiver<Transfer::Raw> {
+        self.raw_requests.subscribe(None)
     }
 
-    pub fn raw_sender(&self) -> Sender<Transfer::Raw> {
-        self.raw_requests.0.clone()
+    /// Subscribes to the raw messages accepted by `filter`, the rest never take space in the subscription buffer
+    pub fn raw_subscribe(
+        &self,
+        filter: Box<dyn ValidatorTrait<Transfer::Raw> + Send + Sync>,
+    ) -> PolicyReceiver<Transfer::Raw> {
+        self.raw_requests.subscribe(Some(filter))
     }
 
-    pub async fn add_request(&self, info: Transfer::Info) -> Receiver<Transfer> {
+    pub async fn add_request(&self, info: Transfer::Info) -> PendingRequest<Transfer> {
         let mut requests = self.pending_requests.lock().await;
         let (_, r) = requests
-            .entry(info)
-            .or_insert(bounded(MAX_CHANNEL_CAPACITY));
-        r.clone()
+            .entry(info.clone())
+            .or_insert_with(|| channel(self.channels.requests));
+        PendingRequest {
+            info,
+            reciever: Some(r.clone()),
+            requests: self.pending_requests.clone(),
+        }
     }
 
-    pub async fn sender(&self, info: Transfer::Info) -> Option<Sender<Transfer>> {
+    pub async fn sender(&self, info: Transfer::Info) -> Option<PolicySender<Transfer>> {
         let requests = self.pending_requests.lock().await;
         requests.get(&info).map(|(s, _)| s.clone())
     }
 
-    pub async fn get_sender(&self, message: &Transfer) -> Option<Vec<Sender<Transfer>>> {
+    pub async fn get_sender(&self, message: &Transfer) -> Option<Vec<PolicySender<Transfer>>> {
         let requests = self.pending_requests.lock().await;
         if let Some(infos) = &message.error_info() {
             return Some(
@@ -133,25 +190,111 @@ where
     }
 
     pub async fn raw_send(&self, msg: Transfer::Raw) -> BinaryOptionsResult<()> {
-        let sender = &self.raw_requests.0;
-        if sender.receiver_count() > 1 {
-            sender
-                .send(msg)
-                .await
-                .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string()))?;
-        }
-        Ok(())
+        self.raw_requests.send(msg).await
     }
 
     pub async fn update_data(
         &self,
         message: Transfer,
-    ) -> BinaryOptionsResult<Option<Vec<Sender<Transfer>>>> {
+    ) -> BinaryOptionsResult<Option<Vec<PolicySender<Transfer>>>> {
         self.inner.update(&message).await?;
         Ok(self.get_sender(&message).await)
     }
 }
 
+impl<Raw: Clone> RawSubscriptions<Raw> {
+    pub fn new(config: ChannelConfig) -> Self {
+        Self {
+            config,
+            subscribers: SyncMutex::new(Vec::new()),
+        }
+    }
+
+    pub fn subscribe(
+        &self,
+        filter: Option<Box<dyn ValidatorTrait<Raw> + Send + Sync>>,
+    ) -> PolicyReceiver<Raw> {
+        let (sender, reciever) = channel(self.config);
+        self.lock().push(Arc::new(RawSubscriber { sender, filter }));
+        reciever
+    }
+
+    /// Number of subscriptions that are still alive
+    pub fn len(&self) -> usize {
+        self.lock()
+            .iter()
+            .filter(|s| s.sender.receiver_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forwards `msg` to every subscriber that accepts it, subscribers whose recievers were dropped are removed
+    pub async fn send(&self, msg: Raw) -> BinaryOptionsResult<()> {
+        let subscribers = {
+            let mut subscribers = self.lock();
+            subscribers.retain(|s| s.sender.receiver_count() > 0);
+            subscribers.clone()
+        };
+        for subscriber in subscribers {
+            if subscriber.filter.as_ref().is_none_or(|f| f.validate(&msg)) {
+                // The reciever could be dropped at any point, that's not an error
+                let _ = subscriber.sender.send(msg.clone()).await;
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Arc<RawSubscriber<Raw>>>> {
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<Transfer: MessageTransfer> PendingRequest<Transfer> {
+    fn remove_unused(
+        requests: &mut HashMap<Transfer::Info, (PolicySender<Transfer>, PolicyReceiver<Transfer>)>,
+        info: &Transfer::Info,
+    ) {
+        // The map keeps one reciever alive, if it's the only one left nobody is waiting for the response
+        if requests
+            .get(info)
+            .is_some_and(|(s, _)| s.receiver_count() <= 1)
+        {
+            requests.remove(info);
+        }
+    }
+}
+
+impl<Transfer: MessageTransfer> Deref for PendingRequest<Transfer> {
+    type Target = PolicyReceiver<Transfer>;
+
+    fn deref(&self) -> &Self::Target {
+        self.reciever
+            .as_ref()
+            .expect("The reciever is only taken when dropping the request")
+    }
+}
+
+impl<Transfer: MessageTransfer> Drop for PendingRequest<Transfer> {
+    fn drop(&mut self) {
+        drop(self.reciever.take());
+        match self.requests.try_lock() {
+            Ok(mut requests) => Self::remove_unused(&mut requests, &self.info),
+            Err(_) => {
+                if let Ok(handle) = Handle::try_current() {
+                    let requests = self.requests.clone();
+                    let info = self.info.clone();
+                    handle.spawn(async move {
+                        Self::remove_unused(&mut *requests.lock().await, &info);
+                    });
+                }
+            }
+        }
+    }
+}
+
 impl<T, Transfer> Deref for Data<T, Transfer>
 where
     Transfer: MessageTransfer,
@@ -162,3 +305,77 @@ where
         &self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::BinaryOptionsToolsError,
+        general::{
+            channel::BackpressurePolicy,
+            testing::{TestData, TestInfo, TestMessage, WAIT},
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_raw_subscribers_see_every_message() -> anyhow::Result<()> {
+        let subscriptions =
+            RawSubscriptions::new(ChannelConfig::new(2, BackpressurePolicy::DropOldest));
+        let all = subscriptions.subscribe(None);
+        let even = subscriptions.subscribe(Some(Box::new(|msg: &u32| msg.is_multiple_of(2))));
+        let dropped = subscriptions.subscribe(None);
+        drop(dropped);
+        for i in 0..4 {
+            subscriptions.send(i).await?;
+        }
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(even.recv().await?, 0);
+        assert_eq!(even.recv().await?, 2);
+        assert!(matches!(
+            all.recv().await,
+            Err(BinaryOptionsToolsError::ChannelLagged(2))
+        ));
+        assert_eq!(all.recv().await?, 2);
+        assert_eq!(all.recv().await?, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dropped_requests_are_removed() -> anyhow::Result<()> {
+        let data = Data::<TestData, TestMessage>::new(TestData::default());
+        let first = data.add_request(TestInfo::from("echo")).await;
+        let second = data.add_request(TestInfo::from("echo")).await;
+        drop(first);
+        // Someone is still waiting for the response
+        assert!(data.sender(TestInfo::from("echo")).await.is_some());
+        drop(second);
+        assert!(data.sender(TestInfo::from("echo")).await.is_none());
+
+        // Dropped while the requests are locked, the entry is removed once the lock is released
+        let request = data.add_request(TestInfo::from("echo")).await;
+        let requests = data.pending_requests.lock().await;
+        drop(request);
+        drop(requests);
+        tokio::time::timeout(WAIT, async {
+            while data.sender(TestInfo::from("echo")).await.is_some() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_requests_are_removed() -> anyhow::Result<()> {
+        let data = Data::<TestData, TestMessage>::new(TestData::default());
+        let request = data.add_request(TestInfo::from("echo")).await;
+        let waiter = tokio::spawn(async move { request.recv().await });
+        tokio::task::yield_now().await;
+        assert!(data.sender(TestInfo::from("echo")).await.is_some());
+        waiter.abort();
+        assert!(waiter.await.is_err_and(|e| e.is_cancelled()));
+        assert!(data.sender(TestInfo::from("echo")).await.is_none());
+        Ok(())
+    }
+}
@@ -1,18 +1,15 @@
-use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError};
-
 use super::traits::{MessageTransfer, ValidatorTrait};
 
+/// Returns the error carried by `message` if it's a failure response
 pub fn validate<Transfer>(
-    validator: &Box<dyn ValidatorTrait<Transfer> + Send + Sync>,
+    validator: &(dyn ValidatorTrait<Transfer> + Send + Sync),
     message: Transfer,
-) -> BinaryOptionsResult<Option<Transfer>>
+) -> Result<Option<Transfer>, Transfer::Error>
 where
     Transfer: MessageTransfer,
 {
     if let Some(e) = message.error() {
-        Err(BinaryOptionsToolsError::WebSocketMessageError(
-            e.to_string(),
-        ))
+        Err(e)
     } else if validator.validate(&message) {
         Ok(Some(message))
     } else {
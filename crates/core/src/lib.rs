@@ -1,3 +1,6 @@
+// The `Config` derive refers to this crate by name, so it also works inside of it
+extern crate self as binary_options_tools_core;
+
 pub mod constants;
 pub mod error;
 pub mod general;
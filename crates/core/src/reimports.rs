@@ -1,4 +1,7 @@
+pub use arc_swap::ArcSwap;
 pub use tokio_tungstenite::{
-    Connector, MaybeTlsStream, WebSocketStream, connect_async_tls_with_config,
+    Connector, MaybeTlsStream, WebSocketStream, client_async_tls_with_config,
+    connect_async_tls_with_config,
     tungstenite::{Bytes, Message, handshake::client::generate_key, http::Request},
 };
+pub use tokio_util::sync::CancellationToken;
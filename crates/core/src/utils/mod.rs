@@ -1,2 +1,3 @@
+pub mod redact;
 pub mod time;
 pub mod tracing;
@@ -0,0 +1,120 @@
+use std::{
+    io::Write,
+    sync::{LazyLock, RwLock},
+};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Text written instead of a secret
+pub const REDACTED: &str = "<redacted>";
+
+/// Shorter values are too likely to appear in normal text to be scrubbed
+const MIN_SECRET_LEN: usize = 8;
+
+static SECRETS: LazyLock<RwLock<Vec<String>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Registers a value that must never appear in the logs, every `Redacted` writer replaces it with `REDACTED`
+pub fn register_secret(secret: impl ToString) {
+    let secret = secret.to_string();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+    let mut secrets = SECRETS.write().unwrap_or_else(|e| e.into_inner());
+    if !secrets.contains(&secret) {
+        secrets.push(secret);
+        // Longer secrets first so a secret that contains another one is fully replaced
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+}
+
+/// Replaces every registered secret in `text`
+pub fn redact(text: &str) -> String {
+    let secrets = SECRETS.read().unwrap_or_else(|e| e.into_inner());
+    secrets.iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret.as_str(), REDACTED)
+    })
+}
+
+/// Wraps the writer of a tracing layer so the registered secrets never reach it
+#[derive(Clone, Debug)]
+pub struct Redacted<M> {
+    inner: M,
+}
+
+/// Writer created by `Redacted`
+pub struct RedactedWriter<W> {
+    inner: W,
+}
+
+impl<M> Redacted<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacted<M> {
+    type Writer = RedactedWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactedWriter {
+            inner: self.inner.make_writer(),
+        }
+    }
+}
+
+impl<W: Write> Write for RedactedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Every event is written at once so a secret is never split between two calls
+        let text = String::from_utf8_lossy(buf);
+        self.inner.write_all(redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redacted_writer() -> anyhow::Result<()> {
+        register_secret("short");
+        register_secret("s3cr3t-session-token");
+        register_secret("s3cr3t-session");
+        assert_eq!(redact("short"), "short");
+
+        let buffer = Buffer::default();
+        let make_writer = Redacted::new({
+            let buffer = buffer.clone();
+            move || buffer.clone()
+        });
+        make_writer
+            .make_writer()
+            .write_all(br#"42["auth",{"session":"s3cr3t-session-token"}] and s3cr3t-session"#)?;
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone())?;
+        assert_eq!(
+            written,
+            r#"42["auth",{"session":"<redacted>"}] and <redacted>"#
+        );
+        Ok(())
+    }
+}
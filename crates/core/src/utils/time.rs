@@ -2,6 +2,7 @@ use std::time::Duration;
 
 use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError};
 use core::future::Future;
+use tokio_util::sync::CancellationToken;
 
 pub async fn timeout<F, T, E>(duration: Duration, future: F, task: String) -> BinaryOptionsResult<T>
 where
@@ -17,3 +18,38 @@ where
     };
     res
 }
+
+/// Runs the future until it finishes or the token is cancelled, in which case the future is dropped
+pub async fn cancellable<F, T, E>(
+    token: &CancellationToken,
+    future: F,
+    task: String,
+) -> Result<T, E>
+where
+    E: From<BinaryOptionsToolsError>,
+    F: Future<Output = Result<T, E>>,
+{
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(BinaryOptionsToolsError::Cancelled(task).into()),
+        result = future => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancellable() {
+        let token = CancellationToken::new();
+        let pending = std::future::pending::<BinaryOptionsResult<()>>();
+        token.cancel();
+        let res = cancellable(&token, pending, "Pending".into()).await;
+        assert!(matches!(res, Err(BinaryOptionsToolsError::Cancelled(task)) if task == "Pending"));
+
+        let token = CancellationToken::new();
+        let res = cancellable(&token, async { BinaryOptionsResult::Ok(1) }, "Ready".into()).await;
+        assert_eq!(res.ok(), Some(1));
+    }
+}
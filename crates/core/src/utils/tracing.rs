@@ -12,6 +12,8 @@ use tracing_subscriber::{
 
 use crate::{constants::MAX_LOGGING_CHANNEL_CAPACITY, general::stream::RecieverStream};
 
+use super::redact::Redacted;
+
 pub fn start_tracing(terminal: bool) -> anyhow::Result<()> {
     let error_logs = OpenOptions::new()
         .append(true)
@@ -24,12 +26,16 @@ pub fn start_tracing(terminal: bool) -> anyhow::Result<()> {
             // log-error file, to log the errors that arise
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(error_logs)
+                .with_writer(Redacted::new(error_logs))
                 .with_filter(LevelFilter::WARN),
         );
     if terminal {
-        sub.with(fmt::Layer::default().with_filter(LevelFilter::DEBUG))
-            .try_init()?;
+        sub.with(
+            fmt::Layer::default()
+                .with_writer(Redacted::new(std::io::stdout))
+                .with_filter(LevelFilter::DEBUG),
+        )
+        .try_init()?;
     } else {
         sub.try_init()?;
     }
@@ -49,12 +55,16 @@ pub fn start_tracing_leveled(terminal: bool, level: LevelFilter) -> anyhow::Resu
             // log-error file, to log the errors that arise
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(error_logs)
+                .with_writer(Redacted::new(error_logs))
                 .with_filter(LevelFilter::WARN),
         );
     if terminal {
-        sub.with(fmt::Layer::default().with_filter(level))
-            .try_init()?;
+        sub.with(
+            fmt::Layer::default()
+                .with_writer(Redacted::new(std::io::stdout))
+                .with_filter(level),
+        )
+        .try_init()?;
     } else {
         sub.try_init()?;
     }
@@ -102,7 +112,7 @@ pub fn stream_logs_layer(
     let layer = tracing_subscriber::fmt::layer::<Registry>()
         .json()
         .flatten_event(true)
-        .with_writer(writer)
+        .with_writer(Redacted::new(writer))
         .with_filter(level)
         .boxed();
     (layer, receiver)
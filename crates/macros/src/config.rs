@@ -10,6 +10,8 @@ enum FieldConfig {
     Optional,
     #[darling(rename = "iterator")]
     Iterator { dtype: Type, add_fn: Option<String> },
+    #[darling(rename = "default")]
+    Default,
 }
 
 #[derive(Debug, FromField)]
@@ -59,7 +61,7 @@ impl ToTokens for Config {
         tokens.extend(quote! {
             #[derive(Clone)]
             pub struct #new_name #generics {
-                #(#field_names: ::std::sync::Arc<::std::sync::Mutex<#field_type>>),*
+                #(#field_names: ::std::sync::Arc<::binary_options_tools_core::reimports::ArcSwap<#field_type>>),*
             }
 
             pub struct #builder_name #generics {
@@ -100,7 +102,7 @@ impl ToTokens for Config {
             impl #impl_generics From<#name #ty_generics> for #new_name #ty_generics #where_clause {
                 fn from(value: #name #ty_generics) -> Self {
                     Self {
-                        #(#field_names3: ::std::sync::Arc::new(::std::sync::Mutex::new(value.#field_names3))),*
+                        #(#field_names3: ::std::sync::Arc::new(::binary_options_tools_core::reimports::ArcSwap::from_pointee(value.#field_names3))),*
                     }
                 }
             }
@@ -113,15 +115,11 @@ impl ToTokens for Config {
                 }
             }
 
-            impl #impl_generics TryFrom<#new_name #ty_generics> for #name #ty_generics #where_clause {
-                type Error = ::anyhow::Error;
-
-                fn try_from(value: #new_name #ty_generics) -> ::std::result::Result<Self, Self::Error> {
-                    Ok(
-                        Self {
-                            #(#field_names5: value.#field_names5.lock().map_err(|e| ::anyhow::anyhow!("Poison error {e}"))?.clone()),*
-                        }
-                    )
+            impl #impl_generics From<#new_name #ty_generics> for #name #ty_generics #where_clause {
+                fn from(value: #new_name #ty_generics) -> Self {
+                    Self {
+                        #(#field_names5: value.#field_names5.load_full().as_ref().clone()),*
+                    }
                 }
             }
 
@@ -146,6 +144,7 @@ impl ToTokens for ConfigField {
         let dtype = &self.ty;
         let set_name = Ident::new(&format!("set_{}", name), name.span());
         let get_name = Ident::new(&format!("get_{}", name), name.span());
+        let load_name = Ident::new(&format!("load_{}", name), name.span());
         let extra = if let Some(FieldConfig::Iterator { dtype, add_fn }) = &self.extra {
             let add_name = Ident::new(&format!("add_{}", name), name.span());
             let add_fn = if let Some(add) = add_fn {
@@ -154,11 +153,12 @@ impl ToTokens for ConfigField {
                 Ident::new("push", name.span())
             };
             quote! {
-                pub fn #add_name(&self, value: #dtype) -> ::anyhow::Result<()> {
-                    let mut field = self.#name.lock().map_err(|e| ::anyhow::anyhow!("Poison error {e}"))?;
-                    field.#add_fn(value);
-                    Ok(())
-
+                pub fn #add_name(&self, value: #dtype) {
+                    self.#name.rcu(|field| {
+                        let mut field = ::std::clone::Clone::clone(field.as_ref());
+                        field.#add_fn(::std::clone::Clone::clone(&value));
+                        field
+                    });
                 }
             }
         } else {
@@ -167,14 +167,17 @@ impl ToTokens for ConfigField {
         tokens.extend(quote! {
             #extra
 
-            pub fn #set_name(&self, value: #dtype) -> ::anyhow::Result<()> {
-                let mut field = self.#name.lock().map_err(|e| ::anyhow::anyhow!("Poison error {e}"))?;
-                *field = value;
-                Ok(())
+            pub fn #set_name(&self, value: #dtype) {
+                self.#name.store(::std::sync::Arc::new(value));
+            }
+
+            pub fn #get_name(&self) -> #dtype {
+                ::std::clone::Clone::clone(self.#name.load().as_ref())
             }
 
-            pub fn #get_name(&self) -> ::anyhow::Result<#dtype> {
-                Ok(self.#name.lock().map_err(|e| ::anyhow::anyhow!("Poison error {e}"))?.clone())
+            /// Snapshot of the current value, it never waits for writers
+            pub fn #load_name(&self) -> ::std::sync::Arc<#dtype> {
+                self.#name.load_full()
             }
         });
     }
@@ -207,18 +210,23 @@ impl ConfigField {
             match extra {
                 FieldConfig::Iterator { .. } => {
                     quote! {
-                        #name: ::std::sync::Arc::new(::std::sync::Mutex::new(value.#name.unwrap_or_else(::std::default::Default::default)))
+                        #name: ::std::sync::Arc::new(::binary_options_tools_core::reimports::ArcSwap::from_pointee(value.#name.unwrap_or_else(::std::default::Default::default)))
                     }
                 }
                 FieldConfig::Optional => {
                     quote! {
-                        #name: ::std::sync::Arc::new(::std::sync::Mutex::new(value.#name.unwrap_or(::std::option::Option::None)))
+                        #name: ::std::sync::Arc::new(::binary_options_tools_core::reimports::ArcSwap::from_pointee(value.#name.unwrap_or(::std::option::Option::None)))
+                    }
+                }
+                FieldConfig::Default => {
+                    quote! {
+                        #name: ::std::sync::Arc::new(::binary_options_tools_core::reimports::ArcSwap::from_pointee(value.#name.unwrap_or_default()))
                     }
                 }
             }
         } else {
             quote! {
-                #name: ::std::sync::Arc::new(::std::sync::Mutex::new(value.#name.ok_or(::anyhow::anyhow!("Option for field '{}' was None", #name_str))?))
+                #name: ::std::sync::Arc::new(::binary_options_tools_core::reimports::ArcSwap::from_pointee(value.#name.ok_or(::anyhow::anyhow!("Option for field '{}' was None", #name_str))?))
             }
         }
     }
@@ -66,6 +66,8 @@ impl ToTokens for Regions {
         let reals: Vec<&Region> = regions.iter().filter_map(|r| r.get_real()).collect();
         let reals_stream = reals.iter().map(|r| r.to_stream());
         let reals_url = reals.iter().map(|r| r.url());
+        let names = regions.iter().map(|r| r.name.to_uppercase());
+        let by_name = regions.iter().map(|r| r.to_stream());
 
         tokens.extend(quote! {
             #(#regions)*
@@ -85,6 +87,14 @@ impl ToTokens for Regions {
             pub fn regions_str() -> Vec<&'static str> {
                 ::std::vec::Vec::from([#(#reals_url),*])
             }
+
+            /// Region with the given name, case insensitive
+            pub fn by_name(name: &str) -> ::std::option::Option<(&'static str, f64, f64)> {
+                match name.to_uppercase().as_str() {
+                    #(#names => ::std::option::Option::Some(#by_name),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
         });
     }
 }